@@ -1,25 +1,38 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
 use std::fmt::{Debug, Display, Formatter, Result as fmtResult};
 pub struct Graph<T> {
     nodes: Vec<Node<T>>,
-    edges: Vec<Option<isize>>,
-    buf_len: usize,
+    edges: Vec<Vec<(usize, isize)>>,
+    /**
+     * The incoming edges of every node as (neighbor index, weight) pairs,
+     * kept so that searches can walk the edges backwards
+     */
+    incoming: Vec<Vec<(usize, isize)>>,
+}
+
+/**
+ * A path through the graph found by a search.
+ */
+pub struct Route {
+    pub nodes: Vec<usize>,
+    /**
+     * The summed up weights of the edges along the path
+     */
+    pub length: isize,
+    pub expanded: usize,
 }
 
 impl<T> Graph<T> {
     pub fn new() -> Graph<T> {
-        let buf_len = 1000;
-        Graph {
-            nodes: Vec::new(),
-            edges: vec![None; buf_len * buf_len],
-            buf_len: buf_len,
-        }
+        Self::new_pre_length(1000)
     }
 
     pub fn new_pre_length(buf_len: usize) -> Graph<T> {
         Graph {
-            nodes: Vec::new(),
-            edges: vec![None; buf_len * buf_len],
-            buf_len: buf_len,
+            nodes: Vec::with_capacity(buf_len),
+            edges: Vec::with_capacity(buf_len),
+            incoming: Vec::with_capacity(buf_len),
         }
     }
 
@@ -32,54 +45,460 @@ impl<T> Graph<T> {
     }
 
     pub fn get_edge_amount(&self) -> usize {
-        self.edges.iter().filter(|e| e.is_some()).count()
+        self.edges.iter().map(|edges| edges.len()).sum()
     }
 
     pub fn add_node(&mut self, element: T) -> usize {
         self.nodes.push(Node {
-            element: element,
+            element,
             visited: false,
         });
-        if self.nodes.len() % self.buf_len == 0 && self.nodes.len() > 0 {
-            let extension_length =
-                (self.nodes.len() + self.buf_len).pow(2) - (self.nodes.len()).pow(2);
-
-            let mut extension: Vec<Option<isize>> = vec![None; extension_length];
-            let old_row_length = self.nodes.len() - 1;
-            self.edges.append(&mut extension);
-            for i in (0..(self.edges.len() - extension_length)).rev() {
-                let y = (i - (i % old_row_length)) / old_row_length;
-                self.edges[i + y] = self.edges[i];
-                if y > 0 {
-                    self.edges[i] = None
-                }
-            }
-            dbg!(self.edges.len());
-        }
+        self.edges.push(Vec::new());
+        self.incoming.push(Vec::new());
         self.nodes.len() - 1
     }
 
     pub fn get_neighbors(&self, from: usize) -> Vec<usize> {
-        let mut vec: Vec<usize> = Vec::new();
-        for to in 0..self.nodes.len() {
-            if self.get_edge(from, to).is_some() {
-                vec.push(to);
-            }
-        }
-        vec
+        self.edges[from].iter().map(|&(to, _)| to).collect()
+    }
+
+    /**
+     * # Returns
+     * The outgoing edges of a node as (neighbor index, weight) pairs
+     */
+    pub fn get_weighted_neighbors(&self, from: usize) -> &[(usize, isize)] {
+        &self.edges[from]
+    }
+
+    /**
+     * # Returns
+     * The incoming edges of a node as (neighbor index, weight) pairs
+     */
+    pub fn get_weighted_predecessors(&self, to: usize) -> &[(usize, isize)] {
+        &self.incoming[to]
     }
 
     pub fn get_edge(&self, from: usize, to: usize) -> Option<isize> {
-        self.edges[(from * self.nodes.len() + to)]
+        self.edges[from]
+            .iter()
+            .find(|&&(index, _)| index == to)
+            .map(|&(_, weight)| weight)
     }
 
     pub fn set_mono_edge(&mut self, from: usize, to: usize, weight: isize) {
-        self.edges[(from * self.nodes.len() + to)] = Some(weight);
+        match self.edges[from].iter_mut().find(|(index, _)| *index == to) {
+            Some(edge) => edge.1 = weight,
+            None => self.edges[from].push((to, weight)),
+        }
+        match self.incoming[to]
+            .iter_mut()
+            .find(|(index, _)| *index == from)
+        {
+            Some(edge) => edge.1 = weight,
+            None => self.incoming[to].push((from, weight)),
+        }
     }
 
     pub fn set_bi_edge(&mut self, from: usize, to: usize, weight: isize) {
-        self.edges[(to * self.nodes.len() + from)] = Some(weight);
-        self.edges[(from * self.nodes.len() + to)] = Some(weight);
+        self.set_mono_edge(from, to, weight);
+        self.set_mono_edge(to, from, weight);
+    }
+
+    /**
+     * Removes every edge leaving or entering the node.
+     */
+    pub fn clear_edges(&mut self, index: usize) {
+        for (to, _) in std::mem::take(&mut self.edges[index]) {
+            self.incoming[to].retain(|&(from, _)| from != index);
+        }
+        for (from, _) in std::mem::take(&mut self.incoming[index]) {
+            self.edges[from].retain(|&(to, _)| to != index);
+        }
+    }
+
+    /**
+     * Removes the node together with its edges by moving the last node into
+     * its place, so only the index of the last node changes.
+     * # Returns
+     * The element of the removed node
+     */
+    pub fn swap_remove_node(&mut self, index: usize) -> T {
+        self.clear_edges(index);
+        let last = self.nodes.len() - 1;
+        if index != last {
+            let (outgoing, incoming) = (self.edges[last].clone(), self.incoming[last].clone());
+            for (to, _) in outgoing {
+                for edge in self.incoming[to]
+                    .iter_mut()
+                    .filter(|(from, _)| *from == last)
+                {
+                    edge.0 = index;
+                }
+            }
+            for (from, _) in incoming {
+                for edge in self.edges[from].iter_mut().filter(|(to, _)| *to == last) {
+                    edge.0 = index;
+                }
+            }
+        }
+        self.edges.swap_remove(index);
+        self.incoming.swap_remove(index);
+        self.nodes.swap_remove(index).element
+    }
+
+    /**
+     * Labels every node with the index of the connected component it belongs
+     * to, edges are treated as undirected.
+     * # Returns
+     * The amount of components and the label of every node, components are
+     * numbered in the order of their first node
+     */
+    pub fn get_components(&self) -> (usize, Vec<usize>) {
+        let mut parents: Vec<usize> = (0..self.nodes.len()).collect();
+        fn find(parents: &mut [usize], mut index: usize) -> usize {
+            while parents[index] != index {
+                parents[index] = parents[parents[index]];
+                index = parents[index];
+            }
+            index
+        }
+        for from in 0..self.nodes.len() {
+            for &(to, _) in &self.edges[from] {
+                let (a, b) = (find(&mut parents, from), find(&mut parents, to));
+                if a != b {
+                    parents[a.max(b)] = a.min(b);
+                }
+            }
+        }
+        let mut labels: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let mut amount = 0;
+        let mut components = Vec::with_capacity(self.nodes.len());
+        for index in 0..self.nodes.len() {
+            let root = find(&mut parents, index);
+            let label = *labels[root].get_or_insert_with(|| {
+                amount += 1;
+                amount - 1
+            });
+            components.push(label);
+        }
+        (amount, components)
+    }
+
+    /**
+     * Runs Dijkstra from the start over the whole graph without entering the
+     * blocked nodes.
+     * # Returns
+     * The length of the shortest path to every node, None for the nodes that
+     * cannot be reached
+     */
+    pub fn get_distances(
+        &self,
+        start: usize,
+        is_blocked: &dyn Fn(usize) -> bool,
+    ) -> Vec<Option<isize>> {
+        let mut distances: Vec<Option<isize>> = vec![None; self.nodes.len()];
+        let mut heap: BinaryHeap<Reverse<(isize, usize)>> = BinaryHeap::new();
+        distances[start] = Some(0);
+        heap.push(Reverse((0, start)));
+        while let Some(Reverse((distance, node_index))) = heap.pop() {
+            if distances[node_index] != Some(distance) {
+                continue;
+            }
+            for &(neighbor, weight) in &self.edges[node_index] {
+                if is_blocked(neighbor) {
+                    continue;
+                }
+                let neighbor_distance = distance + weight;
+                if distances[neighbor].is_none_or(|known| neighbor_distance < known) {
+                    distances[neighbor] = Some(neighbor_distance);
+                    heap.push(Reverse((neighbor_distance, neighbor)));
+                }
+            }
+        }
+        distances
+    }
+
+    /**
+     * Runs a BFS forwards from the start and backwards from the goal, always
+     * growing the smaller of both frontiers by a whole level, until they meet.
+     * Blocked nodes are never entered.
+     * # Returns
+     * The path with the fewest edges between both nodes, None if there is none
+     */
+    pub fn bidirectional_bfs(
+        &self,
+        start: usize,
+        goal: usize,
+        is_blocked: &dyn Fn(usize) -> bool,
+    ) -> Option<Route> {
+        let node_amount = self.nodes.len();
+        let mut depths: [Vec<Option<usize>>; 2] =
+            [vec![None; node_amount], vec![None; node_amount]];
+        let mut parents: [Vec<Option<usize>>; 2] =
+            [vec![None; node_amount], vec![None; node_amount]];
+        let mut frontiers: [Vec<usize>; 2] = [vec![start], vec![goal]];
+        let mut expanded = 0;
+        depths[0][start] = Some(0);
+        depths[1][goal] = Some(0);
+        if start == goal {
+            return Some(self.join_route(start, &parents, expanded));
+        }
+        while !frontiers[0].is_empty() && !frontiers[1].is_empty() {
+            let side = if frontiers[0].len() <= frontiers[1].len() {
+                0
+            } else {
+                1
+            };
+            let mut next_frontier: Vec<usize> = Vec::new();
+            let mut meeting: Option<(usize, usize)> = None;
+            for &node_index in &frontiers[side] {
+                expanded += 1;
+                let depth = depths[side][node_index].unwrap() + 1;
+                let edges = match side {
+                    0 => &self.edges[node_index],
+                    _ => &self.incoming[node_index],
+                };
+                for &(neighbor, _) in edges {
+                    if depths[side][neighbor].is_some() || is_blocked(neighbor) {
+                        continue;
+                    }
+                    depths[side][neighbor] = Some(depth);
+                    parents[side][neighbor] = Some(node_index);
+                    next_frontier.push(neighbor);
+                    if let Some(other_depth) = depths[1 - side][neighbor] {
+                        if meeting.is_none_or(|(_, best)| depth + other_depth < best) {
+                            meeting = Some((neighbor, depth + other_depth));
+                        }
+                    }
+                }
+            }
+            if let Some((meeting_index, _)) = meeting {
+                return Some(self.join_route(meeting_index, &parents, expanded));
+            }
+            frontiers[side] = next_frontier;
+        }
+        None
+    }
+
+    /**
+     * Runs Dijkstra forwards from the start and backwards from the goal,
+     * always expanding the side with the closer node. The searches stop once
+     * the closest nodes of both sides are together at least as far apart as
+     * the shortest connection found so far, no path through unexpanded nodes
+     * can be shorter then. Weights must not be negative and blocked nodes are
+     * never entered.
+     * # Returns
+     * The shortest path between both nodes, None if there is none
+     */
+    pub fn bidirectional_dijkstra(
+        &self,
+        start: usize,
+        goal: usize,
+        is_blocked: &dyn Fn(usize) -> bool,
+    ) -> Option<Route> {
+        let node_amount = self.nodes.len();
+        let mut distances: [Vec<Option<isize>>; 2] =
+            [vec![None; node_amount], vec![None; node_amount]];
+        let mut parents: [Vec<Option<usize>>; 2] =
+            [vec![None; node_amount], vec![None; node_amount]];
+        let mut heaps: [BinaryHeap<Reverse<(isize, usize)>>; 2] =
+            [BinaryHeap::new(), BinaryHeap::new()];
+        let mut best: Option<(isize, usize)> = None;
+        let mut expanded = 0;
+        distances[0][start] = Some(0);
+        distances[1][goal] = Some(0);
+        heaps[0].push(Reverse((0, start)));
+        heaps[1].push(Reverse((0, goal)));
+        if start == goal {
+            best = Some((0, start));
+        }
+        while let (Some(&Reverse((forward, _))), Some(&Reverse((backward, _)))) =
+            (heaps[0].peek(), heaps[1].peek())
+        {
+            if best.is_some_and(|(length, _)| forward + backward >= length) {
+                break;
+            }
+            let side = if forward <= backward { 0 } else { 1 };
+            let Reverse((distance, node_index)) = heaps[side].pop().unwrap();
+            if distances[side][node_index] != Some(distance) {
+                continue;
+            }
+            expanded += 1;
+            let edges = match side {
+                0 => &self.edges[node_index],
+                _ => &self.incoming[node_index],
+            };
+            for &(neighbor, weight) in edges {
+                if is_blocked(neighbor) {
+                    continue;
+                }
+                let neighbor_distance = distance + weight;
+                if distances[side][neighbor].is_none_or(|known| neighbor_distance < known) {
+                    distances[side][neighbor] = Some(neighbor_distance);
+                    parents[side][neighbor] = Some(node_index);
+                    heaps[side].push(Reverse((neighbor_distance, neighbor)));
+                }
+                if let Some(other_distance) = distances[1 - side][neighbor] {
+                    let length = neighbor_distance + other_distance;
+                    if best.is_none_or(|(known, _)| length < known) {
+                        best = Some((length, neighbor));
+                    }
+                }
+            }
+        }
+        best.map(|(_, meeting_index)| self.join_route(meeting_index, &parents, expanded))
+    }
+
+    /**
+     * Yen's algorithm: every further path branches off one of the paths found
+     * before it at some node, the branch is searched with Dijkstra while the
+     * nodes before the branching node and the edges taken there by earlier
+     * paths with the same beginning are blocked. The given blocked nodes are
+     * never entered by any path.
+     * # Returns
+     * Up to k paths from the start to the goal that do not visit any node
+     * twice, shortest first. The expanded count of a path sums up every
+     * Dijkstra run until it was found.
+     */
+    pub fn get_k_shortest_paths(
+        &self,
+        start: usize,
+        goal: usize,
+        k: usize,
+        is_blocked: &dyn Fn(usize) -> bool,
+    ) -> Vec<Route> {
+        let mut expanded = 0;
+        let locked_nodes: Vec<bool> = (0..self.nodes.len()).map(is_blocked).collect();
+        let mut routes: Vec<Route> = Vec::new();
+        match self.get_restricted_route(start, goal, &locked_nodes, &HashSet::new(), &mut expanded)
+        {
+            Some((nodes, length)) if k > 0 => routes.push(Route {
+                nodes,
+                length,
+                expanded,
+            }),
+            _ => return routes,
+        }
+        let mut candidates: BinaryHeap<Reverse<(isize, Vec<usize>)>> = BinaryHeap::new();
+        let mut known: HashSet<Vec<usize>> = HashSet::new();
+        known.insert(routes[0].nodes.clone());
+        while routes.len() < k {
+            let previous = routes.last().unwrap().nodes.clone();
+            let mut blocked_nodes = locked_nodes.clone();
+            let mut root_length = 0;
+            for spur_index in 0..previous.len() - 1 {
+                let root = &previous[..=spur_index];
+                let blocked_edges: HashSet<(usize, usize)> = routes
+                    .iter()
+                    .filter(|route| {
+                        route.nodes.len() > spur_index + 1 && route.nodes.starts_with(root)
+                    })
+                    .map(|route| (route.nodes[spur_index], route.nodes[spur_index + 1]))
+                    .collect();
+                if let Some((spur, spur_length)) = self.get_restricted_route(
+                    previous[spur_index],
+                    goal,
+                    &blocked_nodes,
+                    &blocked_edges,
+                    &mut expanded,
+                ) {
+                    let mut nodes = root[..spur_index].to_vec();
+                    nodes.extend(spur);
+                    if known.insert(nodes.clone()) {
+                        candidates.push(Reverse((root_length + spur_length, nodes)));
+                    }
+                }
+                blocked_nodes[previous[spur_index]] = true;
+                root_length += self
+                    .get_edge(previous[spur_index], previous[spur_index + 1])
+                    .unwrap();
+            }
+            match candidates.pop() {
+                Some(Reverse((length, nodes))) => routes.push(Route {
+                    nodes,
+                    length,
+                    expanded,
+                }),
+                None => break,
+            }
+        }
+        routes
+    }
+
+    /**
+     * Dijkstra that may neither enter the blocked nodes nor use the blocked
+     * edges.
+     * # Returns
+     * The nodes of the shortest path and its length
+     */
+    fn get_restricted_route(
+        &self,
+        start: usize,
+        goal: usize,
+        blocked_nodes: &[bool],
+        blocked_edges: &HashSet<(usize, usize)>,
+        expanded: &mut usize,
+    ) -> Option<(Vec<usize>, isize)> {
+        let mut distances: Vec<Option<isize>> = vec![None; self.nodes.len()];
+        let mut parents: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let mut heap: BinaryHeap<Reverse<(isize, usize)>> = BinaryHeap::new();
+        distances[start] = Some(0);
+        heap.push(Reverse((0, start)));
+        while let Some(Reverse((distance, node_index))) = heap.pop() {
+            if distances[node_index] != Some(distance) {
+                continue;
+            }
+            *expanded += 1;
+            if node_index == goal {
+                let mut nodes = vec![goal];
+                while let Some(parent) = parents[*nodes.last().unwrap()] {
+                    nodes.push(parent);
+                }
+                nodes.reverse();
+                return Some((nodes, distance));
+            }
+            for &(neighbor, weight) in &self.edges[node_index] {
+                if blocked_nodes[neighbor] || blocked_edges.contains(&(node_index, neighbor)) {
+                    continue;
+                }
+                let neighbor_distance = distance + weight;
+                if distances[neighbor].is_none_or(|known| neighbor_distance < known) {
+                    distances[neighbor] = Some(neighbor_distance);
+                    parents[neighbor] = Some(node_index);
+                    heap.push(Reverse((neighbor_distance, neighbor)));
+                }
+            }
+        }
+        None
+    }
+
+    /**
+     * Joins the paths of a forward and a backward search at the node where
+     * they met.
+     */
+    fn join_route(
+        &self,
+        meeting_index: usize,
+        parents: &[Vec<Option<usize>>; 2],
+        expanded: usize,
+    ) -> Route {
+        let mut nodes = vec![meeting_index];
+        while let Some(parent) = parents[0][*nodes.last().unwrap()] {
+            nodes.push(parent);
+        }
+        nodes.reverse();
+        while let Some(child) = parents[1][*nodes.last().unwrap()] {
+            nodes.push(child);
+        }
+        let length = nodes
+            .windows(2)
+            .map(|pair| self.get_edge(pair[0], pair[1]).unwrap())
+            .sum();
+        Route {
+            nodes,
+            length,
+            expanded,
+        }
     }
 }
 
@@ -93,7 +512,7 @@ impl<T> Debug for Graph<T> {
                     None => "X",
                 });
             }
-            result.push_str("\n");
+            result.push('\n');
         }
         write!(f, "{}", result)
     }
@@ -109,7 +528,7 @@ impl<T> Display for Graph<T> {
                     None => "🟥",
                 });
             }
-            result.push_str("\n");
+            result.push('\n');
         }
         write!(f, "{}", result)
     }
@@ -131,3 +550,247 @@ impl<T> Debug for Node<T> {
         write!(f, "Node`{{`..., {}`}}`", self.visited)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_path_graph(length: usize) -> Graph<usize> {
+        let mut graph: Graph<usize> = Graph::new();
+        for index in 0..length {
+            graph.add_node(index);
+        }
+        for index in 1..length {
+            graph.set_bi_edge(index - 1, index, index as isize);
+        }
+        graph
+    }
+
+    #[test]
+    fn edges_are_stored_in_both_directions() {
+        let mut graph = get_path_graph(3);
+        graph.set_mono_edge(2, 0, 7);
+        assert_eq!(graph.get_edge(0, 1), Some(1));
+        assert_eq!(graph.get_edge(1, 0), Some(1));
+        assert_eq!(graph.get_edge(0, 2), None);
+        assert_eq!(graph.get_edge(2, 0), Some(7));
+        assert_eq!(graph.get_weighted_predecessors(0), &[(1, 1), (2, 7)]);
+        assert_eq!(graph.get_edge_amount(), 5);
+        graph.set_bi_edge(0, 1, 4);
+        assert_eq!(graph.get_edge(1, 0), Some(4));
+        assert_eq!(graph.get_edge_amount(), 5);
+    }
+
+    #[test]
+    fn clear_edges_removes_incoming_and_outgoing_edges() {
+        let mut graph = get_path_graph(3);
+        graph.clear_edges(1);
+        assert_eq!(graph.get_edge_amount(), 0);
+        assert!(graph.get_weighted_predecessors(0).is_empty());
+        assert!(graph.get_weighted_predecessors(2).is_empty());
+    }
+
+    #[test]
+    fn swap_remove_node_renumbers_the_edges_of_the_last_node() {
+        let mut graph = get_path_graph(4);
+        assert_eq!(graph.swap_remove_node(1), 1);
+        assert_eq!(graph.get_node_amount(), 3);
+        assert_eq!(graph.get_node(1).element, 3);
+        assert_eq!(graph.get_edge(1, 2), Some(3));
+        assert_eq!(graph.get_edge(2, 1), Some(3));
+        assert_eq!(graph.get_edge(0, 1), None);
+        assert_eq!(graph.get_edge_amount(), 2);
+    }
+
+    #[test]
+    fn components_are_numbered_by_their_first_node() {
+        let mut graph = get_path_graph(5);
+        graph.clear_edges(2);
+        graph.set_mono_edge(4, 2, 1);
+        assert_eq!(graph.get_components(), (2, vec![0, 0, 1, 1, 1]));
+    }
+
+    #[test]
+    fn distances_follow_the_weights() {
+        let mut graph = get_path_graph(4);
+        graph.set_mono_edge(0, 3, 5);
+        assert_eq!(
+            graph.get_distances(0, &|_| false),
+            vec![Some(0), Some(1), Some(3), Some(5)]
+        );
+        assert_eq!(
+            graph.get_distances(0, &|node_index| node_index == 3),
+            vec![Some(0), Some(1), Some(3), None]
+        );
+        graph.clear_edges(1);
+        assert_eq!(
+            graph.get_distances(1, &|_| false),
+            vec![None, Some(0), None, None]
+        );
+    }
+
+    /**
+     * Connects random pairs of nodes with one way edges of weights from 1 to
+     * 9, the same seed always gives the same graph
+     */
+    fn get_random_graph(node_amount: usize, edge_amount: usize, seed: u64) -> Graph<usize> {
+        let mut random = seed.max(1);
+        let mut next = move |below: usize| {
+            random ^= random << 13;
+            random ^= random >> 7;
+            random ^= random << 17;
+            random as usize % below
+        };
+        let mut graph: Graph<usize> = Graph::new();
+        for index in 0..node_amount {
+            graph.add_node(index);
+        }
+        for _ in 0..edge_amount {
+            let (from, to) = (next(node_amount), next(node_amount));
+            if from != to {
+                graph.set_mono_edge(from, to, 1 + next(9) as isize);
+            }
+        }
+        graph
+    }
+
+    /**
+     * # Returns
+     * The same graph with a weight of 1 on every edge
+     */
+    fn get_unweighted(graph: &Graph<usize>) -> Graph<usize> {
+        let mut unweighted: Graph<usize> = Graph::new();
+        for index in 0..graph.get_node_amount() {
+            unweighted.add_node(index);
+        }
+        for from in 0..graph.get_node_amount() {
+            for &(to, _) in graph.get_weighted_neighbors(from) {
+                unweighted.set_mono_edge(from, to, 1);
+            }
+        }
+        unweighted
+    }
+
+    #[test]
+    fn bidirectional_searches_count_edges_or_weights() {
+        let mut graph = get_path_graph(4);
+        graph.set_mono_edge(0, 3, 20);
+        let fewest = graph.bidirectional_bfs(0, 3, &|_| false).unwrap();
+        assert_eq!((fewest.nodes, fewest.length), (vec![0, 3], 20));
+        let lightest = graph.bidirectional_dijkstra(0, 3, &|_| false).unwrap();
+        assert_eq!((lightest.nodes, lightest.length), (vec![0, 1, 2, 3], 6));
+        let backwards = graph.bidirectional_dijkstra(3, 0, &|_| false).unwrap();
+        assert_eq!((backwards.nodes, backwards.length), (vec![3, 2, 1, 0], 6));
+        assert!(graph
+            .bidirectional_bfs(0, 3, &|node_index| node_index == 3)
+            .is_none());
+        let detour = graph
+            .bidirectional_bfs(0, 2, &|node_index| node_index == 3)
+            .unwrap();
+        assert_eq!(detour.nodes, vec![0, 1, 2]);
+        let alone = graph.bidirectional_dijkstra(1, 1, &|_| false).unwrap();
+        assert_eq!((alone.nodes, alone.length), (vec![1], 0));
+    }
+
+    #[test]
+    fn bidirectional_searches_agree_with_dijkstra() {
+        for seed in 1..=40 {
+            let graph = get_random_graph(30, 60, seed);
+            let unweighted = get_unweighted(&graph);
+            let distances = graph.get_distances(0, &|_| false);
+            let depths = unweighted.get_distances(0, &|_| false);
+            for goal in 0..graph.get_node_amount() {
+                let lightest = graph.bidirectional_dijkstra(0, goal, &|_| false);
+                assert_eq!(lightest.as_ref().map(|route| route.length), distances[goal]);
+                let fewest = graph.bidirectional_bfs(0, goal, &|_| false);
+                let edges = fewest.as_ref().map(|route| route.nodes.len() as isize - 1);
+                assert_eq!(edges, depths[goal], "seed {} goal {}", seed, goal);
+                for route in lightest.iter().chain(fewest.iter()) {
+                    assert_eq!((route.nodes[0], *route.nodes.last().unwrap()), (0, goal));
+                }
+            }
+        }
+    }
+
+    /**
+     * Lists every path from the start to the goal that does not visit a node
+     * twice by trying every edge
+     */
+    fn get_simple_paths(
+        graph: &Graph<usize>,
+        route: &mut Vec<usize>,
+        goal: usize,
+        paths: &mut Vec<(isize, Vec<usize>)>,
+    ) {
+        let last = *route.last().unwrap();
+        if last == goal {
+            let length = route
+                .windows(2)
+                .map(|pair| graph.get_edge(pair[0], pair[1]).unwrap())
+                .sum();
+            paths.push((length, route.clone()));
+            return;
+        }
+        for &(neighbor, _) in graph.get_weighted_neighbors(last) {
+            if !route.contains(&neighbor) {
+                route.push(neighbor);
+                get_simple_paths(graph, route, goal, paths);
+                route.pop();
+            }
+        }
+    }
+
+    #[test]
+    fn k_shortest_paths_branch_off_the_earlier_ones() {
+        let mut graph: Graph<usize> = Graph::new();
+        for index in 0..6 {
+            graph.add_node(index);
+        }
+        for &(from, to, weight) in &[
+            (0, 1, 3),
+            (0, 2, 2),
+            (1, 3, 4),
+            (2, 1, 1),
+            (2, 3, 2),
+            (2, 4, 3),
+            (3, 4, 2),
+            (3, 5, 1),
+            (4, 5, 2),
+        ] {
+            graph.set_mono_edge(from, to, weight);
+        }
+        let routes = graph.get_k_shortest_paths(0, 5, 3, &|_| false);
+        let found: Vec<(Vec<usize>, isize)> = routes
+            .into_iter()
+            .map(|route| (route.nodes, route.length))
+            .collect();
+        assert_eq!(found[0], (vec![0, 2, 3, 5], 5));
+        assert_eq!(found[1], (vec![0, 2, 4, 5], 7));
+        assert_eq!(found[2].1, 8);
+        let avoiding = graph.get_k_shortest_paths(0, 5, 10, &|node_index| node_index == 2);
+        let found: Vec<Vec<usize>> = avoiding.into_iter().map(|route| route.nodes).collect();
+        assert_eq!(found, vec![vec![0, 1, 3, 5], vec![0, 1, 3, 4, 5]]);
+        assert!(graph.get_k_shortest_paths(0, 5, 0, &|_| false).is_empty());
+        assert!(graph.get_k_shortest_paths(5, 0, 3, &|_| false).is_empty());
+    }
+
+    #[test]
+    fn k_shortest_paths_agree_with_every_simple_path() {
+        for seed in 1..=40 {
+            let graph = get_random_graph(8, 30, seed);
+            let mut paths: Vec<(isize, Vec<usize>)> = Vec::new();
+            get_simple_paths(&graph, &mut vec![0], 7, &mut paths);
+            let routes = graph.get_k_shortest_paths(0, 7, paths.len() + 1, &|_| false);
+            let mut lengths: Vec<isize> = paths.iter().map(|&(length, _)| length).collect();
+            lengths.sort_unstable();
+            let found: Vec<isize> = routes.iter().map(|route| route.length).collect();
+            assert_eq!(found, lengths, "seed {}", seed);
+            let found: HashSet<(isize, Vec<usize>)> = routes
+                .into_iter()
+                .map(|route| (route.length, route.nodes))
+                .collect();
+            assert_eq!(found.len(), paths.len());
+            assert!(paths.iter().all(|path| found.contains(path)));
+        }
+    }
+}
@@ -3,8 +3,19 @@
 extern crate image as img;
 mod graph;
 mod maze;
-use maze::Maze;
+use maze::agents::Strategy;
+use maze::directions::Turn;
+use maze::exits::Exits;
+use maze::hex::HexMaze;
+use maze::keys::DEFAULT_KEY_COLORS;
+use maze::layers::{LayeredMaze, DEFAULT_STAIR_COLORS};
+use maze::one_way::DEFAULT_ONE_WAY_COLORS;
+use maze::portals::DEFAULT_PORTAL_COLORS;
+use maze::search::{Algorithm, Solution};
+use maze::walkers::{Hand, Walker};
+use maze::{Connectivity, CornerCutting, Maze, MazeOptions, TerrainCosts, Topology};
 use std::env;
+use std::fs;
 use std::time::Instant;
 
 fn main() {
@@ -14,10 +25,36 @@ fn main() {
     let mut entry_x: u32 = 0;
     let mut entry_y: u32 = 0;
     let mut node_image_path = String::new();
+    let mut component_image_path = String::new();
+    let mut solution_path = String::new();
+    let mut solve_algorithm: Option<Algorithm> = None;
+    let mut grid_algorithm: Option<Algorithm> = None;
+    let mut animation_algorithm: Option<Algorithm> = None;
+    let mut frame_every: usize = 1;
+    let mut viewer_algorithm: Option<Algorithm> = None;
+    let mut jump_point_search = false;
+    let mut key_search = false;
+    let mut trap_search = false;
+    let mut shortest_path_limit: Option<usize> = None;
+    let mut alternative_paths: Option<(usize, Option<[u32; 2]>)> = None;
+    let mut checkpoints: Option<Vec<[u32; 2]>> = None;
+    let mut checkpoint_exit: Option<[u32; 2]> = None;
+    let mut exit_ranking: Option<Exits> = None;
+    let mut bidirectional_exit: Option<[u32; 2]> = None;
+    let mut upper_layers: Vec<String> = Vec::new();
+    let mut hex_maze = false;
+    let mut wall_breaks: Option<(isize, Option<usize>)> = None;
+    let mut toggled_tiles: Vec<[u32; 2]> = Vec::new();
+    let mut robot_sight: Option<u32> = None;
+    let mut agents: Option<(Vec<Strategy>, u32)> = None;
+    let mut walkers: Option<(Vec<Walker>, bool)> = None;
+    let mut hex_generation: Option<([u32; 2], u64)> = None;
+    let mut options = MazeOptions::default();
 
-    for (i, arg) in env::args().enumerate() {
+    let args: Vec<String> = env::args().collect();
+    for (i, arg) in args.iter().enumerate() {
         if i == 1 {
-            path = String::from(&arg);
+            path = String::from(arg);
         }
         if i == 2 {
             entry_x = arg.trim_end().parse().unwrap();
@@ -26,23 +63,952 @@ fn main() {
             entry_y = arg.trim_end().parse().unwrap();
         }
         if i > 3 {
-            if arg.contains("-o") {
-                node_image_path = String::from("./node_image.png");
+            match arg.as_str() {
+                "-o" => node_image_path = String::from("./node_image.png"),
+                "-j" => jump_point_search = true,
+                "-l" => options.lazy_graph = true,
+                "-x" => {
+                    key_search = true;
+                    options.keys = match args.get(i + 1) {
+                        Some(palette_path) if !palette_path.starts_with('-') => {
+                            read_key_colors(palette_path)
+                        }
+                        _ => DEFAULT_KEY_COLORS.to_vec(),
+                    }
+                }
+                "-q" => {
+                    options.portal_colors = DEFAULT_PORTAL_COLORS.to_vec();
+                    options.portal_cost = args
+                        .get(i + 1)
+                        .and_then(|cost| cost.parse().ok())
+                        .unwrap_or(0);
+                }
+                "-z" => {
+                    options.topology = Topology::Torus;
+                    options.exit = args.get(i + 1).and_then(|tile| parse_tile(tile));
+                }
+                "-v" => {
+                    trap_search = true;
+                    options.one_way_colors = DEFAULT_ONE_WAY_COLORS.to_vec();
+                }
+                "-h" => {
+                    hex_maze = true;
+                    hex_generation = args.get(i + 1).and_then(|size| {
+                        let mut values = size.split(',').map(|value| value.trim().parse().ok());
+                        let width = values.next()??;
+                        let height = values.next()??;
+                        let seed = values.next().flatten().unwrap_or(1);
+                        Some(([width as u32, height as u32], seed))
+                    })
+                }
+                "-i" => {
+                    wall_breaks = Some(match args.get(i + 1) {
+                        Some(setting) if !setting.starts_with('-') => {
+                            let mut values = setting.split(',').map(|value| value.trim());
+                            (
+                                values
+                                    .next()
+                                    .and_then(|cost| cost.parse().ok())
+                                    .unwrap_or(1),
+                                values.next().and_then(|limit| limit.parse().ok()),
+                            )
+                        }
+                        _ => (1, None),
+                    })
+                }
+                "-R" => {
+                    robot_sight = Some(
+                        args.get(i + 1)
+                            .and_then(|sight| sight.parse().ok())
+                            .unwrap_or(2),
+                    )
+                }
+                "-A" => {
+                    let mut parts = args
+                        .get(i + 1)
+                        .map(|agent| agent.split(','))
+                        .into_iter()
+                        .flatten();
+                    let strategies = match parts.next().and_then(Strategy::from_name) {
+                        Some(strategy) => vec![strategy],
+                        None => Strategy::ALL.to_vec(),
+                    };
+                    let sight = parts
+                        .next()
+                        .and_then(|sight| sight.parse().ok())
+                        .unwrap_or(2);
+                    agents = Some((strategies, sight))
+                }
+                "-W" => {
+                    let mut parts = args
+                        .get(i + 1)
+                        .map(|walker| walker.split(','))
+                        .into_iter()
+                        .flatten();
+                    let chosen = match parts.next().and_then(Walker::from_name) {
+                        Some(walker) => vec![walker],
+                        None => Walker::ALL.to_vec(),
+                    };
+                    walkers = Some((chosen, parts.next() == Some("gif")))
+                }
+                "-y" => {
+                    toggled_tiles = args
+                        .get(i + 1)
+                        .map(|tiles| tiles.split(';').filter_map(parse_tile).collect())
+                        .unwrap_or_default()
+                }
+                "-f" => {
+                    options.stair_colors = DEFAULT_STAIR_COLORS.to_vec();
+                    upper_layers = args
+                        .get(i + 1)
+                        .map(|paths| paths.split(';').map(String::from).collect())
+                        .unwrap_or_default()
+                }
+                "-u" => {
+                    options.portals = args
+                        .get(i + 1)
+                        .map(|pairs| pairs.split(';').filter_map(parse_portal).collect())
+                        .unwrap_or_default()
+                }
+                "-a" => {
+                    shortest_path_limit = Some(
+                        args.get(i + 1)
+                            .and_then(|limit| limit.parse().ok())
+                            .unwrap_or(10),
+                    )
+                }
+                "-e" => {
+                    exit_ranking = Some(match args.get(i + 1) {
+                        Some(tiles) if !tiles.starts_with('-') => {
+                            Exits::Tiles(tiles.split(';').filter_map(parse_tile).collect())
+                        }
+                        _ => Exits::Openings,
+                    })
+                }
+                "-m" => {
+                    checkpoints = args
+                        .get(i + 1)
+                        .map(|tiles| tiles.split(';').filter_map(parse_tile).collect());
+                    checkpoint_exit = args.get(i + 2).and_then(|tile| parse_tile(tile));
+                }
+                "-b" => bidirectional_exit = args.get(i + 1).and_then(|tile| parse_tile(tile)),
+                "-k" => {
+                    alternative_paths = Some((
+                        args.get(i + 1).and_then(|k| k.parse().ok()).unwrap_or(3),
+                        args.get(i + 2).and_then(|tile| parse_tile(tile)),
+                    ))
+                }
+                "-c" => component_image_path = String::from("./component_image.png"),
+                "-p" => solution_path = args.get(i + 1).cloned().unwrap_or_default(),
+                "-s" => {
+                    solve_algorithm = args.get(i + 1).and_then(|name| Algorithm::from_name(name))
+                }
+                "-r" => {
+                    grid_algorithm = args.get(i + 1).and_then(|name| Algorithm::from_name(name))
+                }
+                "-g" => {
+                    animation_algorithm =
+                        args.get(i + 1).and_then(|name| Algorithm::from_name(name))
+                }
+                "-t" => {
+                    viewer_algorithm = args.get(i + 1).and_then(|name| Algorithm::from_name(name))
+                }
+                "-d" => {
+                    let corner_cutting = match args.get(i + 1).map(|rule| rule.as_str()) {
+                        Some("either") => CornerCutting::ForbidIfEither,
+                        Some("both") => CornerCutting::ForbidIfBoth,
+                        _ => CornerCutting::Allow,
+                    };
+                    options.connectivity = Connectivity::Eight(corner_cutting);
+                }
+                "-w" => {
+                    options.terrain = match args.get(i + 1).map(|terrain| terrain.as_str()) {
+                        Some(palette_path)
+                            if palette_path != "grau" && !palette_path.starts_with('-') =>
+                        {
+                            match read_palette(palette_path) {
+                                Ok(terrain) => terrain,
+                                Err(error) => {
+                                    println!(
+                                        "Die Palette {} kann nicht gelesen werden: {}",
+                                        palette_path, error
+                                    );
+                                    return;
+                                }
+                            }
+                        }
+                        _ => TerrainCosts::Grayscale(10),
+                    }
+                }
+                "-n" => {
+                    frame_every = args
+                        .get(i + 1)
+                        .and_then(|every| every.parse().ok())
+                        .unwrap_or(1)
+                }
+                _ => {}
             }
         }
     }
 
+    if hex_maze {
+        run_hex_maze(
+            path,
+            [entry_x, entry_y],
+            hex_generation,
+            solve_algorithm.unwrap_or(Algorithm::Dijkstra),
+        );
+        return;
+    }
+    let lazy_graph = options.lazy_graph;
+    let layer_paths: Vec<String> = std::iter::once(path.clone()).chain(upper_layers).collect();
+    let layer_options = options.clone();
     let now = Instant::now();
     let mut start = now.elapsed().as_secs();
-    let maze = Maze::new(path, entry_x, entry_y).unwrap();
+    let mut maze = Maze::new_with_options(path, entry_x, entry_y, options).unwrap();
     let mut end = now.elapsed().as_secs();
     let build_graph_time = end - start;
-    start = now.elapsed().as_secs();
-    let coords = Maze::solve_maze(&maze).unwrap();
-    end = now.elapsed().as_secs();
-    if node_image_path.len() > 0 {
+    toggle_tiles(&mut maze, &toggled_tiles);
+    let exit = if lazy_graph {
+        println!("Das Einlesen des Labyrinths dauert {} Sekunden, der Graph wird erst bei Bedarf berechnet.",build_graph_time);
+        None
+    } else {
+        start = now.elapsed().as_secs();
+        let exit = Maze::solve_maze(&maze);
+        end = now.elapsed().as_secs();
+        println!("Das Berechnen des Graphen für das Labyrinth dauert {} Sekunden, dabei wurden {} Knoten gesetzt und {} Kanten gesetzt.",build_graph_time,maze.get_graph().get_node_amount(),maze.get_graph().get_edge_amount());
+        match exit {
+            Some(coords) => println!("Das Finden des Ausgangs durch BFS dauert {} Sekunden und es befindet sich an den Koordinaten ({},{}).",end-start,coords[0],coords[1]),
+            None => {
+                println!("Durch BFS wurde in {} Sekunden kein Ausgang gefunden, der Eingang ist vom Rest des Labyrinths abgeschnitten.",end-start);
+                print_component_report(&maze);
+            }
+        }
+        exit
+    };
+    let portals = maze.get_portals();
+    if !portals.is_empty() {
+        let pairs: Vec<String> = portals
+            .iter()
+            .map(|(from, to)| format!("({},{}) ⇄ ({},{})", from[0], from[1], to[0], to[1]))
+            .collect();
+        println!(
+            "Das Labyrinth enthält {} Portale: {}.",
+            portals.len(),
+            pairs.join(", ")
+        );
+    }
+    if !node_image_path.is_empty() {
         maze.export_graph_png(node_image_path);
     }
-    println!("Das Berechnen des Graphen für das Labyrinth dauert {} Sekunden, dabei wurden {} Knoten gesetzt und {} Kanten gesetzt.",build_graph_time,maze.get_graph().get_node_amount(),maze.get_graph().get_edge_amount());
-    println!("Das Finden des Ausgangs durch BFS dauert {} Sekunden und es befindet sich an den Koordinaten ({},{}).",end-start,coords[0],coords[1]);
+    if !component_image_path.is_empty() {
+        if exit.is_some() || lazy_graph {
+            print_component_report(&maze);
+        }
+        maze.export_components_png(component_image_path);
+    }
+    if !solution_path.is_empty() {
+        check_solution(&maze, solution_path);
+    }
+    if let Some(algorithm) = solve_algorithm {
+        match maze.solve(algorithm) {
+            Some(solution) => print_solution(&maze, &solution),
+            None => println!("Es wurde kein Weg zu einem Ausgang gefunden."),
+        }
+    }
+    if let Some(algorithm) = grid_algorithm {
+        match maze.solve_on_grid(algorithm) {
+            Some(solution) => print_solution(&maze, &solution),
+            None => println!("Es wurde kein Weg zu einem Ausgang gefunden."),
+        }
+    }
+    if let Some(limit) = shortest_path_limit {
+        print_shortest_paths(&maze, limit);
+    }
+    if let Some(checkpoints) = checkpoints {
+        print_checkpoint_route(&maze, &checkpoints, checkpoint_exit);
+    }
+    if let Some(exits) = exit_ranking {
+        print_exit_ranking(&maze, &exits);
+    }
+    if let Some((k, exit)) = alternative_paths {
+        print_alternative_paths(&maze, k, exit);
+    }
+    if let Some(exit) = bidirectional_exit {
+        match maze.solve_bidirectional(exit, false) {
+            Some(solution) => println!("Die bidirektionale BFS findet einen Weg über {} Knoten mit der Länge {}, dabei wurden {} Knoten untersucht.",solution.path.len(),solution.length,solution.expanded),
+            None => println!("Die bidirektionale BFS findet keinen Weg zum Ausgang ({},{}).",exit[0],exit[1]),
+        }
+        match maze.solve_bidirectional(exit, true) {
+            Some(solution) => print_solution(&maze, &solution),
+            None => println!(
+                "Der bidirektionale Dijkstra findet keinen Weg zum Ausgang ({},{}).",
+                exit[0], exit[1]
+            ),
+        }
+    }
+    if key_search {
+        print_key_route(&maze);
+    }
+    if trap_search {
+        print_traps(&maze);
+    }
+    if let Some(sight) = robot_sight {
+        print_exploration(&maze, sight, frame_every);
+    }
+    if let Some((strategies, sight)) = &agents {
+        print_agent_walks(&maze, strategies, *sight);
+    }
+    if let Some((chosen, is_animated)) = &walkers {
+        print_walks(&maze, chosen, *is_animated, frame_every);
+    }
+    if let Some((wall_cost, max_breaks)) = wall_breaks {
+        print_wall_breaks(&maze, wall_cost, max_breaks);
+    }
+    if layer_paths.len() > 1 {
+        print_layered_route(&layer_paths, [entry_x, entry_y], layer_options);
+    }
+    if jump_point_search {
+        match maze.solve_jps() {
+            Ok(solution) => print_solution(&maze, &solution),
+            Err(error) => println!("Die Jump Point Search ist fehlgeschlagen: {}", error),
+        }
+    }
+    if let Some(algorithm) = animation_algorithm {
+        let [width, height] = maze.get_size();
+        let scale = (300 / width.max(height)).max(1);
+        maze.export_search_gif(
+            String::from("./search_animation.gif"),
+            algorithm,
+            frame_every,
+            scale,
+        );
+    }
+    if let Some(algorithm) = viewer_algorithm {
+        maze.run_viewer(algorithm).unwrap();
+    }
+}
+
+/**
+ * Checks a submitted solution, painted in red onto the maze image or given as
+ * a text file with one "x,y" coordinate per line.
+ */
+fn check_solution(maze: &Maze, solution_path: String) {
+    let tiles: Vec<[u32; 2]> = if solution_path.ends_with(".png") {
+        match maze.read_painted_path(solution_path, [255, 0, 0]) {
+            Ok(tiles) => tiles,
+            Err(error) => {
+                println!("Die Lösung kann nicht gelesen werden: {}", error);
+                return;
+            }
+        }
+    } else {
+        fs::read_to_string(&solution_path)
+            .unwrap()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut coords = line.split(',').map(|coord| coord.trim().parse().unwrap());
+                [coords.next().unwrap(), coords.next().unwrap()]
+            })
+            .collect()
+    };
+    match maze.validate_path(&tiles) {
+        Ok(validation) => match validation.shortest_length {
+            Some(shortest_length) if validation.is_optimal() => println!(
+                "Die Lösung ist gültig und mit der Länge {} optimal.",
+                shortest_length
+            ),
+            Some(shortest_length) => println!(
+                "Die Lösung ist gültig, mit der Länge {} aber um {} länger als nötig.",
+                validation.length,
+                validation.length - shortest_length
+            ),
+            None => println!("Die Lösung ist mit der Länge {} gültig.", validation.length),
+        },
+        Err(error) => println!("Die Lösung ist ungültig: {}", error),
+    }
+}
+
+/**
+ * Parses a tile given as "x,y".
+ */
+fn parse_tile(tile: &str) -> Option<[u32; 2]> {
+    let (x, y) = tile.split_once(',')?;
+    Some([x.trim().parse().ok()?, y.trim().parse().ok()?])
+}
+
+/**
+ * Parses the two ends of a portal given as "x,y=x,y".
+ */
+fn parse_portal(pair: &str) -> Option<([u32; 2], [u32; 2])> {
+    let (from, to) = pair.split_once('=')?;
+    Some((parse_tile(from)?, parse_tile(to)?))
+}
+
+/**
+ * Reads a palette of terrain costs with one "r,g,b=cost" entry per line.
+ * # Returns
+ * The reason if the file cannot be read or a line is no such entry
+ */
+fn read_palette(palette_path: &str) -> Result<TerrainCosts, String> {
+    let parse_entry = |line: &str| -> Option<([u8; 3], u8)> {
+        let (color, cost) = line.split_once('=')?;
+        let mut channels = color.split(',').map(|channel| channel.trim().parse().ok());
+        let color = [channels.next()??, channels.next()??, channels.next()??];
+        if channels.next().is_some() {
+            return None;
+        }
+        Some((color, cost.trim().parse().ok()?))
+    };
+    fs::read_to_string(palette_path)
+        .map_err(|error| error.to_string())?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            parse_entry(line).ok_or(format!(
+                "Zeile {} hat nicht die Form r,g,b=Kosten",
+                index + 1
+            ))
+        })
+        .collect::<Result<Vec<([u8; 3], u8)>, String>>()
+        .map(TerrainCosts::Palette)
+}
+
+/**
+ * Reads pairs of key and door colors with one "r,g,b=r,g,b" entry per line.
+ */
+fn read_key_colors(palette_path: &str) -> Vec<([u8; 3], [u8; 3])> {
+    let parse_color = |color: &str| -> [u8; 3] {
+        let mut channels = color
+            .split(',')
+            .map(|channel| channel.trim().parse().unwrap());
+        [
+            channels.next().unwrap(),
+            channels.next().unwrap(),
+            channels.next().unwrap(),
+        ]
+    };
+    fs::read_to_string(palette_path)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (key, door) = line.split_once('=').unwrap();
+            (parse_color(key), parse_color(door))
+        })
+        .collect()
+}
+
+fn print_key_route(maze: &Maze) {
+    println!(
+        "Das Labyrinth enthält {} Schlüssel und {} Türen.",
+        maze.get_keys().len(),
+        maze.get_doors().len()
+    );
+    match maze.solve_keys() {
+        Ok(route) => {
+            let collected: Vec<String> = route
+                .collected
+                .iter()
+                .map(|key| format!("({},{})", key[0], key[1]))
+                .collect();
+            if collected.is_empty() {
+                println!("Für den Weg wird kein Schlüssel benötigt.");
+            } else {
+                println!(
+                    "Die Schlüssel werden in der Reihenfolge {} eingesammelt.",
+                    collected.join(", ")
+                );
+            }
+            print_solution(maze, &route.solution);
+        }
+        Err(error) => println!("Das Rätsel ist nicht lösbar: {}", error),
+    }
+}
+
+/**
+ * Lets a robot that only sees the tiles around it find its way out with
+ * D* Lite and records its walk as an animation.
+ */
+fn print_exploration(maze: &Maze, sight: u32, frame_every: usize) {
+    let [width, height] = maze.get_size();
+    let scale = (300 / width.max(height)).max(1);
+    let animation = (String::from("./exploration.gif"), frame_every, scale);
+    match maze.explore_d_star_lite(sight, Some(animation)) {
+        Ok(exploration) => {
+            let exit = exploration.path.last().unwrap();
+            println!("Der Roboter mit der Sichtweite {} erreicht den Ausgang ({},{}) nach {} Schritten mit der Länge {}, dabei wurde {} Mal neu geplant und {} Felder untersucht.",sight,exit[0],exit[1],exploration.path.len()-1,exploration.length,exploration.replans,exploration.expanded);
+            if let Some(shortest_length) = maze.get_shortest_exit_distance() {
+                println!(
+                    "Mit Kenntnis des ganzen Labyrinths wäre der Weg {} lang.",
+                    shortest_length
+                );
+            }
+        }
+        Err(error) => println!("Der Roboter findet keinen Ausgang: {}", error),
+    }
+}
+
+/**
+ * Lets an agent with every given strategy explore the maze and compares the
+ * walked length with the shortest way out.
+ */
+fn print_agent_walks(maze: &Maze, strategies: &[Strategy], sight: u32) {
+    for &strategy in strategies {
+        match maze.explore(strategy, sight) {
+            Ok(walk) => {
+                let exit = walk.path.last().unwrap();
+                let seen = walk.seen.iter().filter(|&&is_seen| is_seen).count();
+                println!("{} mit der Sichtweite {} erreicht den Ausgang ({},{}) nach {} Schritten mit der Länge {} und hat dabei {} Felder gesehen.",strategy,sight,exit[0],exit[1],walk.path.len()-1,walk.length,seen);
+                if let Some(optimal) = walk.optimal {
+                    println!(
+                        "Der kürzeste Weg ist {} lang, der gelaufene Weg ist {:.2} Mal so lang.",
+                        optimal,
+                        walk.length as f64 / optimal.max(1) as f64
+                    );
+                }
+                let name = match strategy {
+                    Strategy::Frontier => "frontier",
+                    Strategy::WallFollower => "wall_follower",
+                    Strategy::Tremaux => "tremaux",
+                };
+                maze.export_agent_png(format!("./agent_{}.png", name), &walk);
+            }
+            Err(error) => println!("{} findet keinen Ausgang: {}", strategy, error),
+        }
+    }
+}
+
+/**
+ * Walks the maze with every given walker, draws the walks and animates them
+ * if asked to.
+ */
+fn print_walks(maze: &Maze, walkers: &[Walker], is_animated: bool, frame_every: usize) {
+    if let Some(shortest_length) = maze.get_shortest_exit_distance() {
+        println!("Der kürzeste Weg ist {} lang.", shortest_length);
+    }
+    for &walker in walkers {
+        match maze.walk(walker) {
+            Ok(walk) => {
+                let exit = walk.path.last().unwrap();
+                println!(
+                    "{} erreicht den Ausgang ({},{}) nach {} Schritten mit der Länge {}.",
+                    walker,
+                    exit[0],
+                    exit[1],
+                    walk.path.len() - 1,
+                    walk.length
+                );
+                if !walk.filled.is_empty() {
+                    println!(
+                        "Dabei wurden {} Felder in Sackgassen gefüllt.",
+                        walk.filled.len()
+                    );
+                }
+                let name = match walker {
+                    Walker::WallFollower(Hand::Right) => "right_hand",
+                    Walker::WallFollower(Hand::Left) => "left_hand",
+                    Walker::Pledge(Hand::Right) => "pledge_right",
+                    Walker::Pledge(Hand::Left) => "pledge_left",
+                    Walker::Tremaux => "tremaux",
+                    Walker::DeadEndFilling => "dead_end_filling",
+                };
+                maze.export_walk_png(format!("./walk_{}.png", name), &walk);
+                if is_animated {
+                    let [width, height] = maze.get_size();
+                    let scale = (300 / width.max(height)).max(1);
+                    maze.export_walk_gif(format!("./walk_{}.gif", name), &walk, frame_every, scale);
+                }
+            }
+            Err(error) => println!("{} findet keinen Ausgang: {}", walker, error),
+        }
+    }
+}
+
+/**
+ * Turns every given tile from a wall into a walkable tile or the other way
+ * round, the graph is only repaired around the changed tiles.
+ */
+fn toggle_tiles(maze: &mut Maze, tiles: &[[u32; 2]]) {
+    for &[x, y] in tiles {
+        let is_wall = maze.is_wall(x, y);
+        match maze.set_wall(x, y, !is_wall) {
+            Ok(()) if is_wall => println!("Die Wand auf ({},{}) wurde entfernt.", x, y),
+            Ok(()) => println!("Auf ({},{}) wurde eine Wand gesetzt.", x, y),
+            Err(error) => println!(
+                "Das Feld ({},{}) kann nicht geändert werden: {}",
+                x, y, error
+            ),
+        }
+    }
+}
+
+fn print_wall_breaks(maze: &Maze, wall_cost: isize, max_breaks: Option<usize>) {
+    match maze.solve_wall_breaks(wall_cost, max_breaks) {
+        Ok(route) => {
+            let exit = route.path.last().unwrap();
+            if route.walls.is_empty() {
+                println!(
+                    "Der Ausgang ({},{}) ist ohne das Durchbrechen einer Wand erreichbar.",
+                    exit[0], exit[1]
+                );
+            } else {
+                let walls: Vec<String> = route
+                    .walls
+                    .iter()
+                    .map(|wall| format!("({},{})", wall[0], wall[1]))
+                    .collect();
+                println!("Um den Ausgang ({},{}) zu erreichen, müssen {} Wände mit den Kosten {} durchbrochen werden: {}.",exit[0],exit[1],route.walls.len(),route.cost,walls.join(", "));
+            }
+            println!(
+                "Der Weg führt über {} Felder, dabei wurden {} Felder untersucht.",
+                route.path.len(),
+                route.expanded
+            );
+            maze.export_wall_breaks_png(String::from("./wall_break_image.png"), &route);
+        }
+        Err(error) => println!("Es gibt keinen Weg durch die Wände: {}", error),
+    }
+}
+
+/**
+ * Loads a hex maze from the text file or generates one with the given size
+ * and seed into it, solves it and draws the solution.
+ */
+fn run_hex_maze(
+    path: String,
+    entry: [u32; 2],
+    generation: Option<([u32; 2], u64)>,
+    algorithm: Algorithm,
+) {
+    let loaded = match generation {
+        Some(([width, height], seed)) => {
+            HexMaze::generate(width, height, entry, seed).inspect(|maze| {
+                fs::write(&path, maze.to_text()).unwrap();
+                println!("Das Wabenlabyrinth wurde in {} gespeichert.", path);
+            })
+        }
+        None => HexMaze::new(path, entry[0], entry[1]),
+    };
+    let maze = match loaded {
+        Ok(maze) => maze,
+        Err(error) => {
+            println!("Das Wabenlabyrinth kann nicht geladen werden: {}", error);
+            return;
+        }
+    };
+    println!(
+        "Der Graph des Wabenlabyrinths hat {} Knoten und {} Kanten.",
+        maze.get_graph().get_node_amount(),
+        maze.get_graph().get_edge_amount()
+    );
+    match maze.solve(algorithm) {
+        Some(solution) => {
+            let exit = solution.path.last().unwrap();
+            println!("Der gefundene Weg zum Ausgang ({},{}) hat die Länge {}, dabei wurden {} Knoten untersucht.",exit[0],exit[1],solution.length,solution.expanded);
+            let directions: Vec<String> = maze
+                .get_directions(&solution.path)
+                .iter()
+                .map(|(direction, steps)| format!("{} {}", direction, steps))
+                .collect();
+            println!("Wegbeschreibung: {}", directions.join(", "));
+            maze.export_png(String::from("./hex_solution_image.png"), Some(&solution), 6);
+        }
+        None => {
+            println!("Es wurde kein Weg zu einem Ausgang gefunden.");
+            maze.export_png(String::from("./hex_solution_image.png"), None, 6);
+        }
+    }
+}
+
+/**
+ * Solves the main maze as the lowest layer with the given images stacked on
+ * top of it and describes the way on every layer.
+ */
+fn print_layered_route(paths: &[String], entry: [u32; 2], options: MazeOptions) {
+    let layered_maze = match LayeredMaze::new(paths, 0, entry, options) {
+        Ok(layered_maze) => layered_maze,
+        Err(error) => {
+            println!("Die Ebenen können nicht verbunden werden: {}", error);
+            return;
+        }
+    };
+    let solution = match layered_maze.solve(Algorithm::Dijkstra) {
+        Some(solution) => solution,
+        None => {
+            println!("Es wurde auf keiner Ebene ein Weg zu einem Ausgang gefunden.");
+            return;
+        }
+    };
+    let (exit_layer, exit) = *solution.path.last().unwrap();
+    println!("Der gefundene Weg über {} Ebenen führt zum Ausgang ({},{}) auf Ebene {} und hat die Länge {}, dabei wurden {} Knoten untersucht.",layered_maze.get_layers().len(),exit[0],exit[1],exit_layer,solution.length,solution.expanded);
+    for (layer, tiles) in layered_maze.get_layer_segments(&solution.path) {
+        let [x, y] = tiles[0];
+        println!("Ebene {} ab ({},{}):", layer, x, y);
+        print_directions(&layered_maze.get_layers()[layer], &tiles);
+    }
+    layered_maze.export_solution_pngs(String::from("./layer_solution.png"), &solution);
+}
+
+fn print_traps(maze: &Maze) {
+    let traps = maze.get_trap_tiles();
+    println!(
+        "Das Labyrinth enthält {} Einbahnfelder.",
+        maze.get_one_way_tiles().len()
+    );
+    if traps.is_empty() {
+        println!("Von jedem erreichbaren Feld aus kann ein Ausgang erreicht werden.");
+    } else {
+        println!(
+            "Von {} erreichbaren Feldern aus kann kein Ausgang mehr erreicht werden.",
+            traps.len()
+        );
+    }
+    maze.export_traps_png(String::from("./trap_image.png"), &traps);
+}
+
+fn print_solution(maze: &Maze, solution: &Solution) {
+    let exit = solution.path.last().unwrap();
+    println!("Der gefundene Weg zum Ausgang ({},{}) hat die Länge {}, dabei wurden {} Knoten untersucht.",exit[0],exit[1],solution.length,solution.expanded);
+    print_directions(maze, &solution.path);
+    maze.export_solution_png(String::from("./solution_image.png"), solution);
+}
+
+/**
+ * Counts the shortest paths, lists up to limit of them and draws all of them
+ * into one image.
+ */
+fn print_shortest_paths(maze: &Maze, limit: usize) {
+    let shortest_paths = match maze.get_shortest_paths() {
+        Some(shortest_paths) => shortest_paths,
+        None => {
+            println!("Es wurde kein Weg zu einem Ausgang gefunden.");
+            return;
+        }
+    };
+    let exits: Vec<String> = shortest_paths
+        .exits
+        .iter()
+        .map(|exit| format!("({},{})", exit[0], exit[1]))
+        .collect();
+    println!(
+        "Es gibt {} kürzeste Wege der Länge {} zu den Ausgängen {}.",
+        shortest_paths.count,
+        shortest_paths.length,
+        exits.join(", ")
+    );
+    if shortest_paths.is_unique() {
+        println!("Die optimale Lösung ist eindeutig.");
+    }
+    for (index, path) in maze
+        .enumerate_shortest_paths(&shortest_paths, limit)
+        .iter()
+        .enumerate()
+    {
+        println!("Weg {}:", index + 1);
+        print_directions(maze, path);
+    }
+    maze.export_shortest_paths_png(String::from("./shortest_paths_image.png"), &shortest_paths);
+}
+
+/**
+ * Routes through every checkpoint to the exit, or to the exit nearest to the
+ * entry if none is given.
+ */
+fn print_checkpoint_route(maze: &Maze, checkpoints: &[[u32; 2]], exit: Option<[u32; 2]>) {
+    let exit = match exit.or_else(|| {
+        maze.find_nearest_exit(&Exits::Openings)
+            .ok()
+            .and_then(|solution| solution.path.last().copied())
+    }) {
+        Some(exit) => exit,
+        None => {
+            println!("Es wurde kein Weg zu einem Ausgang gefunden.");
+            return;
+        }
+    };
+    match maze.solve_checkpoints(checkpoints, exit) {
+        Ok(route) => {
+            let order: Vec<String> = route
+                .order
+                .iter()
+                .map(|&checkpoint| {
+                    format!(
+                        "({},{})",
+                        checkpoints[checkpoint][0], checkpoints[checkpoint][1]
+                    )
+                })
+                .collect();
+            println!(
+                "Die Kontrollpunkte werden in der Reihenfolge {} besucht{}.",
+                order.join(", "),
+                if route.is_exact {
+                    ""
+                } else {
+                    ", die Reihenfolge ist geschätzt"
+                }
+            );
+            print_solution(maze, &route.solution);
+        }
+        Err(error) => println!("Es gibt keinen Weg über alle Kontrollpunkte: {}", error),
+    }
+}
+
+/**
+ * Prints the distance to every exit, ordered from the nearest to the farthest.
+ */
+fn print_exit_ranking(maze: &Maze, exits: &Exits) {
+    let distances = match maze.get_exit_distances(exits) {
+        Ok(distances) => distances,
+        Err(error) => {
+            println!("Die Ausgänge konnten nicht bewertet werden: {}", error);
+            return;
+        }
+    };
+    let ranking = maze.get_exit_ranking(exits).unwrap();
+    match ranking.first() {
+        Some((exit, distance)) => println!(
+            "Der nächste Ausgang liegt bei ({},{}) mit der Länge {}.",
+            exit[0], exit[1], distance
+        ),
+        None => println!("Keiner der Ausgänge ist erreichbar."),
+    }
+    for (rank, (exit, distance)) in ranking.iter().enumerate() {
+        println!(
+            "{}. ({},{}): Länge {}",
+            rank + 1,
+            exit[0],
+            exit[1],
+            distance
+        );
+    }
+    for exit_distance in distances
+        .iter()
+        .filter(|exit_distance| exit_distance.distance.is_none())
+    {
+        println!(
+            "({},{}) ist nicht erreichbar.",
+            exit_distance.exit[0], exit_distance.exit[1]
+        );
+    }
+}
+
+/**
+ * Lists the k shortest paths to the exit, or to the nearest exit if none is
+ * given, and draws them in different colors.
+ */
+fn print_alternative_paths(maze: &Maze, k: usize, exit: Option<[u32; 2]>) {
+    let exit = match exit.or_else(|| {
+        maze.solve(Algorithm::Dijkstra)
+            .and_then(|solution| solution.path.last().copied())
+    }) {
+        Some(exit) => exit,
+        None => {
+            println!("Es wurde kein Weg zu einem Ausgang gefunden.");
+            return;
+        }
+    };
+    let solutions = maze.solve_k_shortest(exit, k);
+    println!(
+        "Es wurden {} Wege zum Ausgang ({},{}) gefunden.",
+        solutions.len(),
+        exit[0],
+        exit[1]
+    );
+    for (index, solution) in solutions.iter().enumerate() {
+        println!("Weg {} mit der Länge {}:", index + 1, solution.length);
+        print_directions(maze, &solution.path);
+    }
+    if !solutions.is_empty() {
+        maze.export_solutions_png(String::from("./alternative_paths_image.png"), &solutions);
+    }
+}
+
+fn print_directions(maze: &Maze, path: &[[u32; 2]]) {
+    let absolute: Vec<String> = maze
+        .get_directions(path)
+        .iter()
+        .map(|(direction, steps)| format!("{} {}", direction, steps))
+        .collect();
+    let relative: Vec<String> = maze
+        .get_relative_directions(path)
+        .iter()
+        .map(|(turn, steps)| {
+            let name = match turn {
+                Turn::Straight => "geradeaus",
+                Turn::HalfRight => "halbrechts",
+                Turn::Right => "rechts",
+                Turn::SharpRight => "scharf rechts",
+                Turn::Back => "zurück",
+                Turn::SharpLeft => "scharf links",
+                Turn::Left => "links",
+                Turn::HalfLeft => "halblinks",
+            };
+            format!("{} {}", name, steps)
+        })
+        .collect();
+    println!("Wegbeschreibung: {}", absolute.join(", "));
+    println!("Abbiegungen: {}", relative.join(", "));
+}
+
+fn print_component_report(maze: &Maze) {
+    let report = maze.get_component_report();
+    let (graph_components, _) = maze.get_graph().get_components();
+    println!(
+        "Das Labyrinth zerfällt in {} Zusammenhangskomponenten, der Graph in {}.",
+        report.components.len(),
+        graph_components
+    );
+    for (index, component) in report.components.iter().enumerate() {
+        let openings: Vec<String> = component
+            .openings
+            .iter()
+            .map(|opening| format!("({},{})", opening[0], opening[1]))
+            .collect();
+        println!(
+            "Komponente {}{}: {} Felder, {} Knoten, Öffnungen: {}",
+            index,
+            if report.entry_component == Some(index) {
+                " (Eingang)"
+            } else {
+                ""
+            },
+            component.tiles,
+            component.nodes,
+            if openings.is_empty() {
+                String::from("keine")
+            } else {
+                openings.join(", ")
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_palette;
+    use crate::maze::TerrainCosts;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn palettes_are_read_line_by_line() {
+        let palette_path = env::temp_dir().join("maze_solver_palette.txt");
+        fs::write(&palette_path, "10, 20, 30 = 4\n\n255,0,0=9\n").unwrap();
+        match read_palette(palette_path.to_str().unwrap()) {
+            Ok(TerrainCosts::Palette(palette)) => {
+                assert_eq!(palette, vec![([10, 20, 30], 4), ([255, 0, 0], 9)])
+            }
+            _ => panic!("the palette was not read"),
+        }
+        fs::write(&palette_path, "10,20,30=4\n10,20=3\n").unwrap();
+        assert_eq!(
+            read_palette(palette_path.to_str().unwrap()).err(),
+            Some(String::from("Zeile 2 hat nicht die Form r,g,b=Kosten"))
+        );
+        fs::remove_file(&palette_path).unwrap();
+        assert!(read_palette(palette_path.to_str().unwrap()).is_err());
+    }
 }
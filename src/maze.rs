@@ -1,45 +1,397 @@
 extern crate image as img;
 use crate::graph::Graph;
 use img::{Rgb, RgbImage};
-use std::collections::VecDeque;
+use std::cell::OnceCell;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter, Result as fmtResult};
+pub mod agents;
+pub mod animation;
+pub mod checkpoints;
+pub mod components;
+pub mod d_star_lite;
+pub mod directions;
+pub mod exits;
+#[cfg(test)]
+pub mod fixtures;
+pub mod grid;
+pub mod hex;
+pub mod jump_point;
+pub mod keys;
+pub mod layers;
+pub mod one_way;
+pub mod portals;
+pub mod search;
+pub mod shortest_paths;
+pub mod updates;
+pub mod validation;
+pub mod viewer;
+pub mod walkers;
+pub mod wall_breaks;
+use layers::Stairs;
+use search::{Algorithm, Solution};
+
+/**
+ * The steps to the eight neighbors of a tile, clockwise starting at the top
+ */
+const STEPS: [[i64; 2]; 8] = [
+    [0, -1],
+    [1, -1],
+    [1, 0],
+    [1, 1],
+    [0, 1],
+    [-1, 1],
+    [-1, 0],
+    [-1, -1],
+];
+
+/**
+ * Costs of an orthogonal and a diagonal step in 8-connected mazes, their
+ * ratio approximates the square root of two. In 4-connected mazes every step
+ * costs 1.
+ */
+pub const OCTILE_ORTHOGONAL_COST: isize = 10;
+pub const OCTILE_DIAGONAL_COST: isize = 14;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Connectivity {
+    Four,
+    Eight(CornerCutting),
+}
+
+/**
+ * Decides when a diagonal step may pass between the two orthogonal neighbors
+ * it touches.
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub enum CornerCutting {
+    Allow,
+    ForbidIfEither,
+    ForbidIfBoth,
+}
+
+/**
+ * Decides what lies beyond the border of the maze.
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub enum Topology {
+    /**
+     * The maze ends at its border, every walkable tile there is an opening
+     */
+    Bounded,
+    /**
+     * The left border is connected to the right one and the top border to
+     * the bottom one, so there are no openings and the exit has to be given
+     */
+    Torus,
+}
+
+/**
+ * Maps the color of a walkable pixel to the cost of stepping onto its tile.
+ */
+#[derive(Clone)]
+pub enum TerrainCosts {
+    Uniform,
+    /**
+     * Colors with their costs, colors that are not listed cost 1
+     */
+    Palette(Vec<([u8; 3], u8)>),
+    /**
+     * White costs 1, the darker a pixel the closer its cost gets to the
+     * given maximum
+     */
+    Grayscale(u8),
+}
+
+impl TerrainCosts {
+    pub fn get_cost(&self, pixel: [u8; 3]) -> u8 {
+        match self {
+            TerrainCosts::Uniform => 1,
+            TerrainCosts::Palette(palette) => palette
+                .iter()
+                .find(|(color, _)| *color == pixel)
+                .map_or(1, |&(_, cost)| cost),
+            TerrainCosts::Grayscale(max_cost) => {
+                let intensity = pixel.iter().map(|&channel| channel as u32).sum::<u32>() / 3;
+                (1 + (255 - intensity) * (max_cost.max(&1) - 1) as u32 / 255) as u8
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MazeOptions {
+    pub connectivity: Connectivity,
+    pub topology: Topology,
+    /**
+     * The only exit of the maze instead of the openings on the border,
+     * required on a torus
+     */
+    pub exit: Option<[u32; 2]>,
+    pub terrain: TerrainCosts,
+    /**
+     * Builds the graph on its first use instead of in the constructor, a
+     * maze that is only solved on the grid never builds it at all
+     */
+    pub lazy_graph: bool,
+    /**
+     * Pairs of the color of a key and the color of the doors it opens, see
+     * keys::DEFAULT_KEY_COLORS
+     */
+    pub keys: Vec<([u8; 3], [u8; 3])>,
+    /**
+     * Colors that each mark the two ends of a portal, see
+     * portals::DEFAULT_PORTAL_COLORS
+     */
+    pub portal_colors: Vec<[u8; 3]>,
+    /**
+     * Pairs of tiles connected by a portal in addition to the colored ones
+     */
+    pub portals: Vec<([u32; 2], [u32; 2])>,
+    /**
+     * The cost of jumping from one end of a portal to the other
+     */
+    pub portal_cost: isize,
+    /**
+     * Colors of one-way tiles with the only direction they can be left in,
+     * see one_way::DEFAULT_ONE_WAY_COLORS
+     */
+    pub one_way_colors: Vec<([u8; 3], Directions)>,
+    /**
+     * Colors of stair tiles leading to the layer above or below, see
+     * layers::DEFAULT_STAIR_COLORS
+     */
+    pub stair_colors: Vec<([u8; 3], Stairs)>,
+    /**
+     * The cost of taking the stairs from one layer to the next
+     */
+    pub stair_cost: isize,
+}
+
+impl Default for MazeOptions {
+    fn default() -> MazeOptions {
+        MazeOptions {
+            connectivity: Connectivity::Four,
+            topology: Topology::Bounded,
+            exit: None,
+            terrain: TerrainCosts::Uniform,
+            lazy_graph: false,
+            keys: Vec::new(),
+            portal_colors: Vec::new(),
+            portals: Vec::new(),
+            portal_cost: 0,
+            one_way_colors: Vec::new(),
+            stair_colors: Vec::new(),
+            stair_cost: 1,
+        }
+    }
+}
+
+/**
+ * The graph of a maze together with the index of the node placed on every
+ * tile
+ */
+type ScannedGraph = (Graph<([u32; 2], bool)>, Vec<Option<usize>>);
+
+/**
+ * A walk in a straight or diagonal line from one tile to another.
+ */
+struct StraightWalk {
+    step: [i64; 2],
+    steps: u32,
+    cost: isize,
+}
+
 pub struct Maze {
     size: [u32; 2],
     maze: Vec<bool>,
-    graph: Graph<([u32; 2], bool)>,
+    /**
+     * Built by scan on first use
+     */
+    graph: OnceCell<ScannedGraph>,
     entry: [u32; 2],
+    options: MazeOptions,
+    /**
+     * The terrain cost of every tile, empty if every tile costs 1
+     */
+    costs: Vec<u8>,
+    min_cost: isize,
+    /**
+     * The tiles holding a key or a door with the index of their pair in
+     * MazeOptions::keys
+     */
+    keys: HashMap<[u32; 2], usize>,
+    doors: HashMap<[u32; 2], usize>,
+    /**
+     * Both ends of every portal, each mapped to the other one
+     */
+    portals: HashMap<[u32; 2], [u32; 2]>,
+    /**
+     * The one-way tiles with the only step that leaves them
+     */
+    one_ways: HashMap<[u32; 2], [i64; 2]>,
+    /**
+     * The stair tiles with the layer they lead to
+     */
+    stairs: HashMap<[u32; 2], Stairs>,
 }
 
 impl Maze {
     pub fn new(path: String, entry_x: u32, entry_y: u32) -> Result<Maze, MazeError> {
+        Self::new_with_options(path, entry_x, entry_y, MazeOptions::default())
+    }
+
+    pub fn new_with_options(
+        path: String,
+        entry_x: u32,
+        entry_y: u32,
+        options: MazeOptions,
+    ) -> Result<Maze, MazeError> {
         let image: RgbImage = img::open(&path).unwrap().to_rgb8();
+        Self::from_image(&image, entry_x, entry_y, options)
+    }
+
+    /**
+     * Reads the maze from an image that is already loaded, black pixels are
+     * walls.
+     */
+    pub fn from_image(
+        image: &RgbImage,
+        entry_x: u32,
+        entry_y: u32,
+        options: MazeOptions,
+    ) -> Result<Maze, MazeError> {
         let dimensions = image.dimensions();
         let mut maze = vec![false; (dimensions.0 * dimensions.1) as usize];
+        let mut costs: Vec<u8> = match options.terrain {
+            TerrainCosts::Uniform => Vec::new(),
+            _ => vec![1; maze.len()],
+        };
+        let mut keys: HashMap<[u32; 2], usize> = HashMap::new();
+        let mut doors: HashMap<[u32; 2], usize> = HashMap::new();
+        let mut portal_ends: Vec<Vec<[u32; 2]>> = vec![Vec::new(); options.portal_colors.len()];
+        let mut one_ways: HashMap<[u32; 2], [i64; 2]> = HashMap::new();
+        let mut stairs: HashMap<[u32; 2], Stairs> = HashMap::new();
 
         for column in 0..(dimensions.0) {
             for row in 0..(dimensions.1) {
                 let pixel = image.get_pixel(column, row);
+                let key = options.keys.iter().position(|&(key, _)| key == pixel.0);
+                let door = options.keys.iter().position(|&(_, door)| door == pixel.0);
+                let portal = options
+                    .portal_colors
+                    .iter()
+                    .position(|&color| color == pixel.0);
+                let one_way = options
+                    .one_way_colors
+                    .iter()
+                    .find(|&&(color, _)| color == pixel.0);
+                let stair = options
+                    .stair_colors
+                    .iter()
+                    .find(|&&(color, _)| color == pixel.0);
                 if pixel[0] == 0 && pixel[1] == 0 && pixel[2] == 0 {
                     maze[(row * dimensions.0 + column) as usize] = true;
+                } else if let Some(key) = key {
+                    keys.insert([column, row], key);
+                } else if let Some(door) = door {
+                    doors.insert([column, row], door);
+                } else if let Some(portal) = portal {
+                    portal_ends[portal].push([column, row]);
+                } else if let Some(&(_, direction)) = one_way {
+                    one_ways.insert([column, row], STEPS[direction.get_octant() as usize]);
+                } else if let Some(&(_, stair)) = stair {
+                    stairs.insert([column, row], stair);
+                } else if !costs.is_empty() {
+                    costs[(row * dimensions.0 + column) as usize] =
+                        options.terrain.get_cost(pixel.0);
                 }
             }
         }
+        let mut portals: HashMap<[u32; 2], [u32; 2]> = HashMap::new();
+        let mut pairs: Vec<([u32; 2], [u32; 2])> = Vec::new();
+        for ends in portal_ends {
+            match ends[..] {
+                [] => {}
+                [from, to] => pairs.push((from, to)),
+                _ => return Err(MazeError::InvalidPortal),
+            }
+        }
+        pairs.extend_from_slice(&options.portals);
+        if options.portal_cost < 0 {
+            return Err(MazeError::InvalidPortal);
+        }
+        for (from, to) in pairs {
+            let is_walkable = |[x, y]: [u32; 2]| {
+                x < dimensions.0 && y < dimensions.1 && !maze[(y * dimensions.0 + x) as usize]
+            };
+            if from == to
+                || !is_walkable(from)
+                || !is_walkable(to)
+                || portals.contains_key(&from)
+                || portals.contains_key(&to)
+            {
+                return Err(MazeError::InvalidPortal);
+            }
+            portals.insert(from, to);
+            portals.insert(to, from);
+        }
+        match options.exit {
+            Some([x, y]) if x >= dimensions.0 || y >= dimensions.1 => {
+                return Err(MazeError::OutOfBounds)
+            }
+            Some([x, y]) if maze[(y * dimensions.0 + x) as usize] => {
+                return Err(MazeError::NotWalkable)
+            }
+            None if options.topology == Topology::Torus => return Err(MazeError::MissingExit),
+            _ => {}
+        }
         if entry_x < dimensions.0 && entry_y < dimensions.1 {
-            let mut maze = Maze {
+            let min_cost = costs
+                .iter()
+                .zip(maze.iter())
+                .filter(|(_, &is_wall)| !is_wall)
+                .map(|(&cost, _)| cost as isize)
+                .min()
+                .unwrap_or(1);
+            let maze = Maze {
                 size: [dimensions.0, dimensions.1],
-                maze: maze,
-                graph: Graph::new(),
+                maze,
+                graph: OnceCell::new(),
                 entry: [entry_x, entry_y],
+                options,
+                costs,
+                min_cost,
+                keys,
+                doors,
+                portals,
+                one_ways,
+                stairs,
             };
-            maze.scan();
+            if !maze.options.lazy_graph {
+                maze.get_graph();
+            }
             return Ok(maze);
         }
         Err(MazeError::OutOfBounds)
     }
 
+    pub fn get_size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    pub fn get_entry(&self) -> [u32; 2] {
+        self.entry
+    }
+
+    /**
+     * # Returns
+     * The graph of the maze, it gets built by this call if it does not exist
+     * yet
+     */
     pub fn get_graph(&self) -> &Graph<([u32; 2], bool)> {
-        &self.graph
+        &self.graph.get_or_init(|| self.scan()).0
     }
 
     fn get_tile(&self, x: u32, y: u32) -> Result<bool, MazeError> {
@@ -49,7 +401,15 @@ impl Maze {
         Err(MazeError::OutOfBounds)
     }
 
-    fn set_tile(&mut self, x: u32, y: u32, value: bool) -> () {
+    /**
+     * # Returns
+     * True if the tile is a wall or lies outside of the maze
+     */
+    pub fn is_wall(&self, x: u32, y: u32) -> bool {
+        self.get_tile(x, y).unwrap_or(true)
+    }
+
+    fn set_tile(&mut self, x: u32, y: u32, value: bool) {
         self.maze[(y * self.size[0] + x) as usize] = value;
     }
 
@@ -62,251 +422,639 @@ impl Maze {
     fn get_neighbors(&self, x: u32, y: u32) -> Result<[Option<bool>; 4], MazeError> {
         if self.get_tile(x, y).is_ok() {
             let mut neighbors: [Option<bool>; 4] = [None, None, None, None];
-            if x != 0 {
-                neighbors[3] = self.get_tile(x - 1, y).ok();
-            } else {
-                neighbors[3] = None;
-            }
-            if y != 0 {
-                neighbors[0] = self.get_tile(x, y - 1).ok();
-            } else {
-                neighbors[0] = None;
+            for (neighbor, step) in neighbors.iter_mut().zip(STEPS.iter().step_by(2)) {
+                *neighbor = self
+                    .offset(x, y, *step)
+                    .and_then(|[to_x, to_y]| self.get_tile(to_x, to_y).ok());
             }
-            neighbors[1] = self.get_tile(x + 1, y).ok();
-            neighbors[2] = self.get_tile(x, y + 1).ok();
             return Ok(neighbors);
         }
         Err(MazeError::OutOfBounds)
     }
 
-    fn scan(&mut self) {
-        let mut upper_neighbor_nodes: Vec<Option<usize>> = vec![None; self.size[0] as usize];
-        let mut left_neighbor: Option<usize> = None;
+    /**
+     * # Returns
+     * The position one step away from the given tile, wrapped around the
+     * borders on a torus and None if it lies outside of a bounded maze
+     */
+    fn offset(&self, x: u32, y: u32, step: [i64; 2]) -> Option<[u32; 2]> {
+        let [to_x, to_y] = [x as i64 + step[0], y as i64 + step[1]];
+        match self.options.topology {
+            Topology::Bounded => {
+                if to_x < 0
+                    || to_y < 0
+                    || to_x >= self.size[0] as i64
+                    || to_y >= self.size[1] as i64
+                {
+                    return None;
+                }
+                Some([to_x as u32, to_y as u32])
+            }
+            Topology::Torus => Some([
+                to_x.rem_euclid(self.size[0] as i64) as u32,
+                to_y.rem_euclid(self.size[1] as i64) as u32,
+            ]),
+        }
+    }
+
+    fn is_border(&self, x: u32, y: u32) -> bool {
+        x == 0 || y == 0 || x == self.size[0] - 1 || y == self.size[1] - 1
+    }
+
+    /**
+     * # Returns
+     * True for the entry and the exits, every tile on the border unless the
+     * exit is given explicitly
+     */
+    fn is_opening_tile(&self, x: u32, y: u32) -> bool {
+        match self.options.exit {
+            Some(exit) => [x, y] == exit || [x, y] == self.entry,
+            None => self.is_border(x, y),
+        }
+    }
+
+    /**
+     * # Returns
+     * True if the tile is an opening and not the entry, so a walk that
+     * reaches it has found an exit
+     */
+    fn is_exit_tile(&self, x: u32, y: u32) -> bool {
+        self.is_opening_tile(x, y) && [x, y] != self.entry
+    }
+
+    /**
+     * # Returns
+     * Every walkable tile that is an exit, see is_exit_tile
+     */
+    fn get_grid_exits(&self) -> Vec<[u32; 2]> {
+        if let Some(exit) = self.options.exit {
+            return vec![exit];
+        }
+        let [width, height] = self.size;
+        let mut tiles: Vec<[u32; 2]> = Vec::new();
+        for y in 0..height {
+            let columns: Vec<u32> = if y == 0 || y == height - 1 {
+                (0..width).collect()
+            } else {
+                vec![0, width - 1]
+            };
+            for x in columns {
+                if !self.get_tile(x, y).unwrap() && [x, y] != self.entry {
+                    tiles.push([x, y]);
+                }
+            }
+        }
+        tiles
+    }
+
+    /**
+     * # Returns
+     * The steps a walker may take in this maze, clockwise starting at the top
+     */
+    fn get_steps(&self) -> Vec<[i64; 2]> {
+        match self.options.connectivity {
+            Connectivity::Four => STEPS.iter().step_by(2).copied().collect(),
+            Connectivity::Eight(_) => STEPS.to_vec(),
+        }
+    }
+
+    /**
+     * # Returns
+     * The tile reached by taking a step from the given tile, None if it is a
+     * wall, outside of the maze, a diagonal step that cuts a forbidden corner
+     * or the tile is a one-way tile pointing elsewhere
+     */
+    fn get_step(&self, x: u32, y: u32, step: [i64; 2]) -> Option<[u32; 2]> {
+        if !self.can_leave([x, y], step) {
+            return None;
+        }
+        self.get_open_step(x, y, step)
+    }
+
+    /**
+     * # Returns
+     * False if the tile is a one-way tile that cannot be left with the step
+     */
+    fn can_leave(&self, tile: [u32; 2], step: [i64; 2]) -> bool {
+        self.one_ways
+            .get(&tile)
+            .is_none_or(|&allowed| allowed == step)
+    }
+
+    /**
+     * Same as get_step, but ignores the direction of one-way tiles.
+     */
+    fn get_open_step(&self, x: u32, y: u32, step: [i64; 2]) -> Option<[u32; 2]> {
+        let [to_x, to_y] = self.offset(x, y, step)?;
+        if self.get_tile(to_x, to_y).ok()? || self.cuts_corner([x, y], [to_x, to_y]) {
+            return None;
+        }
+        Some([to_x, to_y])
+    }
+
+    /**
+     * # Returns
+     * True if the diagonal step between both tiles passes a corner the
+     * corner cutting rule forbids
+     */
+    fn cuts_corner(&self, from: [u32; 2], to: [u32; 2]) -> bool {
+        match self.options.connectivity {
+            Connectivity::Eight(corner_cutting) if from[0] != to[0] && from[1] != to[1] => {
+                let horizontal = self.get_tile(to[0], from[1]).unwrap();
+                let vertical = self.get_tile(from[0], to[1]).unwrap();
+                match corner_cutting {
+                    CornerCutting::Allow => false,
+                    CornerCutting::ForbidIfEither => horizontal || vertical,
+                    CornerCutting::ForbidIfBoth => horizontal && vertical,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /**
+     * # Returns
+     * The tiles from which a single step leads to the given tile
+     */
+    fn get_predecessors(&self, x: u32, y: u32) -> Vec<[u32; 2]> {
+        self.get_steps()
+            .into_iter()
+            .filter_map(|step| {
+                let [from_x, from_y] = self.offset(x, y, [-step[0], -step[1]])?;
+                if self.get_tile(from_x, from_y).ok()? {
+                    return None;
+                }
+                (self.get_step(from_x, from_y, step) == Some([x, y])).then_some([from_x, from_y])
+            })
+            .collect()
+    }
+
+    /**
+     * # Returns
+     * The steps that can be taken from the given tile together with the tiles
+     * they lead to
+     */
+    fn get_moves(&self, x: u32, y: u32) -> Vec<([i64; 2], [u32; 2])> {
+        self.get_steps()
+            .into_iter()
+            .filter_map(|step| self.get_step(x, y, step).map(|tile| (step, tile)))
+            .collect()
+    }
+
+    /**
+     * # Returns
+     * The terrain cost of stepping onto the given tile
+     */
+    pub fn get_tile_cost(&self, x: u32, y: u32) -> isize {
+        if self.costs.is_empty() {
+            return 1;
+        }
+        self.costs[(y * self.size[0] + x) as usize] as isize
+    }
+
+    /**
+     * # Returns
+     * The cost of taking a step from the given tile, the cost of the step
+     * itself multiplied with the terrain cost of the tile it leads to
+     */
+    pub fn get_move_cost(&self, x: u32, y: u32, step: [i64; 2]) -> isize {
+        let [to_x, to_y] = self.offset(x, y, step).unwrap();
+        self.get_step_cost(step) * self.get_tile_cost(to_x, to_y)
+    }
+
+    pub fn get_step_cost(&self, step: [i64; 2]) -> isize {
+        match self.options.connectivity {
+            Connectivity::Four => 1,
+            Connectivity::Eight(_) if step[0] != 0 && step[1] != 0 => OCTILE_DIAGONAL_COST,
+            Connectivity::Eight(_) => OCTILE_ORTHOGONAL_COST,
+        }
+    }
+
+    /**
+     * # Returns
+     * A lower bound for the cost of walking from one tile to another, the
+     * manhattan distance in 4-connected mazes and the octile distance in
+     * 8-connected ones, multiplied with the lowest terrain cost. On a torus
+     * the distances may go across the borders.
+     */
+    pub fn get_cost_estimate(&self, from: [u32; 2], to: [u32; 2]) -> isize {
+        let [mut dx, mut dy] = [
+            from[0].abs_diff(to[0]) as isize,
+            from[1].abs_diff(to[1]) as isize,
+        ];
+        if self.options.topology == Topology::Torus {
+            dx = dx.min(self.size[0] as isize - dx);
+            dy = dy.min(self.size[1] as isize - dy);
+        }
+        let distance = match self.options.connectivity {
+            Connectivity::Four => dx + dy,
+            Connectivity::Eight(_) => {
+                OCTILE_DIAGONAL_COST * dx.min(dy)
+                    + OCTILE_ORTHOGONAL_COST * (dx.max(dy) - dx.min(dy))
+            }
+        };
+        distance * self.min_cost
+    }
+
+    /**
+     * Adds a node for every walkable tile that is the entry, an opening on the
+     * border, a junction, a corner, a dead end, a key, a door, the end of a
+     * portal or a stair, in row major order. Straight corridors are skipped, the cost of
+     * walking along them is stored as the weight of the edge between the nodes
+     * at both of their ends. One-way tiles always become nodes, the edges only point
+     * in the directions they can be walked. Both ends of a portal are
+     * connected by an edge with the portal cost unless walking between them
+     * is cheaper.
+     * # Returns
+     * The graph and the index of the node placed on every tile
+     */
+    fn scan(&self) -> ScannedGraph {
+        let mut graph: Graph<([u32; 2], bool)> = Graph::new();
+        let mut node_indices: Vec<Option<usize>> = vec![None; self.maze.len()];
         for y in 0..self.size[1] {
             for x in 0..self.size[0] {
-                if self.get_tile(x, y).unwrap() != true {
-                    let [top_option, right_option, bottom_option, left_option] =
-                        self.get_neighbors(x, y).unwrap();
-                    if top_option.is_none()
-                        || right_option.is_none()
-                        || bottom_option.is_none()
-                        || left_option.is_none()
-                    {
-                        if top_option.is_some() && top_option.unwrap() == false {
-                            let node_index = self.graph.add_node(([x, y], true));
-                            self.graph.set_bi_edge(
-                                upper_neighbor_nodes[x as usize].unwrap(),
-                                node_index,
-                                0,
-                            );
-                        } else if right_option.is_some() && right_option.unwrap() == false {
-                            let node_index = self.graph.add_node(([x, y], true));
-                            left_neighbor = Some(node_index);
-                        } else if bottom_option.is_some() && bottom_option.unwrap() == false {
-                            let node_index = self.graph.add_node(([x, y], true));
-                            upper_neighbor_nodes[x as usize] = Some(node_index);
-                        } else if left_option.is_some() && left_option.unwrap() == false {
-                            let node_index = self.graph.add_node(([x, y], true));
-                            self.graph
-                                .set_bi_edge(left_neighbor.unwrap(), node_index, 0);
-                        }
-                    } else if top_option.is_some()
-                        && right_option.is_some()
-                        && bottom_option.is_some()
-                        && left_option.is_some()
-                    {
-                        let [top, right, bottom, left] = [
-                            top_option.unwrap(),
-                            right_option.unwrap(),
-                            bottom_option.unwrap(),
-                            left_option.unwrap(),
-                        ];
-                        if top == false && right == false && bottom == false && left == false
-                        //┼
-                        {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            self.graph
-                                .set_bi_edge(left_neighbor.unwrap(), node_index, 0);
-                            self.graph.set_bi_edge(
-                                upper_neighbor_nodes[x as usize].unwrap(),
-                                node_index,
-                                0,
-                            );
-                            left_neighbor = Some(node_index);
-                            upper_neighbor_nodes[x as usize] = Some(node_index);
-                        } else if top == false && right == false && bottom == true && left == false
-                        //┴
-                        {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            self.graph
-                                .set_bi_edge(left_neighbor.unwrap(), node_index, 0);
-                            self.graph.set_bi_edge(
-                                upper_neighbor_nodes[x as usize].unwrap(),
-                                node_index,
-                                0,
-                            );
-                            left_neighbor = Some(node_index);
-                            upper_neighbor_nodes[x as usize] = None;
-                        } else if top == true && right == false && bottom == false && left == false
-                        //┬
-                        {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            self.graph
-                                .set_bi_edge(left_neighbor.unwrap(), node_index, 0);
-                            left_neighbor = Some(node_index);
-                            upper_neighbor_nodes[x as usize] = Some(node_index);
-                        } else if top == false && right == false && bottom == false && left == true
-                        //├
-                        {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            self.graph.set_bi_edge(
-                                upper_neighbor_nodes[x as usize].unwrap(),
-                                node_index,
-                                0,
-                            );
-                            left_neighbor = Some(node_index);
-                            upper_neighbor_nodes[x as usize] = Some(node_index);
-                        } else if top == false && right == true && bottom == false && left == false
-                        //¬├
-                        {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            self.graph
-                                .set_bi_edge(left_neighbor.unwrap(), node_index, 0);
-                            self.graph.set_bi_edge(
-                                upper_neighbor_nodes[x as usize].unwrap(),
-                                node_index,
-                                0,
-                            );
-                            upper_neighbor_nodes[x as usize] = Some(node_index);
-                            left_neighbor = None;
-                        } else if top == true && right == false && bottom == false && left == true
-                        //┌
-                        {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            upper_neighbor_nodes[x as usize] = Some(node_index);
-                            left_neighbor = Some(node_index);
-                        } else if top == true && right == true && bottom == false && left == false
-                        //┐
-                        {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            self.graph
-                                .set_bi_edge(left_neighbor.unwrap(), node_index, 0);
-                            upper_neighbor_nodes[x as usize] = Some(node_index);
-                            left_neighbor = None;
-                        } else if top == false && right == true && bottom == true && left == false
-                        //┘
-                        {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            self.graph
-                                .set_bi_edge(left_neighbor.unwrap(), node_index, 0);
-                            self.graph.set_bi_edge(
-                                upper_neighbor_nodes[x as usize].unwrap(),
-                                node_index,
-                                0,
-                            );
-                            left_neighbor = None;
-                            upper_neighbor_nodes[x as usize] = None;
-                        } else if top == false && right == false && bottom == true && left == true
-                        //└
-                        {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            self.graph.set_bi_edge(
-                                upper_neighbor_nodes[x as usize].unwrap(),
-                                node_index,
-                                0,
-                            );
-                            left_neighbor = Some(node_index);
-                            upper_neighbor_nodes[x as usize] = None;
-                        } else if top == false && right == true && bottom == true && left == true
-                        //↓
-                        {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            self.graph.set_bi_edge(
-                                upper_neighbor_nodes[x as usize].unwrap(),
-                                node_index,
-                                0,
-                            );
-                            upper_neighbor_nodes[x as usize] = None;
-                        } else if top == true && right == false && bottom == true && left == true
-                        //←
-                        {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            left_neighbor = Some(node_index);
-                        } else if top == true && right == true && bottom == false && left == true
-                        //↑
-                        {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            upper_neighbor_nodes[x as usize] = Some(node_index);
-                        } else if top == true && right == true && bottom == true && left == false
-                        //→
+                if self.is_node_tile(x, y) {
+                    let node_index = graph.add_node(([x, y], self.is_opening_tile(x, y)));
+                    node_indices[(y * self.size[0] + x) as usize] = Some(node_index);
+                }
+            }
+        }
+        let forward_steps: Vec<[i64; 2]> = self
+            .get_steps()
+            .into_iter()
+            .filter(|step| step[1] > 0 || (step[1] == 0 && step[0] > 0))
+            .collect();
+        for node_index in 0..graph.get_node_amount() {
+            self.add_corridor_edges(&mut graph, &node_indices, node_index, &forward_steps);
+        }
+        for node_index in 0..graph.get_node_amount() {
+            self.add_portal_edge(&mut graph, &node_indices, node_index);
+        }
+        (graph, node_indices)
+    }
+
+    /**
+     * # Returns
+     * True if the tile gets a graph node, see scan
+     */
+    fn is_node_tile(&self, x: u32, y: u32) -> bool {
+        if self.get_tile(x, y).unwrap() {
+            return false;
+        }
+        let moves = self.get_moves(x, y);
+        let is_corridor = moves.len() == 2 && moves[0].0 == [-moves[1].0[0], -moves[1].0[1]];
+        let is_marked = self.keys.contains_key(&[x, y])
+            || self.doors.contains_key(&[x, y])
+            || self.portals.contains_key(&[x, y])
+            || self.stairs.contains_key(&[x, y]);
+        !is_corridor || self.is_opening_tile(x, y) || is_marked || [x, y] == self.entry
+    }
+
+    /**
+     * Follows the corridors leaving the node with the given steps and adds
+     * the edges to the nodes at their ends in both directions, keeping the
+     * cheaper edge if two corridors connect the same nodes.
+     */
+    fn add_corridor_edges(
+        &self,
+        graph: &mut Graph<([u32; 2], bool)>,
+        node_indices: &[Option<usize>],
+        node_index: usize,
+        steps: &[[i64; 2]],
+    ) {
+        let [x, y] = graph.get_node(node_index).element.0;
+        for &step in steps {
+            if let Some((to, weight, back_weight)) = self.follow_corridor(node_indices, x, y, step)
+            {
+                let mut set_edge = |from: usize, to: usize, weight: Option<isize>| {
+                    if let Some(weight) = weight {
+                        if from != to && graph.get_edge(from, to).is_none_or(|known| weight < known)
                         {
-                            let node_index = self.graph.add_node(([x, y], false));
-                            self.graph
-                                .set_bi_edge(left_neighbor.unwrap(), node_index, 0);
-                            left_neighbor = None;
+                            graph.set_mono_edge(from, to, weight);
                         }
                     }
-                }
+                };
+                set_edge(node_index, to, weight);
+                set_edge(to, node_index, back_weight);
+            }
+        }
+    }
+
+    /**
+     * Adds the edge from a node on the end of a portal to the other end,
+     * unless walking there is cheaper.
+     */
+    fn add_portal_edge(
+        &self,
+        graph: &mut Graph<([u32; 2], bool)>,
+        node_indices: &[Option<usize>],
+        node_index: usize,
+    ) {
+        let tile = graph.get_node(node_index).element.0;
+        if let Some(&[x, y]) = self.portals.get(&tile) {
+            let to = node_indices[(y * self.size[0] + x) as usize].unwrap();
+            if graph
+                .get_edge(node_index, to)
+                .is_none_or(|weight| self.options.portal_cost <= weight)
+            {
+                graph.set_mono_edge(node_index, to, self.options.portal_cost);
             }
         }
     }
 
+    /**
+     * Walks from a node in the direction of the step until the next node.
+     * Only the nodes at both ends can be one-way tiles, every tile in between
+     * can be walked in both directions.
+     * # Returns
+     * The index of that node, the cost of the walk and the cost of walking
+     * back, None if the first step is blocked by a wall or the walk gets back
+     * to where it started on a torus. Each cost is None if a one-way tile at
+     * the start of that direction points elsewhere.
+     */
+    fn follow_corridor(
+        &self,
+        node_indices: &[Option<usize>],
+        x: u32,
+        y: u32,
+        step: [i64; 2],
+    ) -> Option<(usize, Option<isize>, Option<isize>)> {
+        let back_step = [-step[0], -step[1]];
+        let [mut to_x, mut to_y] = self.get_open_step(x, y, step)?;
+        let mut cost = self.get_move_cost(x, y, step);
+        let mut back_cost = self.get_move_cost(to_x, to_y, back_step);
+        loop {
+            if let Some(node_index) = node_indices[(to_y * self.size[0] + to_x) as usize] {
+                return Some((
+                    node_index,
+                    self.can_leave([x, y], step).then_some(cost),
+                    self.can_leave([to_x, to_y], back_step).then_some(back_cost),
+                ));
+            }
+            let [next_x, next_y] = self.get_step(to_x, to_y, step)?;
+            if [next_x, next_y] == [x, y] {
+                return None;
+            }
+            cost += self.get_move_cost(to_x, to_y, step);
+            back_cost += self.get_move_cost(next_x, next_y, back_step);
+            [to_x, to_y] = [next_x, next_y];
+        }
+    }
+
+    /**
+     * # Returns
+     * The index of the graph node placed on the given tile, None if the tile
+     * is a wall, a straight corridor or out of bounds
+     */
+    pub fn get_node_index(&self, x: u32, y: u32) -> Option<usize> {
+        if x < self.size[0] && y < self.size[1] {
+            let (_, node_indices) = self.graph.get_or_init(|| self.scan());
+            return node_indices[(y * self.size[0] + x) as usize];
+        }
+        None
+    }
+
+    /**
+     * # Returns
+     * The nodes at both ends of the straight corridor the tile lies in
+     * together with the cost of walking from the tile to them and back, just
+     * the node itself if there is one on the tile and nothing for walls. The
+     * way back is None if the node is a one-way tile pointing elsewhere.
+     */
+    pub fn get_corridor_ends(&self, x: u32, y: u32) -> Vec<(usize, isize, Option<isize>)> {
+        if let Some(node_index) = self.get_node_index(x, y) {
+            return vec![(node_index, 0, Some(0))];
+        }
+        if self.get_tile(x, y).unwrap_or(true) {
+            return Vec::new();
+        }
+        let (_, node_indices) = self.graph.get_or_init(|| self.scan());
+        self.get_moves(x, y)
+            .into_iter()
+            .filter_map(|(step, _)| {
+                let (node_index, cost, back_cost) =
+                    self.follow_corridor(node_indices, x, y, step)?;
+                Some((node_index, cost?, back_cost))
+            })
+            .collect()
+    }
+
+    /**
+     * # Returns
+     * The exit with the fewest nodes between it and the entry
+     */
     pub fn solve_maze(maze: &Maze) -> Option<[u32; 2]> {
-        Self::bfs(maze.get_graph(), 0)
-    }
-
-    fn bfs(graph: &Graph<([u32; 2], bool)>, start_node_index: usize) -> Option<[u32; 2]> {
-        let mut queue: VecDeque<usize> = VecDeque::new();
-        let mut visited: Vec<usize> = Vec::new();
-        let mut exit_node: Option<[u32; 2]> = None;
-        queue.push_back(start_node_index);
-        visited.push(start_node_index);
-        let mut skipped_first = false;
-        while !queue.is_empty() {
-            let node_index = queue.pop_front().unwrap();
-            if graph.get_node(node_index).element.1 == true {
-                if skipped_first {
-                    exit_node = Some(graph.get_node(node_index).element.0);
-                    break;
-                } else {
-                    skipped_first = true;
+        maze.solve(Algorithm::Bfs)
+            .and_then(|solution| solution.path.last().copied())
+    }
+
+    /**
+     * # Returns
+     * The length of the shortest walk from the entry to any other opening
+     * that does not pass a door
+     */
+    pub fn get_shortest_exit_distance(&self) -> Option<isize> {
+        let start_node_index = self.get_node_index(self.entry[0], self.entry[1])?;
+        Self::dijkstra(self.get_graph(), start_node_index, &|node_index| {
+            self.is_door_node(node_index)
+        })
+        .map(|(_, distance)| distance)
+    }
+
+    fn dijkstra(
+        graph: &Graph<([u32; 2], bool)>,
+        start_node_index: usize,
+        is_blocked: &dyn Fn(usize) -> bool,
+    ) -> Option<([u32; 2], isize)> {
+        let mut distances: Vec<Option<isize>> = vec![None; graph.get_node_amount()];
+        let mut heap: BinaryHeap<Reverse<(isize, usize)>> = BinaryHeap::new();
+        distances[start_node_index] = Some(0);
+        heap.push(Reverse((0, start_node_index)));
+        while let Some(Reverse((distance, node_index))) = heap.pop() {
+            if distances[node_index] != Some(distance) {
+                continue;
+            }
+            let (tile, is_opening) = graph.get_node(node_index).element;
+            if is_opening && node_index != start_node_index {
+                return Some((tile, distance));
+            }
+            for &(child_index, weight) in graph.get_weighted_neighbors(node_index) {
+                if is_blocked(child_index) {
+                    continue;
+                }
+                let child_distance = distance + weight;
+                if distances[child_index].is_none_or(|known| child_distance < known) {
+                    distances[child_index] = Some(child_distance);
+                    heap.push(Reverse((child_distance, child_index)));
                 }
             }
-            for child_index in graph.get_neighbors(node_index) {
-                if !visited.contains(&child_index) {
-                    queue.push_back(child_index);
-                    visited.push(child_index);
+        }
+        None
+    }
+
+    /**
+     * # Returns
+     * Every tile on the straight line from one tile to another, both included
+     */
+    pub fn get_corridor_tiles(&self, from: [u32; 2], to: [u32; 2]) -> Vec<[u32; 2]> {
+        let steps = from[0].abs_diff(to[0]).max(from[1].abs_diff(to[1])) as i64;
+        let [step_x, step_y] = [
+            (to[0] as i64 - from[0] as i64).signum(),
+            (to[1] as i64 - from[1] as i64).signum(),
+        ];
+        (0..=steps)
+            .map(|step| {
+                [
+                    (from[0] as i64 + step * step_x) as u32,
+                    (from[1] as i64 + step * step_y) as u32,
+                ]
+            })
+            .collect()
+    }
+
+    /**
+     * Walks from one tile to another in a straight or diagonal line, on a
+     * torus in every direction since the line may wrap around the borders.
+     * # Returns
+     * The cheapest of these walks, None if the tiles are equal or every line
+     * is blocked or passes a graph node without passes_nodes
+     */
+    fn get_straight_walk(
+        &self,
+        from: [u32; 2],
+        to: [u32; 2],
+        passes_nodes: bool,
+    ) -> Option<StraightWalk> {
+        if from == to {
+            return None;
+        }
+        let candidates: Vec<[i64; 2]> = match self.options.topology {
+            Topology::Bounded => {
+                Directions::between(from, to)?;
+                vec![[
+                    (to[0] as i64 - from[0] as i64).signum(),
+                    (to[1] as i64 - from[1] as i64).signum(),
+                ]]
+            }
+            Topology::Torus => self.get_steps(),
+        };
+        candidates
+            .into_iter()
+            .filter(|step| self.get_steps().contains(step))
+            .filter_map(|step| {
+                let mut walk = StraightWalk {
+                    step,
+                    steps: 0,
+                    cost: 0,
+                };
+                let [mut x, mut y] = from;
+                loop {
+                    let next = self.get_step(x, y, step)?;
+                    walk.steps += 1;
+                    walk.cost += self.get_move_cost(x, y, step);
+                    if next == to {
+                        return Some(walk);
+                    }
+                    if next == from
+                        || (!passes_nodes && self.get_node_index(next[0], next[1]).is_some())
+                    {
+                        return None;
+                    }
+                    [x, y] = next;
                 }
+            })
+            .min_by_key(|walk| walk.cost)
+    }
+
+    /**
+     * # Returns
+     * The tiles walked over between two consecutive tiles of a path, both
+     * included, just the two of them for a jump through a portal. On a torus
+     * the line may wrap around the borders.
+     */
+    pub fn get_walked_tiles(&self, from: [u32; 2], to: [u32; 2]) -> Vec<[u32; 2]> {
+        if self.is_portal_jump(from, to) {
+            return vec![from, to];
+        }
+        if self.options.topology == Topology::Torus {
+            if let Some(walk) = self.get_straight_walk(from, to, true) {
+                let mut tiles = vec![from];
+                for _ in 0..walk.steps {
+                    let [x, y] = *tiles.last().unwrap();
+                    tiles.push(self.offset(x, y, walk.step).unwrap());
+                }
+                return tiles;
             }
         }
-        return exit_node;
+        self.get_corridor_tiles(from, to)
     }
 
-    fn dfs(graph: &Graph<([u32; 2], bool)>, start_node_index: usize) -> Option<[u32; 2]> {
-        let node_length = graph.get_node_amount();
-        let mut color: Vec<char> = vec!['w'; node_length];
-        let mut pi: Vec<Option<usize>> = vec![None; node_length];
-        unimplemented!();
+    /**
+     * Turns the node tiles of a solution into the list of every tile walked,
+     * a jump through a portal goes straight from one of its ends to the other.
+     */
+    pub fn expand_path(&self, path: &[[u32; 2]]) -> Vec<[u32; 2]> {
+        let mut tiles: Vec<[u32; 2]> = path.iter().take(1).copied().collect();
+        for pair in path.windows(2) {
+            tiles.extend(self.get_walked_tiles(pair[0], pair[1]).into_iter().skip(1));
+        }
+        tiles
     }
 
-    pub fn export_graph_png(&self, path: String) {
+    fn get_maze_image(&self) -> RgbImage {
         let mut image = img::RgbImage::new(self.size[0], self.size[1]);
         for x in 0..self.size[0] {
             for y in 0..self.size[1] {
-                if self.get_tile(x, y).unwrap() == true {
+                if self.get_tile(x, y).unwrap() {
                     image.put_pixel(x, y, Rgb([0, 0, 0]));
+                } else if self.portals.contains_key(&[x, y]) {
+                    image.put_pixel(x, y, portals::PORTAL_COLOR);
+                } else if self.one_ways.contains_key(&[x, y]) {
+                    image.put_pixel(x, y, one_way::ONE_WAY_COLOR);
+                } else if self.stairs.contains_key(&[x, y]) {
+                    image.put_pixel(x, y, layers::STAIR_COLOR);
                 } else {
                     image.put_pixel(x, y, Rgb([255, 255, 255]));
                 }
             }
         }
-        for node_index in 0..self.graph.get_node_amount() {
-            let (tile, _) = self.graph.get_node(node_index).element;
+        image
+    }
+
+    pub fn export_graph_png(&self, path: String) {
+        let mut image = self.get_maze_image();
+        let graph = self.get_graph();
+        for node_index in 0..graph.get_node_amount() {
+            let (tile, _) = graph.get_node(node_index).element;
             image.put_pixel(tile[0], tile[1], Rgb([255, 160, 122]));
         }
         image.save(&path).unwrap();
         println!("Bild wurde in {} gespeichert.", path);
     }
+
+    pub fn export_solution_png(&self, path: String, solution: &Solution) {
+        let mut image = self.get_maze_image();
+        for [x, y] in self.expand_path(&solution.path) {
+            image.put_pixel(x, y, Rgb([255, 0, 0]));
+        }
+        image.save(&path).unwrap();
+        println!("Bild wurde in {} gespeichert.", path);
+    }
+
+    /**
+     * Draws several solutions in different colors, the first one is drawn
+     * last so that it stays on top where they overlap.
+     */
+    pub fn export_solutions_png(&self, path: String, solutions: &[Solution]) {
+        let mut image = self.get_maze_image();
+        for (index, solution) in solutions.iter().enumerate().rev() {
+            for [x, y] in self.expand_path(&solution.path) {
+                image.put_pixel(x, y, components::get_component_color(index));
+            }
+        }
+        image.save(&path).unwrap();
+        println!("Bild wurde in {} gespeichert.", path);
+    }
 }
 
 impl Display for Maze {
@@ -319,7 +1067,7 @@ impl Display for Maze {
                     false => maze_string.push('⬜'),
                 }
             }
-            maze_string.push_str("\n");
+            maze_string.push('\n');
         }
         write!(f, "{}", maze_string)
     }
@@ -335,17 +1083,92 @@ impl Debug for Maze {
                     false => maze_string.push('0'),
                 }
             }
-            maze_string.push_str("\n");
+            maze_string.push('\n');
         }
         write!(f, "{}", maze_string)
     }
 }
 
-enum Directions {
+#[derive(Clone, Copy, PartialEq)]
+pub enum Directions {
     Top = 0,
     Right = 1,
     Bottom = 2,
     Left = 3,
+    TopRight = 4,
+    BottomRight = 5,
+    BottomLeft = 6,
+    TopLeft = 7,
+}
+
+impl Directions {
+    /**
+     * # Returns
+     * The direction of a straight or diagonal line between two tiles, None if
+     * the tiles are equal or not on such a line
+     */
+    pub fn between(from: [u32; 2], to: [u32; 2]) -> Option<Directions> {
+        if from[0].abs_diff(to[0]) != from[1].abs_diff(to[1])
+            && from[0] != to[0]
+            && from[1] != to[1]
+        {
+            return None;
+        }
+        match (to[0].cmp(&from[0]), to[1].cmp(&from[1])) {
+            (Ordering::Equal, Ordering::Less) => Some(Directions::Top),
+            (Ordering::Greater, Ordering::Less) => Some(Directions::TopRight),
+            (Ordering::Greater, Ordering::Equal) => Some(Directions::Right),
+            (Ordering::Greater, Ordering::Greater) => Some(Directions::BottomRight),
+            (Ordering::Equal, Ordering::Greater) => Some(Directions::Bottom),
+            (Ordering::Less, Ordering::Greater) => Some(Directions::BottomLeft),
+            (Ordering::Less, Ordering::Equal) => Some(Directions::Left),
+            (Ordering::Less, Ordering::Less) => Some(Directions::TopLeft),
+            (Ordering::Equal, Ordering::Equal) => None,
+        }
+    }
+
+    /**
+     * # Returns
+     * The position of the direction on the compass in eighths of a full turn,
+     * clockwise starting at the top
+     */
+    pub fn get_octant(&self) -> u8 {
+        match self {
+            Directions::Top => 0,
+            Directions::TopRight => 1,
+            Directions::Right => 2,
+            Directions::BottomRight => 3,
+            Directions::Bottom => 4,
+            Directions::BottomLeft => 5,
+            Directions::Left => 6,
+            Directions::TopLeft => 7,
+        }
+    }
+
+    /**
+     * # Returns
+     * The direction of a single step, None if it is no step to a neighbor
+     */
+    pub fn from_step(step: [i64; 2]) -> Option<Directions> {
+        let octant = STEPS.iter().position(|&known| known == step)?;
+        Some(Directions::from_octant(octant as u8))
+    }
+
+    /**
+     * The opposite of get_octant, the octant is taken modulo eight.
+     */
+    pub fn from_octant(octant: u8) -> Directions {
+        match octant % 8 {
+            0 => Directions::Top,
+            1 => Directions::TopRight,
+            2 => Directions::Right,
+            3 => Directions::BottomRight,
+            4 => Directions::Bottom,
+            5 => Directions::BottomLeft,
+            6 => Directions::Left,
+            _ => Directions::TopLeft,
+        }
+    }
 }
 
 impl Display for Directions {
@@ -358,6 +1181,10 @@ impl Display for Directions {
                 Directions::Right => "➡",
                 Directions::Bottom => "⬇",
                 Directions::Left => "⬅",
+                Directions::TopRight => "⬈",
+                Directions::BottomRight => "⬊",
+                Directions::BottomLeft => "⬋",
+                Directions::TopLeft => "⬉",
             }
         )
     }
@@ -373,6 +1200,10 @@ impl Debug for Directions {
                 Directions::Right => "->",
                 Directions::Bottom => "V",
                 Directions::Left => "<-",
+                Directions::TopRight => "A->",
+                Directions::BottomRight => "V->",
+                Directions::BottomLeft => "<-V",
+                Directions::TopLeft => "<-A",
             }
         )
     }
@@ -381,6 +1212,25 @@ impl Debug for Directions {
 pub enum MazeError {
     OutOfBounds,
     NoExitFound,
+    SizeMismatch,
+    NonUniformCosts,
+    InvalidExit,
+    NotWalkable,
+    Unreachable,
+    TooManyKeys,
+    InvalidPortal,
+    PortalsUnsupported,
+    OneWayUnsupported,
+    DoorsUnsupported,
+    TorusUnsupported,
+    MissingExit,
+    InvalidStairs,
+    InvalidHexText,
+    CornerEntry,
+    InvalidWallCost,
+    ProtectedTile,
+    UnreadableImage,
+    AmbiguousPath,
 }
 
 impl Display for MazeError {
@@ -388,6 +1238,29 @@ impl Display for MazeError {
         match self {
             Self::OutOfBounds => write!(f, "The requested Index does not Exist"),
             Self::NoExitFound => write!(f, "Could not find an Exit for the Maze"),
+            Self::SizeMismatch => write!(f, "The Image does not have the Size of the Maze"),
+            Self::NonUniformCosts => write!(f, "The Maze has Tiles with different Terrain Costs"),
+            Self::InvalidExit => write!(f, "The given Exit is a Wall or out of Bounds"),
+            Self::NotWalkable => write!(f, "The given Tile is a Wall or out of Bounds"),
+            Self::Unreachable => write!(f, "The given Tiles cannot all be reached"),
+            Self::TooManyKeys => write!(f, "The Maze has more Key Colors than can be tracked"),
+            Self::InvalidPortal => {
+                write!(f, "A Portal needs two walkable Ends and no negative Cost")
+            }
+            Self::PortalsUnsupported => write!(f, "The Search cannot follow Portals"),
+            Self::OneWayUnsupported => write!(f, "The Search cannot follow One-Way Tiles"),
+            Self::DoorsUnsupported => write!(f, "The Search cannot open Doors"),
+            Self::TorusUnsupported => write!(f, "The Search cannot wrap around a Torus"),
+            Self::MissingExit => write!(f, "A Torus needs an explicit Exit"),
+            Self::InvalidStairs => write!(f, "A Stair does not lead to a matching Stair"),
+            Self::InvalidHexText => write!(f, "The Text does not describe a Hex Maze"),
+            Self::CornerEntry => write!(f, "The Entry has no Neighbor inside the Border"),
+            Self::InvalidWallCost => write!(f, "Breaking a Wall has to cost at least 1"),
+            Self::ProtectedTile => {
+                write!(f, "The Entry, Keys, Doors and Stairs cannot be walled in")
+            }
+            Self::UnreadableImage => write!(f, "The Image could not be opened"),
+            Self::AmbiguousPath => write!(f, "The painted Path branches or touches itself"),
         }
     }
 }
@@ -396,8 +1269,223 @@ impl Debug for MazeError {
         match self {
             Self::OutOfBounds => write!(f, "The requested Index does not Exist"),
             Self::NoExitFound => write!(f, "Could not find an Exit for the Maze"),
+            Self::SizeMismatch => write!(f, "The Image does not have the Size of the Maze"),
+            Self::NonUniformCosts => write!(f, "The Maze has Tiles with different Terrain Costs"),
+            Self::InvalidExit => write!(f, "The given Exit is a Wall or out of Bounds"),
+            Self::NotWalkable => write!(f, "The given Tile is a Wall or out of Bounds"),
+            Self::Unreachable => write!(f, "The given Tiles cannot all be reached"),
+            Self::TooManyKeys => write!(f, "The Maze has more Key Colors than can be tracked"),
+            Self::InvalidPortal => {
+                write!(f, "A Portal needs two walkable Ends and no negative Cost")
+            }
+            Self::PortalsUnsupported => write!(f, "The Search cannot follow Portals"),
+            Self::OneWayUnsupported => write!(f, "The Search cannot follow One-Way Tiles"),
+            Self::DoorsUnsupported => write!(f, "The Search cannot open Doors"),
+            Self::TorusUnsupported => write!(f, "The Search cannot wrap around a Torus"),
+            Self::MissingExit => write!(f, "A Torus needs an explicit Exit"),
+            Self::InvalidStairs => write!(f, "A Stair does not lead to a matching Stair"),
+            Self::InvalidHexText => write!(f, "The Text does not describe a Hex Maze"),
+            Self::CornerEntry => write!(f, "The Entry has no Neighbor inside the Border"),
+            Self::InvalidWallCost => write!(f, "Breaking a Wall has to cost at least 1"),
+            Self::ProtectedTile => {
+                write!(f, "The Entry, Keys, Doors and Stairs cannot be walled in")
+            }
+            Self::UnreadableImage => write!(f, "The Image could not be opened"),
+            Self::AmbiguousPath => write!(f, "The painted Path branches or touches itself"),
         }
     }
 }
 
 impl Error for MazeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::get_maze;
+    use super::*;
+
+    const CORRIDOR: [&str; 5] = ["#.#", "#.#", "#.#", "#.#", "#.#"];
+
+    #[test]
+    fn straight_corridors_become_weighted_edges() {
+        let maze = get_maze(&CORRIDOR, [1, 0], MazeOptions::default());
+        assert_eq!(maze.get_graph().get_node_amount(), 2);
+        let top = maze.get_node_index(1, 0).unwrap();
+        let bottom = maze.get_node_index(1, 4).unwrap();
+        assert_eq!(maze.get_graph().get_edge(top, bottom), Some(4));
+        assert_eq!(maze.get_node_index(1, 2), None);
+        assert_eq!(
+            maze.get_corridor_ends(1, 1),
+            vec![(top, 1, Some(1)), (bottom, 3, Some(3))]
+        );
+    }
+
+    #[test]
+    fn entry_inside_a_straight_corridor_gets_a_node() {
+        let maze = get_maze(&CORRIDOR, [1, 2], MazeOptions::default());
+        let entry = maze.get_node_index(1, 2).unwrap();
+        let top = maze.get_node_index(1, 0).unwrap();
+        assert_eq!(maze.get_graph().get_edge(entry, top), Some(2));
+        assert_eq!(maze.get_shortest_exit_distance(), Some(2));
+    }
+
+    #[test]
+    fn terrain_costs_are_read_from_the_pixel_colors() {
+        let palette = TerrainCosts::Palette(vec![([10, 20, 30], 7)]);
+        assert_eq!(palette.get_cost([10, 20, 30]), 7);
+        assert_eq!(palette.get_cost([10, 20, 31]), 1);
+        let grayscale = TerrainCosts::Grayscale(11);
+        assert_eq!(grayscale.get_cost([255, 255, 255]), 1);
+        assert_eq!(grayscale.get_cost([0, 0, 0]), 11);
+        assert_eq!(grayscale.get_cost([102, 102, 102]), 7);
+        assert_eq!(TerrainCosts::Uniform.get_cost([0, 0, 0]), 1);
+    }
+
+    #[test]
+    fn expensive_terrain_is_walked_around() {
+        let options = MazeOptions {
+            terrain: TerrainCosts::Palette(vec![(fixtures::get_color('g'), 9)]),
+            ..MazeOptions::default()
+        };
+        let maze = get_maze(
+            &["#.###", "#...#", "#g#.#", "#...#", "#.###"],
+            [1, 0],
+            options,
+        );
+        assert_eq!(maze.get_tile_cost(1, 2), 9);
+        let top = maze.get_node_index(1, 1).unwrap();
+        let bottom = maze.get_node_index(1, 3).unwrap();
+        assert_eq!(maze.get_graph().get_edge(top, bottom), Some(10));
+        let solution = maze.solve(Algorithm::Dijkstra).unwrap();
+        assert_eq!(solution.length, 8);
+        assert_eq!(maze.solve_on_grid(Algorithm::AStar).unwrap().length, 8);
+        assert_eq!(fixtures::get_exit_distance(&maze), Some(8));
+    }
+
+    #[test]
+    fn corner_cutting_rules_decide_diagonal_steps() {
+        let fixtures: [[&str; 2]; 3] = [[".#", "#."], ["..", "#."], ["..", ".."]];
+        let rules = [
+            (Connectivity::Four, [false, false, false]),
+            (
+                Connectivity::Eight(CornerCutting::Allow),
+                [true, true, true],
+            ),
+            (
+                Connectivity::Eight(CornerCutting::ForbidIfEither),
+                [false, false, true],
+            ),
+            (
+                Connectivity::Eight(CornerCutting::ForbidIfBoth),
+                [false, true, true],
+            ),
+        ];
+        for (connectivity, allowed) in rules {
+            for (rows, is_allowed) in fixtures.iter().zip(allowed) {
+                let options = MazeOptions {
+                    connectivity,
+                    ..MazeOptions::default()
+                };
+                let maze = get_maze(rows, [0, 0], options);
+                let moves = maze.get_moves(0, 0);
+                assert_eq!(moves.contains(&([1, 1], [1, 1])), is_allowed);
+            }
+        }
+    }
+
+    #[test]
+    fn diagonal_steps_cost_the_octile_distance() {
+        let options = MazeOptions {
+            connectivity: Connectivity::Eight(CornerCutting::Allow),
+            ..MazeOptions::default()
+        };
+        let maze = get_maze(&["#.###", "##.##", "###.#", "###.#"], [1, 0], options);
+        assert_eq!(maze.get_move_cost(1, 0, [1, 1]), OCTILE_DIAGONAL_COST);
+        assert_eq!(maze.get_move_cost(3, 2, [0, 1]), OCTILE_ORTHOGONAL_COST);
+        assert_eq!(maze.get_moves(2, 1).len(), 2);
+        let solution = maze.solve(Algorithm::Dijkstra).unwrap();
+        assert_eq!(
+            solution.length,
+            2 * OCTILE_DIAGONAL_COST + OCTILE_ORTHOGONAL_COST
+        );
+        assert_eq!(solution.path, vec![[1, 0], [3, 2], [3, 3]]);
+        assert_eq!(
+            maze.get_cost_estimate([1, 0], [3, 3]),
+            2 * OCTILE_DIAGONAL_COST + OCTILE_ORTHOGONAL_COST
+        );
+    }
+
+    #[test]
+    fn torus_mazes_need_an_exit() {
+        let options = MazeOptions {
+            topology: Topology::Torus,
+            ..MazeOptions::default()
+        };
+        let image = fixtures::get_image(&CORRIDOR);
+        assert!(matches!(
+            Maze::from_image(&image, 1, 0, options),
+            Err(MazeError::MissingExit)
+        ));
+    }
+
+    #[test]
+    fn paths_wrap_around_the_borders_of_a_torus() {
+        let options = MazeOptions {
+            topology: Topology::Torus,
+            exit: Some([4, 1]),
+            ..MazeOptions::default()
+        };
+        let maze = get_maze(&["#####", "..#..", "#####"], [0, 1], options);
+        assert_eq!(maze.get_cost_estimate([0, 1], [4, 1]), 1);
+        let solution = maze.solve(Algorithm::AStar).unwrap();
+        assert_eq!(solution.length, 1);
+        assert_eq!(maze.expand_path(&solution.path), vec![[0, 1], [4, 1]]);
+        assert_eq!(maze.solve_on_grid(Algorithm::Bfs).unwrap().length, 1);
+        let options = MazeOptions {
+            exit: Some([4, 1]),
+            ..MazeOptions::default()
+        };
+        let maze = get_maze(&["#####", "..#..", "#####"], [0, 1], options);
+        assert!(maze.solve(Algorithm::AStar).is_none());
+    }
+
+    #[test]
+    fn searches_on_a_torus_agree_with_the_tile_distances() {
+        for connectivity in fixtures::CONNECTIVITIES {
+            for seed in 1..=20 {
+                let rows: Vec<String> = fixtures::get_random_rows([18, 14], seed)
+                    .iter()
+                    .skip(1)
+                    .take(12)
+                    .map(|row| row.chars().skip(1).take(16).collect())
+                    .collect();
+                let floor: Vec<[u32; 2]> = (0..12)
+                    .flat_map(|y| (0..16).map(move |x| [x, y]))
+                    .filter(|&[x, y]| rows[y as usize].as_bytes()[x as usize] == b'.')
+                    .collect();
+                let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+                let options = MazeOptions {
+                    connectivity,
+                    topology: Topology::Torus,
+                    exit: floor.last().copied(),
+                    ..MazeOptions::default()
+                };
+                let maze = get_maze(&rows, floor[0], options);
+                let expected = fixtures::get_exit_distance(&maze);
+                for algorithm in [Algorithm::Bfs, Algorithm::Dijkstra, Algorithm::AStar] {
+                    let solution = maze.solve(algorithm);
+                    let on_grid = maze.solve_on_grid(algorithm);
+                    for solution in solution.iter().chain(on_grid.iter()) {
+                        let validation = maze.validate_path(&maze.expand_path(&solution.path));
+                        assert_eq!(validation.unwrap().length, solution.length);
+                    }
+                    if algorithm != Algorithm::Bfs {
+                        let length = solution.map(|solution| solution.length);
+                        assert_eq!(length, expected, "seed {}", seed);
+                        let on_grid = on_grid.map(|solution| solution.length);
+                        assert_eq!(on_grid, expected, "seed {}", seed);
+                    }
+                }
+            }
+        }
+    }
+}
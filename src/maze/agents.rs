@@ -0,0 +1,326 @@
+use super::d_star_lite::UNKNOWN_COLOR;
+use super::walkers::{Hand, Tremaux, WallFollower};
+use super::{Maze, MazeError};
+use img::Rgb;
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter, Result as fmtResult};
+
+/**
+ * How an agent that only knows the tiles around it decides where to go
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub enum Strategy {
+    /**
+     * Walks the shortest known way to the nearest walkable tile next to a
+     * tile it has not seen yet
+     */
+    Frontier,
+    /**
+     * Keeps its right hand on the wall
+     */
+    WallFollower,
+    /**
+     * Marks every passage it walks and never walks a passage a third time
+     */
+    Tremaux,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 3] = [
+        Strategy::Frontier,
+        Strategy::WallFollower,
+        Strategy::Tremaux,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Strategy> {
+        match name.to_lowercase().as_str() {
+            "frontier" => Some(Strategy::Frontier),
+            "wand" | "wall" => Some(Strategy::WallFollower),
+            "tremaux" | "trémaux" => Some(Strategy::Tremaux),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Strategy::Frontier => "Frontier-Erkundung",
+                Strategy::WallFollower => "Rechte-Hand-Regel",
+                Strategy::Tremaux => "Trémaux",
+            }
+        )
+    }
+}
+
+pub struct AgentWalk {
+    pub strategy: Strategy,
+    /**
+     * Every tile the agent walked over, starting at the entry
+     */
+    pub path: Vec<[u32; 2]>,
+    /**
+     * The summed up step costs of the walked path
+     */
+    pub length: isize,
+    /**
+     * Whether the agent has seen the tile
+     */
+    pub seen: Vec<bool>,
+    /**
+     * The length of the shortest way to an exit if the whole maze is known
+     */
+    pub optimal: Option<isize>,
+}
+
+impl Maze {
+    /**
+     * Lets an agent walk from the entry to an exit while it only knows the
+     * tiles within the sight range around the tiles it stood on, the sight is
+     * at least 1. As soon as the agent sees an exit it can reach over known
+     * tiles it walks there on the shortest known way, until then the
+     * strategy chooses every step. Portals are ignored.
+     * # Returns
+     * NoExitFound if the strategy gives up, the wall follower gives up when it
+     * walks in a circle and Trémaux when every passage is marked twice
+     */
+    pub fn explore(&self, strategy: Strategy, sight: u32) -> Result<AgentWalk, MazeError> {
+        let sight = sight.max(1);
+        let mut walk = AgentWalk {
+            strategy,
+            path: vec![self.entry],
+            length: 0,
+            seen: vec![false; self.maze.len()],
+            optimal: self.get_shortest_exit_distance(),
+        };
+        let mut wall_follower = WallFollower::new(self, Hand::Right);
+        let mut tremaux = Tremaux::new(self);
+        let mut route: VecDeque<([i64; 2], [u32; 2])> = VecDeque::new();
+        let mut is_leaving = false;
+        let mut sees_exit = false;
+        let mut has_news = self.look_for_exits(self.entry, sight, &mut walk.seen, &mut sees_exit);
+        loop {
+            let tile = *walk.path.last().unwrap();
+            if self.is_exit_tile(tile[0], tile[1]) {
+                return Ok(walk);
+            }
+            if has_news && !is_leaving {
+                let exit_route = sees_exit
+                    .then(|| {
+                        self.get_known_route(tile, &walk.seen, &|[x, y]| self.is_exit_tile(x, y))
+                    })
+                    .flatten();
+                if let Some(exit_route) = exit_route {
+                    route = exit_route;
+                    is_leaving = true;
+                } else if route
+                    .back()
+                    .is_some_and(|&(_, target)| !self.is_frontier(target, &walk.seen))
+                {
+                    route.clear();
+                }
+            }
+            if route.is_empty() {
+                let previous = walk.path.len().checked_sub(2).map(|index| walk.path[index]);
+                route = match strategy {
+                    Strategy::Frontier => self
+                        .get_known_route(tile, &walk.seen, &|target| {
+                            self.is_frontier(target, &walk.seen)
+                        })
+                        .ok_or(MazeError::NoExitFound)?,
+                    Strategy::WallFollower => {
+                        VecDeque::from(vec![wall_follower.get_next_step(tile)?])
+                    }
+                    Strategy::Tremaux => {
+                        VecDeque::from(vec![tremaux.get_next_step(tile, previous)?])
+                    }
+                };
+            }
+            let (step, next) = route.pop_front().unwrap();
+            walk.length += self.get_move_cost(tile[0], tile[1], step);
+            walk.path.push(next);
+            has_news = self.look_for_exits(next, sight, &mut walk.seen, &mut sees_exit);
+        }
+    }
+
+    /**
+     * Looks around the tile like look_around and remembers whether a walkable
+     * exit has been seen, so the way to the exits is only searched once there
+     * is one.
+     * # Returns
+     * True if a tile has been seen for the first time
+     */
+    fn look_for_exits(
+        &self,
+        tile: [u32; 2],
+        sight: u32,
+        seen: &mut [bool],
+        sees_exit: &mut bool,
+    ) -> bool {
+        let changes = self.look_around(tile, sight, seen);
+        *sees_exit |= changes
+            .iter()
+            .any(|&([x, y], cost)| cost.is_some() && self.is_exit_tile(x, y));
+        !changes.is_empty()
+    }
+
+    /**
+     * # Returns
+     * True if the tile is walkable, seen and lies next to a tile that has not
+     * been seen yet, doors count as walls
+     */
+    fn is_frontier(&self, [x, y]: [u32; 2], seen: &[bool]) -> bool {
+        let index = |[x, y]: [u32; 2]| (y * self.size[0] + x) as usize;
+        seen[index([x, y])]
+            && !self.maze[index([x, y])]
+            && !self.is_door([x, y])
+            && (-1..=1)
+                .flat_map(|step_y| (-1..=1).map(move |step_x| [step_x, step_y]))
+                .filter_map(|step| self.offset(x, y, step))
+                .any(|neighbor| !seen[index(neighbor)])
+    }
+
+    /**
+     * Runs a BFS from the tile over the seen tiles.
+     * # Returns
+     * The steps to the nearest target together with the tiles they lead to,
+     * None if no target can be reached over seen tiles
+     */
+    fn get_known_route(
+        &self,
+        from: [u32; 2],
+        seen: &[bool],
+        is_target: &dyn Fn([u32; 2]) -> bool,
+    ) -> Option<VecDeque<([i64; 2], [u32; 2])>> {
+        let index = |[x, y]: [u32; 2]| (y * self.size[0] + x) as usize;
+        let mut parents: Vec<Option<([i64; 2], [u32; 2])>> = vec![None; self.maze.len()];
+        let mut visited = vec![false; self.maze.len()];
+        let mut queue: VecDeque<[u32; 2]> = VecDeque::new();
+        visited[index(from)] = true;
+        queue.push_back(from);
+        while let Some(tile) = queue.pop_front() {
+            if tile != from && is_target(tile) {
+                let mut route: VecDeque<([i64; 2], [u32; 2])> = VecDeque::new();
+                let mut current = tile;
+                while let Some((step, parent)) = parents[index(current)] {
+                    route.push_front((step, current));
+                    current = parent;
+                }
+                return Some(route);
+            }
+            for (step, next) in self.get_unlocked_moves(tile[0], tile[1]) {
+                if seen[index(next)] && !visited[index(next)] {
+                    visited[index(next)] = true;
+                    parents[index(next)] = Some((step, tile));
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /**
+     * Draws the walked tiles in red and every tile the agent has not seen in
+     * gray.
+     */
+    pub fn export_agent_png(&self, path: String, walk: &AgentWalk) {
+        let mut image = self.get_maze_image();
+        for (index, _) in walk
+            .seen
+            .iter()
+            .enumerate()
+            .filter(|(_, &is_seen)| !is_seen)
+        {
+            let [x, y] = [index as u32 % self.size[0], index as u32 / self.size[0]];
+            image.put_pixel(x, y, UNKNOWN_COLOR);
+        }
+        for &[x, y] in &walk.path {
+            image.put_pixel(x, y, Rgb([255, 0, 0]));
+        }
+        image.save(&path).unwrap();
+        println!("Bild wurde in {} gespeichert.", path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Strategy;
+    use crate::maze::fixtures::{get_exit_distance, get_maze, get_random_maze, CONNECTIVITIES};
+    use crate::maze::{MazeError, MazeOptions};
+
+    #[test]
+    fn strategies_are_found_by_their_names() {
+        assert!(Strategy::from_name("Frontier") == Some(Strategy::Frontier));
+        assert!(Strategy::from_name("wand") == Some(Strategy::WallFollower));
+        assert!(Strategy::from_name("wall") == Some(Strategy::WallFollower));
+        assert!(Strategy::from_name("Trémaux") == Some(Strategy::Tremaux));
+        assert!(Strategy::from_name("random").is_none());
+    }
+
+    #[test]
+    fn agents_give_up_in_closed_mazes() {
+        let maze = get_maze(
+            &["#.###", "#...#", "#.#.#", "#...#", "#####"],
+            [1, 0],
+            MazeOptions::default(),
+        );
+        for strategy in Strategy::ALL {
+            assert!(matches!(
+                maze.explore(strategy, 1),
+                Err(MazeError::NoExitFound)
+            ));
+        }
+    }
+
+    #[test]
+    fn agents_that_see_everything_walk_straight_out() {
+        let maze = get_maze(
+            &["#.###", "#...#", "#.#.#", "#...#", "###.#"],
+            [1, 0],
+            MazeOptions::default(),
+        );
+        for strategy in Strategy::ALL {
+            let walk = maze.explore(strategy, 5).unwrap();
+            assert_eq!(walk.optimal, Some(6));
+            assert_eq!(walk.length, 6);
+            assert!(walk.seen.iter().all(|&seen| seen));
+        }
+    }
+
+    #[test]
+    fn agents_walk_valid_paths_to_an_exit() {
+        for connectivity in CONNECTIVITIES {
+            for seed in 1..=15 {
+                let options = MazeOptions {
+                    connectivity,
+                    ..MazeOptions::default()
+                };
+                let maze = get_random_maze([18, 13], seed, options);
+                let expected = get_exit_distance(&maze);
+                for strategy in Strategy::ALL {
+                    match maze.explore(strategy, 1) {
+                        Ok(walk) => {
+                            assert_eq!(walk.optimal, expected);
+                            let validation = maze.validate_path(&walk.path).unwrap();
+                            assert_eq!(validation.length, walk.length);
+                            assert!(expected.is_some_and(|shortest| shortest <= walk.length));
+                            let width = maze.get_size()[0];
+                            assert!(walk
+                                .path
+                                .iter()
+                                .all(|&[x, y]| walk.seen[(y * width + x) as usize]));
+                        }
+                        Err(_) => assert!(
+                            strategy == Strategy::WallFollower || expected.is_none(),
+                            "seed {}",
+                            seed
+                        ),
+                    }
+                }
+            }
+        }
+    }
+}
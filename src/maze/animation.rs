@@ -0,0 +1,118 @@
+use super::search::{Algorithm, Expansion};
+use super::Maze;
+use img::codecs::gif::{GifEncoder, Repeat};
+use img::imageops::{self, FilterType};
+use img::{Delay, DynamicImage, Frame, Rgb, RgbImage};
+use std::fs::File;
+
+const VISITED_COLOR: Rgb<u8> = Rgb([135, 206, 250]);
+const FRONTIER_COLOR: Rgb<u8> = Rgb([255, 165, 0]);
+const PATH_COLOR: Rgb<u8> = Rgb([255, 0, 0]);
+
+impl Maze {
+    /**
+     * Records a search and writes it as an animated GIF. A frame is taken every
+     * `every` expansions, it shows the visited corridors in blue and the ones
+     * leading to the nodes in the frontier in orange. The last frame shows the
+     * chosen path in red. Every tile is drawn as a square of `scale` pixels.
+     */
+    pub fn export_search_gif(&self, path: String, algorithm: Algorithm, every: usize, scale: u32) {
+        let (solution, trace) = self.solve_traced(algorithm);
+        let mut image = self.get_maze_image();
+        let mut frames: Vec<Frame> = Vec::new();
+        for (step, expansion) in trace.iter().enumerate() {
+            self.draw_expansion(&mut image, expansion);
+            if (step + 1) % every.max(1) == 0 {
+                frames.push(self.get_frame(&image, scale, 100));
+            }
+        }
+        if let Some(solution) = solution {
+            for [x, y] in self.expand_path(&solution.path) {
+                image.put_pixel(x, y, PATH_COLOR);
+            }
+        }
+        frames.push(self.get_frame(&image, scale, 3000));
+        let mut encoder = GifEncoder::new(File::create(&path).unwrap());
+        encoder.set_repeat(Repeat::Infinite).unwrap();
+        encoder.encode_frames(frames).unwrap();
+        println!("Animation wurde in {} gespeichert.", path);
+    }
+
+    pub(super) fn draw_expansion(&self, image: &mut RgbImage, expansion: &Expansion) {
+        let tile = self.get_graph().get_node(expansion.node).element.0;
+        match expansion.parent {
+            Some(parent) => {
+                let parent_tile = self.get_graph().get_node(parent).element.0;
+                for [x, y] in self.get_walked_tiles(parent_tile, tile) {
+                    image.put_pixel(x, y, VISITED_COLOR);
+                }
+            }
+            None => image.put_pixel(tile[0], tile[1], VISITED_COLOR),
+        }
+        for &child in &expansion.queued {
+            let child_tile = self.get_graph().get_node(child).element.0;
+            for [x, y] in self.get_walked_tiles(tile, child_tile).into_iter().skip(1) {
+                image.put_pixel(x, y, FRONTIER_COLOR);
+            }
+        }
+    }
+
+    pub(super) fn get_frame(&self, image: &RgbImage, scale: u32, delay_ms: u32) -> Frame {
+        let scaled = imageops::resize(
+            image,
+            self.size[0] * scale.max(1),
+            self.size[1] * scale.max(1),
+            FilterType::Nearest,
+        );
+        Frame::from_parts(
+            DynamicImage::ImageRgb8(scaled).to_rgba8(),
+            0,
+            0,
+            Delay::from_numer_denom_ms(delay_ms, 1),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FRONTIER_COLOR, VISITED_COLOR};
+    use crate::maze::fixtures::get_maze;
+    use crate::maze::search::Algorithm;
+    use crate::maze::MazeOptions;
+
+    const FORK: [&str; 5] = ["#.###", "#...#", "#.#.#", "#.#.#", "#.#.#"];
+
+    #[test]
+    fn traces_expand_every_node_after_its_parent() {
+        let maze = get_maze(&FORK, [1, 0], MazeOptions::default());
+        let (solution, trace) = maze.solve_traced(Algorithm::Bfs);
+        let solution = solution.unwrap();
+        assert_eq!(trace[0].node, maze.get_node_index(1, 0).unwrap());
+        assert_eq!(trace[0].parent, None);
+        for (step, expansion) in trace.iter().enumerate().skip(1) {
+            let parent = expansion.parent.unwrap();
+            assert!(trace[..step].iter().any(|earlier| earlier.node == parent));
+        }
+        let last = trace.last().unwrap().node;
+        assert_eq!(
+            maze.get_graph().get_node(last).element.0,
+            *solution.path.last().unwrap()
+        );
+        assert_eq!(solution.expanded, trace.len());
+    }
+
+    #[test]
+    fn expansions_paint_visited_and_queued_corridors() {
+        let maze = get_maze(&FORK, [1, 0], MazeOptions::default());
+        let (_, trace) = maze.solve_traced(Algorithm::Bfs);
+        let mut image = maze.get_maze_image();
+        maze.draw_expansion(&mut image, &trace[0]);
+        maze.draw_expansion(&mut image, &trace[1]);
+        assert_eq!(*image.get_pixel(1, 0), VISITED_COLOR);
+        assert_eq!(*image.get_pixel(1, 1), VISITED_COLOR);
+        assert_eq!(*image.get_pixel(2, 1), FRONTIER_COLOR);
+        assert_eq!(*image.get_pixel(1, 3), FRONTIER_COLOR);
+        let frame = maze.get_frame(&image, 3, 100);
+        assert_eq!(frame.buffer().dimensions(), (15, 15));
+    }
+}
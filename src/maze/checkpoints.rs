@@ -0,0 +1,441 @@
+use super::search::Solution;
+use super::{Maze, MazeError};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/**
+ * Up to this amount of checkpoints the best order is searched exactly, above
+ * it a heuristic is used
+ */
+pub const EXACT_CHECKPOINT_LIMIT: usize = 12;
+
+pub struct CheckpointRoute {
+    /**
+     * The indices of the checkpoints in the order they are visited
+     */
+    pub order: Vec<usize>,
+    /**
+     * False if the order was found by the heuristic and may not be optimal
+     */
+    pub is_exact: bool,
+    pub solution: Solution,
+}
+
+/**
+ * The shortest walk between two stops of the route.
+ */
+struct Leg {
+    length: isize,
+    path: Vec<[u32; 2]>,
+}
+
+impl Maze {
+    /**
+     * Finds a route from the entry through every checkpoint to the exit. The
+     * shortest walks between all of these tiles are searched on the graph,
+     * then the order of the checkpoints is chosen, exactly for up to
+     * EXACT_CHECKPOINT_LIMIT checkpoints and with nearest neighbor and 2-opt
+     * above that.
+     * # Returns
+     * NotWalkable if one of the tiles is a wall and Unreachable if there is no
+     * route through all of them
+     */
+    pub fn solve_checkpoints(
+        &self,
+        checkpoints: &[[u32; 2]],
+        exit: [u32; 2],
+    ) -> Result<CheckpointRoute, MazeError> {
+        let mut stops: Vec<[u32; 2]> = vec![self.entry];
+        stops.extend_from_slice(checkpoints);
+        stops.push(exit);
+        let mut expanded = 0;
+        let mut legs: Vec<Vec<Option<Leg>>> = Vec::with_capacity(stops.len());
+        for &from in &stops {
+            legs.push(self.get_legs(from, &stops, &mut expanded)?);
+        }
+        let distances: Vec<Vec<Option<isize>>> = legs
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|leg| leg.as_ref().map(|leg| leg.length))
+                    .collect()
+            })
+            .collect();
+        let is_exact = checkpoints.len() <= EXACT_CHECKPOINT_LIMIT;
+        let order = if is_exact {
+            get_exact_order(&distances)
+        } else {
+            get_heuristic_order(&distances)
+        }
+        .ok_or(MazeError::Unreachable)?;
+        let mut visits: Vec<usize> = vec![0];
+        visits.extend(order.iter().map(|&checkpoint| checkpoint + 1));
+        visits.push(stops.len() - 1);
+        let mut path: Vec<[u32; 2]> = vec![self.entry];
+        let mut length = 0;
+        for pair in visits.windows(2) {
+            let leg = legs[pair[0]][pair[1]].as_ref().unwrap();
+            length += leg.length;
+            path.extend(leg.path.iter().skip(1));
+        }
+        Ok(CheckpointRoute {
+            order,
+            is_exact,
+            solution: Solution {
+                path,
+                length,
+                expanded,
+            },
+        })
+    }
+
+    /**
+     * Runs Dijkstra from the nodes around one stop.
+     * # Returns
+     * The shortest walk to every stop, None for those that cannot be reached
+     */
+    fn get_legs(
+        &self,
+        from: [u32; 2],
+        stops: &[[u32; 2]],
+        expanded: &mut usize,
+    ) -> Result<Vec<Option<Leg>>, MazeError> {
+        let graph = self.get_graph();
+        let starts = self.get_corridor_ends(from[0], from[1]);
+        if starts.is_empty() {
+            return Err(MazeError::NotWalkable);
+        }
+        let mut distances: Vec<Option<isize>> = vec![None; graph.get_node_amount()];
+        let mut parents: Vec<Option<usize>> = vec![None; graph.get_node_amount()];
+        let mut heap: BinaryHeap<Reverse<(isize, usize)>> = BinaryHeap::new();
+        for &(node_index, cost, _) in &starts {
+            if self.is_door_node(node_index) {
+                continue;
+            }
+            if distances[node_index].is_none_or(|known| cost < known) {
+                distances[node_index] = Some(cost);
+                heap.push(Reverse((cost, node_index)));
+            }
+        }
+        while let Some(Reverse((distance, node_index))) = heap.pop() {
+            if distances[node_index] != Some(distance) {
+                continue;
+            }
+            *expanded += 1;
+            for &(neighbor, weight) in graph.get_weighted_neighbors(node_index) {
+                if self.is_door_node(neighbor) {
+                    continue;
+                }
+                let neighbor_distance = distance + weight;
+                if distances[neighbor].is_none_or(|known| neighbor_distance < known) {
+                    distances[neighbor] = Some(neighbor_distance);
+                    parents[neighbor] = Some(node_index);
+                    heap.push(Reverse((neighbor_distance, neighbor)));
+                }
+            }
+        }
+        let mut legs: Vec<Option<Leg>> = Vec::with_capacity(stops.len());
+        for &to in stops {
+            let ends = self.get_corridor_ends(to[0], to[1]);
+            if ends.is_empty() {
+                return Err(MazeError::NotWalkable);
+            }
+            let mut leg = ends
+                .iter()
+                .filter_map(|&(node_index, _, back_cost)| {
+                    Some((distances[node_index]? + back_cost?, node_index))
+                })
+                .min()
+                .map(|(length, node_index)| {
+                    let mut nodes = vec![node_index];
+                    while let Some(parent) = parents[*nodes.last().unwrap()] {
+                        nodes.push(parent);
+                    }
+                    let mut path: Vec<[u32; 2]> = vec![from];
+                    for &node_index in nodes.iter().rev() {
+                        path.push(graph.get_node(node_index).element.0);
+                    }
+                    path.push(to);
+                    path.dedup();
+                    Leg { length, path }
+                });
+            if let Some(direct_length) = self.get_direct_walk(from, to) {
+                if leg.as_ref().is_none_or(|leg| direct_length < leg.length) {
+                    leg = Some(Leg {
+                        length: direct_length,
+                        path: vec![from, to],
+                    });
+                }
+            }
+            legs.push(leg);
+        }
+        Ok(legs)
+    }
+
+    /**
+     * # Returns
+     * The cost of walking in a straight line from one tile to another without
+     * passing a node on the way, None if that is not possible
+     */
+    fn get_direct_walk(&self, from: [u32; 2], to: [u32; 2]) -> Option<isize> {
+        if from == to {
+            return Some(0);
+        }
+        self.get_straight_walk(from, to, false)
+            .map(|walk| walk.cost)
+    }
+}
+
+/**
+ * Held-Karp over the checkpoints, the first stop is the start and the last
+ * one the end of the route.
+ * # Returns
+ * The best order of the checkpoints in between, None if there is no route
+ */
+fn get_exact_order(distances: &[Vec<Option<isize>>]) -> Option<Vec<usize>> {
+    let checkpoint_amount = distances.len() - 2;
+    let end = distances.len() - 1;
+    if checkpoint_amount == 0 {
+        return distances[0][end].map(|_| Vec::new());
+    }
+    let mask_amount = 1 << checkpoint_amount;
+    let mut costs: Vec<Vec<Option<isize>>> = vec![vec![None; checkpoint_amount]; mask_amount];
+    let mut parents: Vec<Vec<Option<usize>>> = vec![vec![None; checkpoint_amount]; mask_amount];
+    for checkpoint in 0..checkpoint_amount {
+        costs[1 << checkpoint][checkpoint] = distances[0][checkpoint + 1];
+    }
+    for mask in 1..mask_amount {
+        for last in 0..checkpoint_amount {
+            let cost = match costs[mask][last] {
+                Some(cost) => cost,
+                None => continue,
+            };
+            for next in 0..checkpoint_amount {
+                if mask & (1 << next) != 0 {
+                    continue;
+                }
+                if let Some(distance) = distances[last + 1][next + 1] {
+                    let next_mask = mask | (1 << next);
+                    if costs[next_mask][next].is_none_or(|known| cost + distance < known) {
+                        costs[next_mask][next] = Some(cost + distance);
+                        parents[next_mask][next] = Some(last);
+                    }
+                }
+            }
+        }
+    }
+    let full_mask = mask_amount - 1;
+    let (_, mut last) = (0..checkpoint_amount)
+        .filter_map(|last| {
+            let cost = costs[full_mask][last]? + distances[last + 1][end]?;
+            Some((cost, last))
+        })
+        .min()?;
+    let mut order = vec![last];
+    let mut mask = full_mask;
+    while let Some(parent) = parents[mask][last] {
+        mask &= !(1 << last);
+        last = parent;
+        order.push(last);
+    }
+    order.reverse();
+    Some(order)
+}
+
+/**
+ * Builds an order by always walking to the nearest unvisited checkpoint and
+ * improves it by reversing parts of it as long as that makes it shorter.
+ * # Returns
+ * None if the nearest neighbor walk gets stuck
+ */
+fn get_heuristic_order(distances: &[Vec<Option<isize>>]) -> Option<Vec<usize>> {
+    let checkpoint_amount = distances.len() - 2;
+    let mut order: Vec<usize> = Vec::with_capacity(checkpoint_amount);
+    let mut visited = vec![false; checkpoint_amount];
+    let mut current = 0;
+    for _ in 0..checkpoint_amount {
+        let (_, next) = (0..checkpoint_amount)
+            .filter(|&checkpoint| !visited[checkpoint])
+            .filter_map(|checkpoint| {
+                distances[current][checkpoint + 1].map(|distance| (distance, checkpoint))
+            })
+            .min()?;
+        visited[next] = true;
+        order.push(next);
+        current = next + 1;
+    }
+    let mut best = get_order_length(distances, &order)?;
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for first in 0..checkpoint_amount {
+            for last in first + 1..checkpoint_amount {
+                order[first..=last].reverse();
+                match get_order_length(distances, &order) {
+                    Some(length) if length < best => {
+                        best = length;
+                        improved = true;
+                    }
+                    _ => order[first..=last].reverse(),
+                }
+            }
+        }
+    }
+    Some(order)
+}
+
+fn get_order_length(distances: &[Vec<Option<isize>>], order: &[usize]) -> Option<isize> {
+    let mut visits: Vec<usize> = vec![0];
+    visits.extend(order.iter().map(|&checkpoint| checkpoint + 1));
+    visits.push(distances.len() - 1);
+    visits
+        .windows(2)
+        .map(|pair| distances[pair[0]][pair[1]])
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_exact_order, get_heuristic_order, get_order_length};
+    use crate::maze::fixtures::{get_maze, get_random_maze, get_tile_distances};
+    use crate::maze::{MazeError, MazeOptions};
+
+    /**
+     * # Returns
+     * Every order of the given checkpoints
+     */
+    fn get_permutations(checkpoints: &[usize]) -> Vec<Vec<usize>> {
+        if checkpoints.is_empty() {
+            return vec![Vec::new()];
+        }
+        let mut permutations: Vec<Vec<usize>> = Vec::new();
+        for (index, &first) in checkpoints.iter().enumerate() {
+            let mut rest = checkpoints.to_vec();
+            rest.remove(index);
+            for mut permutation in get_permutations(&rest) {
+                permutation.insert(0, first);
+                permutations.push(permutation);
+            }
+        }
+        permutations
+    }
+
+    /**
+     * # Returns
+     * The length of the best order found by trying all of them
+     */
+    fn get_best_length(distances: &[Vec<Option<isize>>]) -> Option<isize> {
+        let checkpoints: Vec<usize> = (0..distances.len() - 2).collect();
+        get_permutations(&checkpoints)
+            .iter()
+            .filter_map(|order| get_order_length(distances, order))
+            .min()
+    }
+
+    #[test]
+    fn orders_agree_with_trying_every_order() {
+        let mut random: u64 = 7;
+        let mut next = move |below: u64| {
+            random ^= random << 13;
+            random ^= random >> 7;
+            random ^= random << 17;
+            random % below
+        };
+        for stop_amount in 2..=8 {
+            for _ in 0..20 {
+                let distances: Vec<Vec<Option<isize>>> = (0..stop_amount)
+                    .map(|_| {
+                        (0..stop_amount)
+                            .map(|_| match next(6) {
+                                0 => None,
+                                _ => Some(1 + next(50) as isize),
+                            })
+                            .collect()
+                    })
+                    .collect();
+                let best = get_best_length(&distances);
+                let exact = get_exact_order(&distances);
+                assert_eq!(
+                    exact.and_then(|order| get_order_length(&distances, &order)),
+                    best
+                );
+                if let Some(order) = get_heuristic_order(&distances) {
+                    let mut visited = order.clone();
+                    visited.sort_unstable();
+                    assert_eq!(visited, (0..stop_amount - 2).collect::<Vec<usize>>());
+                    let length = get_order_length(&distances, &order).unwrap();
+                    assert!(best.is_some_and(|best| best <= length));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn routes_visit_every_checkpoint_in_the_best_order() {
+        for seed in 1..=30 {
+            let maze = get_random_maze([20, 14], seed, MazeOptions::default());
+            let from_entry = get_tile_distances(&maze, maze.get_entry());
+            let index = |[x, y]: [u32; 2]| (y * 20 + x) as usize;
+            let reachable: Vec<[u32; 2]> = (0..14)
+                .flat_map(|y| (0..20).map(move |x| [x, y]))
+                .filter(|&tile| from_entry[index(tile)].is_some() && tile != maze.get_entry())
+                .collect();
+            let exit = match maze
+                .get_openings_except_entry()
+                .into_iter()
+                .find(|&opening| from_entry[index(opening)].is_some())
+            {
+                Some(exit) => exit,
+                None => continue,
+            };
+            let checkpoints: Vec<[u32; 2]> = reachable
+                .iter()
+                .step_by(reachable.len() / 4 + 1)
+                .copied()
+                .collect();
+            let mut stops = vec![maze.get_entry()];
+            stops.extend(&checkpoints);
+            stops.push(exit);
+            let distances: Vec<Vec<Option<isize>>> = stops
+                .iter()
+                .map(|&from| {
+                    let from_stop = get_tile_distances(&maze, from);
+                    stops.iter().map(|&to| from_stop[index(to)]).collect()
+                })
+                .collect();
+            let route = maze.solve_checkpoints(&checkpoints, exit).unwrap();
+            assert!(route.is_exact);
+            assert_eq!(
+                Some(route.solution.length),
+                get_best_length(&distances),
+                "seed {}",
+                seed
+            );
+            let tiles = maze.expand_path(&route.solution.path);
+            let validation = maze.validate_path(&tiles).unwrap();
+            assert_eq!(validation.length, route.solution.length);
+            assert!(checkpoints
+                .iter()
+                .all(|checkpoint| tiles.contains(checkpoint)));
+        }
+    }
+
+    #[test]
+    fn walls_and_separated_checkpoints_are_refused() {
+        let maze = get_maze(
+            &["#.###", "#.#.#", "#.#.#", "#.###", "#.###"],
+            [1, 0],
+            MazeOptions::default(),
+        );
+        assert!(matches!(
+            maze.solve_checkpoints(&[[2, 2]], [1, 4]),
+            Err(MazeError::NotWalkable)
+        ));
+        assert!(matches!(
+            maze.solve_checkpoints(&[[3, 2]], [1, 4]),
+            Err(MazeError::Unreachable)
+        ));
+        let route = maze.solve_checkpoints(&[[1, 2]], [1, 4]).unwrap();
+        assert_eq!((route.order, route.solution.length), (vec![0], 4));
+    }
+}
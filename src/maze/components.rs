@@ -0,0 +1,194 @@
+use super::Maze;
+use img::{Rgb, RgbImage};
+use std::collections::VecDeque;
+
+pub struct Component {
+    pub tiles: usize,
+    pub nodes: usize,
+    pub openings: Vec<[u32; 2]>,
+}
+
+pub struct ComponentReport {
+    pub components: Vec<Component>,
+    pub entry_component: Option<usize>,
+}
+
+impl ComponentReport {
+    /**
+     * # Returns
+     * The openings that lie in the same component as the entry, the entry
+     * itself excluded
+     */
+    pub fn get_reachable_openings(&self, entry: [u32; 2]) -> Vec<[u32; 2]> {
+        match self.entry_component {
+            Some(component) => self.components[component]
+                .openings
+                .iter()
+                .filter(|&&opening| opening != entry)
+                .copied()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Maze {
+    /**
+     * Labels every walkable tile with the index of the connected component it
+     * belongs to, walls are labeled with None. One-way tiles connect their
+     * neighbors in both directions here, portals join the tiles at both of
+     * their ends and doors are walkable.
+     * # Returns
+     * The amount of components and the labels in row major order
+     */
+    pub fn get_tile_components(&self) -> (usize, Vec<Option<usize>>) {
+        let mut labels: Vec<Option<usize>> = vec![None; self.maze.len()];
+        let mut amount = 0;
+        let mut queue: VecDeque<[u32; 2]> = VecDeque::new();
+        for y in 0..self.size[1] {
+            for x in 0..self.size[0] {
+                let index = (y * self.size[0] + x) as usize;
+                if self.maze[index] || labels[index].is_some() {
+                    continue;
+                }
+                labels[index] = Some(amount);
+                queue.push_back([x, y]);
+                while let Some([x, y]) = queue.pop_front() {
+                    let successors = self.get_moves(x, y).into_iter().map(|(_, tile)| tile);
+                    let neighbors = successors
+                        .chain(self.get_predecessors(x, y))
+                        .chain(self.get_portal_partner([x, y]));
+                    for [next_x, next_y] in neighbors {
+                        let next_index = (next_y * self.size[0] + next_x) as usize;
+                        if labels[next_index].is_none() {
+                            labels[next_index] = Some(amount);
+                            queue.push_back([next_x, next_y]);
+                        }
+                    }
+                }
+                amount += 1;
+            }
+        }
+        (amount, labels)
+    }
+
+    /**
+     * # Returns
+     * Every walkable tile on the border of the maze
+     */
+    pub fn get_openings(&self) -> Vec<[u32; 2]> {
+        (0..self.get_graph().get_node_amount())
+            .map(|index| self.get_graph().get_node(index).element)
+            .filter(|&(_, is_opening)| is_opening)
+            .map(|(tile, _)| tile)
+            .collect()
+    }
+
+    /**
+     * Groups the tiles, graph nodes and openings of the maze by the connected
+     * component they belong to, which shows where an unsolvable maze is
+     * sealed off.
+     */
+    pub fn get_component_report(&self) -> ComponentReport {
+        let (amount, labels) = self.get_tile_components();
+        let mut components: Vec<Component> = (0..amount)
+            .map(|_| Component {
+                tiles: 0,
+                nodes: 0,
+                openings: Vec::new(),
+            })
+            .collect();
+        for label in labels.iter().flatten() {
+            components[*label].tiles += 1;
+        }
+        for index in 0..self.get_graph().get_node_amount() {
+            let ([x, y], is_opening) = self.get_graph().get_node(index).element;
+            let component = &mut components[labels[(y * self.size[0] + x) as usize].unwrap()];
+            component.nodes += 1;
+            if is_opening {
+                component.openings.push([x, y]);
+            }
+        }
+        let [entry_x, entry_y] = self.entry;
+        ComponentReport {
+            components,
+            entry_component: labels[(entry_y * self.size[0] + entry_x) as usize],
+        }
+    }
+
+    pub fn export_components_png(&self, path: String) {
+        let (_, labels) = self.get_tile_components();
+        let mut image = RgbImage::new(self.size[0], self.size[1]);
+        for x in 0..self.size[0] {
+            for y in 0..self.size[1] {
+                let pixel = match labels[(y * self.size[0] + x) as usize] {
+                    Some(label) => get_component_color(label),
+                    None => Rgb([0, 0, 0]),
+                };
+                image.put_pixel(x, y, pixel);
+            }
+        }
+        image.save(&path).unwrap();
+        println!("Bild wurde in {} gespeichert.", path);
+    }
+}
+
+/**
+ * Picks well distinguishable colors by stepping the hue by the golden ratio.
+ */
+pub fn get_component_color(index: usize) -> Rgb<u8> {
+    let hue = (index as f64 * 0.618_033_988_75).fract() * 6.0;
+    let (saturation, value) = (0.65, 0.95);
+    let chroma = value * saturation;
+    let second = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (red, green, blue) = match hue as u32 {
+        0 => (chroma, second, 0.0),
+        1 => (second, chroma, 0.0),
+        2 => (0.0, chroma, second),
+        3 => (0.0, second, chroma),
+        4 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+    let offset = value - chroma;
+    Rgb([
+        ((red + offset) * 255.0) as u8,
+        ((green + offset) * 255.0) as u8,
+        ((blue + offset) * 255.0) as u8,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::fixtures::get_maze;
+    use crate::maze::MazeOptions;
+
+    const SEALED: [&str; 5] = ["###.###", "#...#..", "#.###.#", "#..##.#", "#####.#"];
+
+    #[test]
+    fn sealed_off_entry_has_no_reachable_openings() {
+        let maze = get_maze(&SEALED, [3, 0], MazeOptions::default());
+        let (amount, labels) = maze.get_tile_components();
+        assert_eq!(amount, 2);
+        assert_eq!(labels[3], Some(0));
+        assert_eq!(labels[0], None);
+        let report = maze.get_component_report();
+        assert_eq!(report.entry_component, Some(0));
+        assert_eq!(report.components[0].tiles, 7);
+        assert_eq!(report.components[0].openings, vec![[3, 0]]);
+        assert_eq!(report.components[1].tiles, 5);
+        assert_eq!(report.components[1].openings, vec![[6, 1], [5, 4]]);
+        assert!(report.get_reachable_openings([3, 0]).is_empty());
+    }
+
+    #[test]
+    fn portals_join_the_components_at_both_ends() {
+        let options = MazeOptions {
+            portals: vec![([2, 3], [5, 3])],
+            ..MazeOptions::default()
+        };
+        let maze = get_maze(&SEALED, [3, 0], options);
+        assert_eq!(maze.get_tile_components().0, 1);
+        let report = maze.get_component_report();
+        assert_eq!(report.get_reachable_openings([3, 0]), vec![[6, 1], [5, 4]]);
+    }
+}
@@ -0,0 +1,560 @@
+use super::{Connectivity, CornerCutting, Maze, MazeError};
+use img::codecs::gif::{GifEncoder, Repeat};
+use img::{Frame, Rgb, RgbImage};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+
+/**
+ * The cost of everything that cannot be reached, large enough to never be a
+ * real cost and small enough to be added up without overflowing
+ */
+const UNREACHABLE: isize = isize::MAX / 4;
+
+pub(super) const UNKNOWN_COLOR: Rgb<u8> = Rgb([128, 128, 128]);
+const WALKED_COLOR: Rgb<u8> = Rgb([255, 0, 0]);
+const PLAN_COLOR: Rgb<u8> = Rgb([255, 165, 0]);
+
+/**
+ * Plans the cheapest way from a moving start to the exits of the maze while
+ * the cost of tiles changes. Searches backwards from the exits like A* and
+ * keeps the costs of that search, so a change only needs the costs around
+ * the changed tiles to be repaired (D* Lite by Koenig and Likhachev). The
+ * planner only knows the tiles it was told about, every other tile is
+ * believed to be walkable with the lowest terrain cost of the maze. Portals
+ * are ignored.
+ */
+pub struct DStarLite<'a> {
+    maze: &'a Maze,
+    /**
+     * The believed terrain cost of every tile, None for walls
+     */
+    costs: Vec<Option<isize>>,
+    /**
+     * The cost of the cheapest way from every tile to an exit as known to the
+     * search, UNREACHABLE if it is not known yet
+     */
+    distances: Vec<isize>,
+    /**
+     * The cost of the cheapest way over a neighbor, if it differs from the
+     * distance the tile has to be expanded again
+     */
+    lookaheads: Vec<isize>,
+    /**
+     * The key every tile is queued with, None if it is not queued
+     */
+    keys: Vec<Option<[isize; 2]>>,
+    queue: BinaryHeap<Reverse<([isize; 2], [u32; 2])>>,
+    is_exit: Vec<bool>,
+    start: [u32; 2],
+    /**
+     * The start at the time of the last change of costs
+     */
+    last_start: [u32; 2],
+    /**
+     * Keeps the old keys valid after the start moved by adding up how far it
+     * moved since the search began
+     */
+    key_modifier: isize,
+    pub expanded: usize,
+}
+
+pub struct Exploration {
+    /**
+     * Every tile the robot walked over, starting at the entry
+     */
+    pub path: Vec<[u32; 2]>,
+    /**
+     * The summed up step costs of the walked path
+     */
+    pub length: isize,
+    /**
+     * How often the planner had to repair its plan after seeing a tile that
+     * differed from its belief
+     */
+    pub replans: usize,
+    pub expanded: usize,
+}
+
+impl<'a> DStarLite<'a> {
+    /**
+     * Creates a planner that believes every tile to be walkable, the exits
+     * are every tile on the border but the start or the explicit exit.
+     */
+    pub fn new(maze: &'a Maze, start: [u32; 2]) -> DStarLite<'a> {
+        let tiles = maze.maze.len();
+        let mut planner = DStarLite {
+            maze,
+            costs: vec![Some(maze.min_cost); tiles],
+            distances: vec![UNREACHABLE; tiles],
+            lookaheads: vec![UNREACHABLE; tiles],
+            keys: vec![None; tiles],
+            queue: BinaryHeap::new(),
+            is_exit: vec![false; tiles],
+            start,
+            last_start: start,
+            key_modifier: 0,
+            expanded: 0,
+        };
+        for y in 0..maze.size[1] {
+            for x in 0..maze.size[0] {
+                let is_exit = match maze.options.exit {
+                    Some(exit) => [x, y] == exit,
+                    None => maze.is_border(x, y) && [x, y] != start,
+                };
+                if is_exit {
+                    let index = planner.index([x, y]);
+                    planner.is_exit[index] = true;
+                    planner.lookaheads[index] = 0;
+                    planner.queue_tile([x, y]);
+                }
+            }
+        }
+        planner
+    }
+
+    fn index(&self, [x, y]: [u32; 2]) -> usize {
+        (y * self.maze.size[0] + x) as usize
+    }
+
+    /**
+     * # Returns
+     * The cost of taking the step as far as the planner knows, UNREACHABLE if
+     * it leads onto or comes from a wall or cuts a corner
+     */
+    fn get_cost(&self, from: [u32; 2], step: [i64; 2]) -> Option<([u32; 2], isize)> {
+        let to = self.maze.offset(from[0], from[1], step)?;
+        if !self.maze.can_leave(from, step) {
+            return None;
+        }
+        let is_wall = |tile: [u32; 2]| self.costs[self.index(tile)].is_none();
+        let cost = match self.costs[self.index(to)] {
+            Some(_) if is_wall(from) => UNREACHABLE,
+            Some(cost) => {
+                let corners = [[to[0], from[1]], [from[0], to[1]]];
+                let is_cut = step[0] != 0
+                    && step[1] != 0
+                    && match self.maze.options.connectivity {
+                        Connectivity::Eight(CornerCutting::ForbidIfEither) => {
+                            corners.iter().any(|&corner| is_wall(corner))
+                        }
+                        Connectivity::Eight(CornerCutting::ForbidIfBoth) => {
+                            corners.iter().all(|&corner| is_wall(corner))
+                        }
+                        _ => false,
+                    };
+                if is_cut {
+                    UNREACHABLE
+                } else {
+                    self.maze.get_step_cost(step) * cost
+                }
+            }
+            None => UNREACHABLE,
+        };
+        Some((to, cost))
+    }
+
+    fn get_key(&self, tile: [u32; 2]) -> [isize; 2] {
+        let index = self.index(tile);
+        let distance = self.distances[index].min(self.lookaheads[index]);
+        if distance >= UNREACHABLE {
+            return [UNREACHABLE, UNREACHABLE];
+        }
+        let estimate = self.maze.get_cost_estimate(self.start, tile);
+        [distance + estimate + self.key_modifier, distance]
+    }
+
+    fn queue_tile(&mut self, tile: [u32; 2]) {
+        let key = self.get_key(tile);
+        let index = self.index(tile);
+        self.keys[index] = Some(key);
+        self.queue.push(Reverse((key, tile)));
+    }
+
+    /**
+     * Recalculates the lookahead of the tile from its neighbors and queues it
+     * if it no longer matches its distance.
+     */
+    fn update_tile(&mut self, tile: [u32; 2]) {
+        let index = self.index(tile);
+        if !self.is_exit[index] {
+            self.lookaheads[index] = self
+                .maze
+                .get_steps()
+                .into_iter()
+                .filter_map(|step| self.get_cost(tile, step))
+                .map(|(to, cost)| (cost + self.distances[self.index(to)]).min(UNREACHABLE))
+                .min()
+                .unwrap_or(UNREACHABLE);
+        }
+        self.keys[index] = None;
+        if self.distances[index] != self.lookaheads[index] {
+            self.queue_tile(tile);
+        }
+    }
+
+    /**
+     * # Returns
+     * Every tile a step can be taken from onto the given tile, the tile
+     * itself included
+     */
+    fn get_neighborhood(&self, tile: [u32; 2]) -> Vec<[u32; 2]> {
+        let mut tiles = vec![tile];
+        for step_y in -1..=1 {
+            for step_x in -1..=1 {
+                if let Some(next) = self.maze.offset(tile[0], tile[1], [step_x, step_y]) {
+                    if !tiles.contains(&next) {
+                        tiles.push(next);
+                    }
+                }
+            }
+        }
+        tiles
+    }
+
+    /**
+     * Expands queued tiles until the distance of the start is known.
+     * # Returns
+     * The cost of the cheapest way from the start to an exit, None if there
+     * is none as far as the planner knows
+     */
+    pub fn plan(&mut self) -> Option<isize> {
+        let start_index = self.index(self.start);
+        while let Some(&Reverse((key, tile))) = self.queue.peek() {
+            let index = self.index(tile);
+            if self.keys[index] != Some(key) {
+                self.queue.pop();
+                continue;
+            }
+            if key >= self.get_key(self.start)
+                && self.lookaheads[start_index] == self.distances[start_index]
+            {
+                break;
+            }
+            self.queue.pop();
+            self.expanded += 1;
+            let new_key = self.get_key(tile);
+            if key < new_key {
+                self.queue_tile(tile);
+            } else if self.distances[index] > self.lookaheads[index] {
+                self.distances[index] = self.lookaheads[index];
+                self.keys[index] = None;
+                for neighbor in self.get_neighborhood(tile).into_iter().skip(1) {
+                    self.update_tile(neighbor);
+                }
+            } else {
+                self.distances[index] = UNREACHABLE;
+                for neighbor in self.get_neighborhood(tile) {
+                    self.update_tile(neighbor);
+                }
+            }
+        }
+        let distance = self.lookaheads[start_index];
+        (distance < UNREACHABLE).then_some(distance)
+    }
+
+    /**
+     * Tells the planner the real cost of tiles, None for walls.
+     * # Returns
+     * True if one of them differed from what the planner believed, then the
+     * plan has to be repaired with plan
+     */
+    pub fn set_costs(&mut self, changes: &[([u32; 2], Option<isize>)]) -> bool {
+        let changed: Vec<[u32; 2]> = changes
+            .iter()
+            .filter(|&&(tile, cost)| self.costs[self.index(tile)] != cost)
+            .map(|&(tile, _)| tile)
+            .collect();
+        if changed.is_empty() {
+            return false;
+        }
+        self.key_modifier += self.maze.get_cost_estimate(self.last_start, self.start);
+        self.last_start = self.start;
+        for &(tile, cost) in changes {
+            let index = self.index(tile);
+            self.costs[index] = cost.map(|cost| cost.max(self.maze.min_cost));
+        }
+        for tile in changed {
+            for neighbor in self.get_neighborhood(tile) {
+                self.update_tile(neighbor);
+            }
+        }
+        true
+    }
+
+    /**
+     * # Returns
+     * The neighbor the plan continues with from the given tile together with
+     * the step leading there, None if the tile is an exit or no exit can be
+     * reached from it
+     */
+    fn get_best_step(&self, from: [u32; 2]) -> Option<([u32; 2], [i64; 2])> {
+        if self.is_exit[self.index(from)] {
+            return None;
+        }
+        self.maze
+            .get_steps()
+            .into_iter()
+            .filter_map(|step| {
+                let (to, cost) = self.get_cost(from, step)?;
+                Some((
+                    (cost + self.distances[self.index(to)]).min(UNREACHABLE),
+                    to,
+                    step,
+                ))
+            })
+            .filter(|&(distance, _, _)| distance < UNREACHABLE)
+            .min_by_key(|&(distance, [x, y], _)| (distance, y, x))
+            .map(|(_, to, step)| (to, step))
+    }
+
+    /**
+     * # Returns
+     * The neighbor of the start the plan continues with and the step leading
+     * there, None if the start is an exit or no exit can be reached
+     */
+    pub fn get_next_step(&self) -> Option<([u32; 2], [i64; 2])> {
+        self.get_best_step(self.start)
+    }
+
+    /**
+     * # Returns
+     * The tiles the robot plans to walk from the start to an exit
+     */
+    pub fn get_plan(&self) -> Vec<[u32; 2]> {
+        let mut tiles = vec![self.start];
+        while let Some((next, _)) = self.get_best_step(*tiles.last().unwrap()) {
+            if tiles.len() > self.costs.len() {
+                break;
+            }
+            tiles.push(next);
+        }
+        tiles
+    }
+
+    pub fn move_to(&mut self, tile: [u32; 2]) {
+        self.start = tile;
+    }
+
+    pub fn get_start(&self) -> [u32; 2] {
+        self.start
+    }
+}
+
+impl Maze {
+    /**
+     * Lets a robot walk from the entry to an exit while it only sees the
+     * tiles within the sight range around it. Every tile it has not seen yet
+     * is believed to be walkable, whenever it sees a tile that differs the
+     * D* Lite planner repairs the plan before the next step. Every `every`
+     * steps a frame is added to the animation if a path is given.
+     * # Returns
+     * NoExitFound if the robot finds out that no exit can be reached
+     */
+    pub fn explore_d_star_lite(
+        &self,
+        sight: u32,
+        animation: Option<(String, usize, u32)>,
+    ) -> Result<Exploration, MazeError> {
+        let mut planner = DStarLite::new(self, self.entry);
+        let mut seen = vec![false; self.maze.len()];
+        let mut exploration = Exploration {
+            path: vec![self.entry],
+            length: 0,
+            replans: 0,
+            expanded: 0,
+        };
+        let mut frames: Vec<Frame> = Vec::new();
+        planner.plan();
+        loop {
+            let changes = self.look_around(planner.get_start(), sight, &mut seen);
+            if planner.set_costs(&changes) {
+                exploration.replans += 1;
+            }
+            let distance = planner.plan();
+            if let Some((path, every, scale)) = &animation {
+                if (exploration.path.len() - 1).is_multiple_of((*every).max(1))
+                    || distance.is_none()
+                {
+                    let image = self.get_exploration_image(&seen, &exploration, &planner);
+                    frames.push(self.get_frame(&image, *scale, 100));
+                }
+                if distance.is_none() || planner.get_next_step().is_none() {
+                    let image = self.get_exploration_image(&seen, &exploration, &planner);
+                    frames.push(self.get_frame(&image, *scale, 3000));
+                    let mut encoder = GifEncoder::new(File::create(path).unwrap());
+                    encoder.set_repeat(Repeat::Infinite).unwrap();
+                    encoder.encode_frames(frames.drain(..)).unwrap();
+                    println!("Animation wurde in {} gespeichert.", path);
+                }
+            }
+            exploration.expanded = planner.expanded;
+            if distance.is_none() {
+                return Err(MazeError::NoExitFound);
+            }
+            let ([x, y], step) = match planner.get_next_step() {
+                Some(next) => next,
+                None => return Ok(exploration),
+            };
+            let from = planner.get_start();
+            exploration.length += self.get_move_cost(from[0], from[1], step);
+            exploration.path.push([x, y]);
+            planner.move_to([x, y]);
+        }
+    }
+
+    /**
+     * Marks every tile within the sight range as seen.
+     * # Returns
+     * The real cost of the tiles seen for the first time, None for walls and
+     * doors
+     */
+    pub(super) fn look_around(
+        &self,
+        [x, y]: [u32; 2],
+        sight: u32,
+        seen: &mut [bool],
+    ) -> Vec<([u32; 2], Option<isize>)> {
+        let sight = sight as i64;
+        let mut changes: Vec<([u32; 2], Option<isize>)> = Vec::new();
+        for step_y in -sight..=sight {
+            for step_x in -sight..=sight {
+                let tile = match self.offset(x, y, [step_x, step_y]) {
+                    Some(tile) => tile,
+                    None => continue,
+                };
+                let index = (tile[1] * self.size[0] + tile[0]) as usize;
+                if !seen[index] {
+                    seen[index] = true;
+                    let cost = (!self.maze[index] && !self.is_door(tile))
+                        .then(|| self.get_tile_cost(tile[0], tile[1]));
+                    changes.push((tile, cost));
+                }
+            }
+        }
+        changes
+    }
+
+    fn get_exploration_image(
+        &self,
+        seen: &[bool],
+        exploration: &Exploration,
+        planner: &DStarLite,
+    ) -> RgbImage {
+        let mut image = self.get_maze_image();
+        for (index, _) in seen.iter().enumerate().filter(|(_, &is_seen)| !is_seen) {
+            let [x, y] = [index as u32 % self.size[0], index as u32 / self.size[0]];
+            image.put_pixel(x, y, UNKNOWN_COLOR);
+        }
+        for [x, y] in planner.get_plan() {
+            image.put_pixel(x, y, PLAN_COLOR);
+        }
+        for &[x, y] in &exploration.path {
+            image.put_pixel(x, y, WALKED_COLOR);
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DStarLite;
+    use crate::maze::fixtures::{
+        get_color, get_exit_distance, get_maze, get_random_rows, CONNECTIVITIES,
+    };
+    use crate::maze::{Connectivity, Maze, MazeError, MazeOptions, TerrainCosts};
+
+    /**
+     * A random maze where every fifth walkable tile is rough terrain
+     */
+    fn get_rough_maze(seed: u64, connectivity: Connectivity) -> Maze {
+        let rows: Vec<String> = get_random_rows([18, 13], seed)
+            .into_iter()
+            .enumerate()
+            .map(|(y, row)| {
+                row.chars()
+                    .enumerate()
+                    .map(|(x, tile)| match tile {
+                        '.' if y > 1 && (x + 3 * y) % 5 == 0 => 'g',
+                        _ => tile,
+                    })
+                    .collect()
+            })
+            .collect();
+        let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+        let options = MazeOptions {
+            connectivity,
+            terrain: TerrainCosts::Palette(vec![(get_color('g'), 3)]),
+            ..MazeOptions::default()
+        };
+        get_maze(&rows, [3, 0], options)
+    }
+
+    #[test]
+    fn known_mazes_are_planned_like_dijkstra() {
+        for connectivity in CONNECTIVITIES {
+            for seed in 1..=15 {
+                let maze = get_rough_maze(seed, connectivity);
+                let mut planner = DStarLite::new(&maze, maze.get_entry());
+                let mut seen = vec![false; maze.maze.len()];
+                planner.set_costs(&maze.look_around(maze.get_entry(), 20, &mut seen));
+                let expected = get_exit_distance(&maze);
+                assert_eq!(planner.plan(), expected, "seed {}", seed);
+                if expected.is_some() {
+                    let validation = maze.validate_path(&planner.get_plan()).unwrap();
+                    assert!(validation.is_optimal());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn repaired_plans_match_a_fresh_plan() {
+        for connectivity in CONNECTIVITIES {
+            for seed in 1..=10 {
+                let maze = get_rough_maze(seed, connectivity);
+                let mut planner = DStarLite::new(&maze, maze.get_entry());
+                let mut seen = vec![false; maze.maze.len()];
+                let mut known: Vec<([u32; 2], Option<isize>)> = Vec::new();
+                planner.plan();
+                for _ in 0..30 {
+                    let changes = maze.look_around(planner.get_start(), 2, &mut seen);
+                    planner.set_costs(&changes);
+                    known.extend(changes);
+                    let distance = planner.plan();
+                    let mut fresh = DStarLite::new(&maze, maze.get_entry());
+                    fresh.move_to(planner.get_start());
+                    fresh.set_costs(&known);
+                    assert_eq!(distance, fresh.plan(), "seed {}", seed);
+                    match planner.get_next_step() {
+                        Some((next, _)) => planner.move_to(next),
+                        None => break,
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn explorers_reach_the_exit_if_there_is_one() {
+        for connectivity in CONNECTIVITIES {
+            for seed in 1..=15 {
+                let maze = get_rough_maze(seed, connectivity);
+                let expected = get_exit_distance(&maze);
+                match maze.explore_d_star_lite(1, None) {
+                    Ok(exploration) => {
+                        let validation = maze.validate_path(&exploration.path).unwrap();
+                        assert_eq!(validation.length, exploration.length);
+                        assert!(expected.is_some_and(|shortest| shortest <= exploration.length));
+                    }
+                    Err(error) => {
+                        assert!(matches!(error, MazeError::NoExitFound));
+                        assert_eq!(expected, None, "seed {}", seed);
+                    }
+                }
+                let seeing = maze.explore_d_star_lite(20, None);
+                assert_eq!(seeing.ok().map(|exploration| exploration.length), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use super::{Directions, Maze, Topology};
+use std::fmt::{Debug, Display, Formatter, Result as fmtResult};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Turn {
+    Straight,
+    HalfRight,
+    Right,
+    SharpRight,
+    Back,
+    SharpLeft,
+    Left,
+    HalfLeft,
+}
+
+impl Turn {
+    pub fn between(from: Directions, to: Directions) -> Turn {
+        match (to.get_octant() + 8 - from.get_octant()) % 8 {
+            0 => Turn::Straight,
+            1 => Turn::HalfRight,
+            2 => Turn::Right,
+            3 => Turn::SharpRight,
+            4 => Turn::Back,
+            5 => Turn::SharpLeft,
+            6 => Turn::Left,
+            _ => Turn::HalfLeft,
+        }
+    }
+}
+
+impl Display for Turn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Turn::Straight => "⬆",
+                Turn::HalfRight => "⬈",
+                Turn::Right => "↱",
+                Turn::SharpRight => "⬊",
+                Turn::Back => "↶",
+                Turn::SharpLeft => "⬋",
+                Turn::Left => "↰",
+                Turn::HalfLeft => "⬉",
+            }
+        )
+    }
+}
+
+impl Debug for Turn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Turn::Straight => "straight",
+                Turn::HalfRight => "half right",
+                Turn::Right => "right",
+                Turn::SharpRight => "sharp right",
+                Turn::Back => "back",
+                Turn::SharpLeft => "sharp left",
+                Turn::Left => "left",
+                Turn::HalfLeft => "half left",
+            }
+        )
+    }
+}
+
+impl Maze {
+    /**
+     * Compresses a path into instructions of a direction and the amount of
+     * tiles to walk in it, e.g. ➡ 12, ⬇ 4, ⬅ 7. The path may either contain
+     * every tile or only the tiles where it turns, on a torus the lines
+     * between them may wrap around the borders. Jumps through portals are
+     * left out, the instructions before and after a jump are never merged.
+     */
+    pub fn get_directions(&self, path: &[[u32; 2]]) -> Vec<(Directions, u32)> {
+        let mut instructions: Vec<(Directions, u32)> = Vec::new();
+        let mut has_jumped = false;
+        for pair in path.windows(2) {
+            if self.is_portal_jump(pair[0], pair[1]) {
+                has_jumped = true;
+                continue;
+            }
+            let (direction, steps) = match self.options.topology {
+                Topology::Bounded => match Directions::between(pair[0], pair[1]) {
+                    Some(direction) => (
+                        direction,
+                        pair[0][0]
+                            .abs_diff(pair[1][0])
+                            .max(pair[0][1].abs_diff(pair[1][1])),
+                    ),
+                    None => continue,
+                },
+                Topology::Torus => match self.get_straight_walk(pair[0], pair[1], true) {
+                    Some(walk) => (Directions::from_step(walk.step).unwrap(), walk.steps),
+                    None => continue,
+                },
+            };
+            match instructions.last_mut() {
+                Some((last_direction, last_steps))
+                    if *last_direction == direction && !has_jumped =>
+                {
+                    *last_steps += steps
+                }
+                _ => instructions.push((direction, steps)),
+            }
+            has_jumped = false;
+        }
+        instructions
+    }
+
+    /**
+     * Same as get_directions, but every direction is given relative to the
+     * previous one, the first instruction always goes straight.
+     */
+    pub fn get_relative_directions(&self, path: &[[u32; 2]]) -> Vec<(Turn, u32)> {
+        let instructions = self.get_directions(path);
+        let mut heading = match instructions.first() {
+            Some(&(direction, _)) => direction,
+            None => return Vec::new(),
+        };
+        instructions
+            .into_iter()
+            .map(|(direction, steps)| {
+                let turn = Turn::between(heading, direction);
+                heading = direction;
+                (turn, steps)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Turn;
+    use crate::maze::fixtures::get_maze;
+    use crate::maze::{Directions, MazeOptions};
+
+    const SNAKE: [&str; 5] = ["#.###", "#.###", "#...#", "###.#", "###.#"];
+
+    #[test]
+    fn straight_tiles_are_merged_into_one_instruction() {
+        let maze = get_maze(&SNAKE, [1, 0], MazeOptions::default());
+        let every_tile = [[1, 0], [1, 1], [1, 2], [2, 2], [3, 2], [3, 3], [3, 4]];
+        let expected = vec![
+            (Directions::Bottom, 2),
+            (Directions::Right, 2),
+            (Directions::Bottom, 2),
+        ];
+        assert_eq!(maze.get_directions(&every_tile), expected);
+        let turns_only = [[1, 0], [1, 2], [3, 2], [3, 4]];
+        assert_eq!(maze.get_directions(&turns_only), expected);
+        assert!(maze.get_directions(&[[1, 0]]).is_empty());
+    }
+
+    #[test]
+    fn relative_directions_follow_the_heading() {
+        let maze = get_maze(&SNAKE, [1, 0], MazeOptions::default());
+        let turns = maze.get_relative_directions(&[[1, 0], [1, 2], [3, 2], [3, 4], [3, 2]]);
+        let turns: Vec<Turn> = turns.into_iter().map(|(turn, _)| turn).collect();
+        assert_eq!(
+            turns,
+            vec![Turn::Straight, Turn::Left, Turn::Right, Turn::Back]
+        );
+        assert_eq!(
+            Turn::between(Directions::Top, Directions::TopLeft),
+            Turn::HalfLeft
+        );
+        assert_eq!(
+            Turn::between(Directions::Left, Directions::TopRight),
+            Turn::SharpRight
+        );
+    }
+
+    #[test]
+    fn portal_jumps_split_the_instructions() {
+        let options = MazeOptions {
+            portals: vec![([1, 1], [3, 3])],
+            ..MazeOptions::default()
+        };
+        let maze = get_maze(&SNAKE, [1, 0], options);
+        assert_eq!(
+            maze.get_directions(&[[1, 0], [1, 1], [3, 3], [3, 4]]),
+            vec![(Directions::Bottom, 1), (Directions::Bottom, 1)]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use super::search::{Algorithm, Solution};
+use super::{Maze, MazeError};
+
+/**
+ * The tiles a search may end at.
+ */
+#[derive(Clone)]
+pub enum Exits {
+    /**
+     * Every walkable tile on the border except for the entry
+     */
+    Openings,
+    /**
+     * The given walkable tiles, a tile inside of a straight corridor is
+     * reached by walking in from either end of the corridor
+     */
+    Tiles(Vec<[u32; 2]>),
+}
+
+/**
+ * An exit together with the graph nodes it is reached through and the cost of
+ * walking from each of them to the exit
+ */
+type ExitApproach = ([u32; 2], Vec<(usize, isize)>);
+
+pub struct ExitDistance {
+    pub exit: [u32; 2],
+    /**
+     * The length of the shortest walk from the entry, None if the exit
+     * cannot be reached
+     */
+    pub distance: Option<isize>,
+}
+
+impl Maze {
+    /**
+     * # Returns
+     * The tiles of the graph nodes the exits are reached through, a tile
+     * inside of a straight corridor is reached through both ends of the
+     * corridor. InvalidExit if one of the given tiles is a wall or out of
+     * bounds
+     */
+    pub fn get_exit_tiles(&self, exits: &Exits) -> Result<Vec<[u32; 2]>, MazeError> {
+        let mut exit_tiles: Vec<[u32; 2]> = Vec::new();
+        for (_, approaches) in self.get_exit_approaches(exits)? {
+            for (node_index, _) in approaches {
+                let tile = self.get_graph().get_node(node_index).element.0;
+                if !exit_tiles.contains(&tile) {
+                    exit_tiles.push(tile);
+                }
+            }
+        }
+        Ok(exit_tiles)
+    }
+
+    /**
+     * # Returns
+     * Every exit in the given order, InvalidExit if one of the given tiles is
+     * a wall or out of bounds
+     */
+    pub(super) fn get_exit_approaches(
+        &self,
+        exits: &Exits,
+    ) -> Result<Vec<ExitApproach>, MazeError> {
+        let tiles = match exits {
+            Exits::Openings => self.get_openings_except_entry(),
+            Exits::Tiles(tiles) => tiles.clone(),
+        };
+        tiles
+            .into_iter()
+            .map(|[x, y]| {
+                let corridor_ends = self.get_corridor_ends(x, y);
+                if corridor_ends.is_empty() {
+                    return Err(MazeError::InvalidExit);
+                }
+                let approaches = corridor_ends
+                    .into_iter()
+                    .filter_map(|(node_index, _, back_cost)| Some((node_index, back_cost?)))
+                    .collect();
+                Ok(([x, y], approaches))
+            })
+            .collect()
+    }
+
+    pub(super) fn get_openings_except_entry(&self) -> Vec<[u32; 2]> {
+        self.get_openings()
+            .into_iter()
+            .filter(|&opening| opening != self.entry)
+            .collect()
+    }
+
+    /**
+     * Runs Dijkstra from the entry and stops at the first exit it reaches.
+     */
+    pub fn find_nearest_exit(&self, exits: &Exits) -> Result<Solution, MazeError> {
+        self.solve_exits(Algorithm::Dijkstra, exits)
+    }
+
+    /**
+     * Runs Dijkstra from the entry over the whole graph.
+     * # Returns
+     * Every exit in the given order with the length of the shortest walk to it
+     */
+    pub fn get_exit_distances(&self, exits: &Exits) -> Result<Vec<ExitDistance>, MazeError> {
+        let exit_approaches = self.get_exit_approaches(exits)?;
+        let start_node_index = self
+            .get_node_index(self.entry[0], self.entry[1])
+            .ok_or(MazeError::NoExitFound)?;
+        let distances = self
+            .get_graph()
+            .get_distances(start_node_index, &|node_index| {
+                self.is_door_node(node_index)
+            });
+        Ok(exit_approaches
+            .into_iter()
+            .map(|(exit, approaches)| ExitDistance {
+                exit,
+                distance: approaches
+                    .into_iter()
+                    .filter_map(|(node_index, back_cost)| Some(distances[node_index]? + back_cost))
+                    .min(),
+            })
+            .collect())
+    }
+
+    /**
+     * # Returns
+     * The reachable exits ordered by the length of the shortest walk to them,
+     * exits with equal lengths keep the given order
+     */
+    pub fn get_exit_ranking(&self, exits: &Exits) -> Result<Vec<([u32; 2], isize)>, MazeError> {
+        let mut ranking: Vec<([u32; 2], isize)> = self
+            .get_exit_distances(exits)?
+            .into_iter()
+            .filter_map(|exit_distance| {
+                exit_distance
+                    .distance
+                    .map(|distance| (exit_distance.exit, distance))
+            })
+            .collect();
+        ranking.sort_by_key(|&(_, distance)| distance);
+        Ok(ranking)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Exits;
+    use crate::maze::fixtures::{get_maze, get_random_maze, get_tile_distances, CONNECTIVITIES};
+    use crate::maze::{MazeError, MazeOptions};
+
+    /**
+     * Three openings at the lengths 4, 5 and 6 from the entry
+     */
+    const BRANCHES: [&str; 5] = ["#.###", "#...#", "#.#..", "#.#.#", "#.#.#"];
+
+    #[test]
+    fn exits_are_ranked_by_their_distance() {
+        let maze = get_maze(&BRANCHES, [1, 0], MazeOptions::default());
+        assert_eq!(
+            maze.get_exit_ranking(&Exits::Openings).unwrap(),
+            vec![([1, 4], 4), ([4, 2], 5), ([3, 4], 6)]
+        );
+        let nearest = maze.find_nearest_exit(&Exits::Openings).unwrap();
+        assert_eq!(
+            (nearest.path, nearest.length),
+            (vec![[1, 0], [1, 1], [1, 4]], 4)
+        );
+        let inside = maze
+            .find_nearest_exit(&Exits::Tiles(vec![[3, 3], [2, 1]]))
+            .unwrap();
+        assert_eq!(
+            (inside.path, inside.length),
+            (vec![[1, 0], [1, 1], [2, 1]], 2)
+        );
+    }
+
+    #[test]
+    fn walls_and_tiles_outside_are_no_exits() {
+        let maze = get_maze(&BRANCHES, [1, 0], MazeOptions::default());
+        for tile in [[0, 0], [2, 2], [5, 1]] {
+            assert!(matches!(
+                maze.get_exit_distances(&Exits::Tiles(vec![[1, 4], tile])),
+                Err(MazeError::InvalidExit)
+            ));
+        }
+    }
+
+    #[test]
+    fn exit_distances_agree_with_the_tile_distances() {
+        for connectivity in CONNECTIVITIES {
+            for seed in 1..=20 {
+                let options = MazeOptions {
+                    connectivity,
+                    ..MazeOptions::default()
+                };
+                let maze = get_random_maze([20, 14], seed, options);
+                let distances = get_tile_distances(&maze, maze.get_entry());
+                let floor: Vec<[u32; 2]> = (0..14)
+                    .flat_map(|y| (0..20).map(move |x| [x, y]))
+                    .filter(|&[x, y]| !maze.is_wall(x, y) && [x, y] != maze.get_entry())
+                    .collect();
+                for exits in [Exits::Openings, Exits::Tiles(floor)] {
+                    let exit_distances = maze.get_exit_distances(&exits).unwrap();
+                    for exit_distance in &exit_distances {
+                        let [x, y] = exit_distance.exit;
+                        assert_eq!(exit_distance.distance, distances[(y * 20 + x) as usize]);
+                    }
+                    let nearest = exit_distances
+                        .iter()
+                        .filter_map(|exit_distance| exit_distance.distance)
+                        .min();
+                    match maze.find_nearest_exit(&exits) {
+                        Ok(solution) => {
+                            assert_eq!(Some(solution.length), nearest, "seed {}", seed);
+                            let exit = solution.path.last().unwrap();
+                            assert!(exit_distances
+                                .iter()
+                                .any(|exit_distance| exit_distance.exit == *exit));
+                            if let Exits::Openings = exits {
+                                let validation =
+                                    maze.validate_path(&maze.expand_path(&solution.path));
+                                assert_eq!(validation.unwrap().length, solution.length);
+                            }
+                        }
+                        Err(error) => {
+                            assert!(matches!(error, MazeError::NoExitFound));
+                            assert_eq!(nearest, None);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
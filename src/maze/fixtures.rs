@@ -0,0 +1,142 @@
+use super::{Connectivity, CornerCutting, Maze, MazeOptions};
+use img::{Rgb, RgbImage};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/**
+ * Every way tiles can be connected, to run a test in each of them
+ */
+pub const CONNECTIVITIES: [Connectivity; 4] = [
+    Connectivity::Four,
+    Connectivity::Eight(CornerCutting::Allow),
+    Connectivity::Eight(CornerCutting::ForbidIfEither),
+    Connectivity::Eight(CornerCutting::ForbidIfBoth),
+];
+
+/**
+ * # Returns
+ * The color a character of a fixture is drawn with, "#" is black, "." is
+ * white and every other character gets a color of its own
+ */
+pub fn get_color(tile: char) -> [u8; 3] {
+    match tile {
+        '#' => [0, 0, 0],
+        '.' => [255, 255, 255],
+        _ => [tile as u8, 255 - tile as u8, 128],
+    }
+}
+
+/**
+ * Draws one row of tiles per string, see get_color.
+ */
+pub fn get_image(rows: &[&str]) -> RgbImage {
+    let mut image = RgbImage::new(rows[0].chars().count() as u32, rows.len() as u32);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, tile) in row.chars().enumerate() {
+            image.put_pixel(x as u32, y as u32, Rgb(get_color(tile)));
+        }
+    }
+    image
+}
+
+pub fn get_maze(rows: &[&str], entry: [u32; 2], options: MazeOptions) -> Maze {
+    Maze::from_image(&get_image(rows), entry[0], entry[1], options).unwrap()
+}
+
+/**
+ * Scatters walls over a bordered maze with a xorshift generator, so that the
+ * same seed always gives the same maze. The entry is at (3,0) and up to three
+ * exits are opened in the other borders.
+ */
+pub fn get_random_rows(size: [u32; 2], seed: u64) -> Vec<String> {
+    let [width, height] = [size[0] as usize, size[1] as usize];
+    let mut random = seed.max(1);
+    let mut next = move |below: usize| {
+        random ^= random << 13;
+        random ^= random >> 7;
+        random ^= random << 17;
+        random as usize % below
+    };
+    let mut tiles: Vec<Vec<char>> = (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    let is_border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+                    if is_border || next(10) < 3 {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    tiles[0][3] = '.';
+    tiles[1][3] = '.';
+    for _ in 0..3 {
+        match next(3) {
+            0 => tiles[height - 1][1 + next(width - 2)] = '.',
+            1 => tiles[1 + next(height - 2)][width - 1] = '.',
+            _ => tiles[2 + next(height - 3)][0] = '.',
+        }
+    }
+    tiles
+        .into_iter()
+        .map(|row| row.into_iter().collect())
+        .collect()
+}
+
+pub fn get_random_maze(size: [u32; 2], seed: u64, options: MazeOptions) -> Maze {
+    let rows = get_random_rows(size, seed);
+    let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+    get_maze(&rows, [3, 0], options)
+}
+
+/**
+ * Runs Dijkstra over the single tiles instead of the graph, following every
+ * step and portal the maze allows and never entering a door.
+ * # Returns
+ * The length of the shortest walk from the tile to every other tile
+ */
+pub fn get_tile_distances(maze: &Maze, from: [u32; 2]) -> Vec<Option<isize>> {
+    let index = |[x, y]: [u32; 2]| (y * maze.size[0] + x) as usize;
+    let mut distances: Vec<Option<isize>> = vec![None; maze.maze.len()];
+    let mut heap: BinaryHeap<Reverse<(isize, [u32; 2])>> = BinaryHeap::new();
+    distances[index(from)] = Some(0);
+    heap.push(Reverse((0, from)));
+    while let Some(Reverse((distance, tile))) = heap.pop() {
+        if distances[index(tile)] != Some(distance) {
+            continue;
+        }
+        let walks = maze
+            .get_moves(tile[0], tile[1])
+            .into_iter()
+            .map(|(step, next)| (next, maze.get_move_cost(tile[0], tile[1], step)));
+        let jump = maze
+            .get_portal_partner(tile)
+            .map(|partner| (partner, maze.options.portal_cost));
+        for (next, cost) in walks.chain(jump) {
+            if maze.is_door(next) {
+                continue;
+            }
+            if distances[index(next)].is_none_or(|known| distance + cost < known) {
+                distances[index(next)] = Some(distance + cost);
+                heap.push(Reverse((distance + cost, next)));
+            }
+        }
+    }
+    distances
+}
+
+/**
+ * # Returns
+ * The length of the shortest walk from the entry to any exit, see
+ * get_tile_distances
+ */
+pub fn get_exit_distance(maze: &Maze) -> Option<isize> {
+    let distances = get_tile_distances(maze, maze.entry);
+    maze.get_grid_exits()
+        .into_iter()
+        .filter_map(|[x, y]| distances[(y * maze.size[0] + x) as usize])
+        .min()
+}
@@ -0,0 +1,305 @@
+use super::search::{Algorithm, Frontier, Solution};
+use super::{Connectivity, Maze, OCTILE_ORTHOGONAL_COST, STEPS};
+
+/**
+ * Marks the tiles at the start of a walk, they have no step leading to them
+ */
+const NO_STEP: u8 = 0xF;
+
+/**
+ * Marks the tiles that were reached by jumping through a portal, it follows
+ * the eight indices into STEPS
+ */
+const PORTAL_STEP: u8 = 8;
+
+/**
+ * One bit for every tile of the maze.
+ */
+struct TileSet {
+    bits: Vec<u64>,
+}
+
+impl TileSet {
+    fn new(tiles: usize) -> TileSet {
+        TileSet {
+            bits: vec![0; tiles.div_ceil(64)],
+        }
+    }
+
+    fn contains(&self, tile_index: usize) -> bool {
+        self.bits[tile_index / 64] & (1 << (tile_index % 64)) != 0
+    }
+
+    fn insert(&mut self, tile_index: usize) {
+        self.bits[tile_index / 64] |= 1 << (tile_index % 64);
+    }
+}
+
+/**
+ * The index into STEPS of the step that led to every tile, packed into four
+ * bits per tile.
+ */
+struct StepTable {
+    nibbles: Vec<u8>,
+}
+
+impl StepTable {
+    fn new(tiles: usize) -> StepTable {
+        StepTable {
+            nibbles: vec![0xFF; tiles.div_ceil(2)],
+        }
+    }
+
+    fn get(&self, tile_index: usize) -> u8 {
+        (self.nibbles[tile_index / 2] >> (tile_index % 2 * 4)) & 0xF
+    }
+
+    fn set(&mut self, tile_index: usize, step_index: u8) {
+        let shift = tile_index % 2 * 4;
+        let byte = &mut self.nibbles[tile_index / 2];
+        *byte = (*byte & !(0xF << shift)) | (step_index << shift);
+    }
+}
+
+impl Maze {
+    /**
+     * Searches from the entry to the nearest other opening directly on the
+     * tiles, without building the graph. Apart from the frontier it only
+     * keeps two bits and the step it was reached with for every tile.
+     * BFS finds the path with the fewest steps, Dijkstra and A* the shortest
+     * one, DFS just any path. Jumping through a portal counts as one step,
+     * doors stay locked.
+     * # Returns
+     * The tiles where the path turns, the expanded count are tiles instead of
+     * graph nodes
+     */
+    pub fn solve_on_grid(&self, algorithm: Algorithm) -> Option<Solution> {
+        let width = self.size[0];
+        let entry_index = (self.entry[1] * width + self.entry[0]) as usize;
+        if self.maze[entry_index] {
+            return None;
+        }
+        let steps: Vec<(u8, [i64; 2])> = self
+            .get_steps()
+            .into_iter()
+            .map(|step| {
+                let step_index = STEPS.iter().position(|&known| known == step).unwrap();
+                (step_index as u8, step)
+            })
+            .collect();
+        let border_estimate = |tile: [u32; 2]| self.get_border_estimate(algorithm, tile);
+        let portal_tails = self.get_portal_tails(&border_estimate);
+        let estimate = |tile: [u32; 2]| -> isize {
+            if algorithm != Algorithm::AStar {
+                return 0;
+            }
+            self.get_portal_estimate(tile, &border_estimate, &portal_tails)
+        };
+        let mut frontier: Frontier<(isize, usize, u8)> = Frontier::new(algorithm);
+        let mut discovered = TileSet::new(self.maze.len());
+        let mut closed = TileSet::new(self.maze.len());
+        let mut parent_steps = StepTable::new(self.maze.len());
+        let mut expanded = 0;
+        discovered.insert(entry_index);
+        frontier.push((0, entry_index, NO_STEP), estimate(self.entry));
+        while let Some((distance, tile_index, step_index)) = frontier.pop() {
+            if closed.contains(tile_index) {
+                continue;
+            }
+            closed.insert(tile_index);
+            parent_steps.set(tile_index, step_index);
+            expanded += 1;
+            let [x, y] = [tile_index as u32 % width, tile_index as u32 / width];
+            if self.is_exit_tile(x, y) {
+                return Some(Solution {
+                    path: self.get_grid_path(&parent_steps, [x, y]),
+                    length: distance,
+                    expanded,
+                });
+            }
+            let jump = self
+                .get_portal_partner([x, y])
+                .map(|partner| (PORTAL_STEP, partner, self.options.portal_cost));
+            let walks = steps.iter().filter_map(|&(step_index, step)| {
+                self.get_step(x, y, step)
+                    .map(|tile| (step_index, tile, self.get_move_cost(x, y, step)))
+            });
+            for (step_index, [to_x, to_y], cost) in walks.chain(jump) {
+                let to_index = (to_y * width + to_x) as usize;
+                let is_known = match algorithm {
+                    Algorithm::Bfs | Algorithm::Dfs => discovered.contains(to_index),
+                    Algorithm::Dijkstra | Algorithm::AStar => closed.contains(to_index),
+                };
+                if is_known || self.is_door([to_x, to_y]) {
+                    continue;
+                }
+                discovered.insert(to_index);
+                let to_distance = distance + cost;
+                frontier.push(
+                    (to_distance, to_index, step_index),
+                    to_distance + estimate([to_x, to_y]),
+                );
+            }
+        }
+        None
+    }
+
+    /**
+     * # Returns
+     * A lower bound for the cost of walking from the tile to the border or
+     * to the explicit exit, zero for every algorithm but A*
+     */
+    fn get_border_estimate(&self, algorithm: Algorithm, tile: [u32; 2]) -> isize {
+        if algorithm != Algorithm::AStar {
+            return 0;
+        }
+        if let Some(exit) = self.options.exit {
+            return self.get_cost_estimate(tile, exit);
+        }
+        let [x, y] = tile;
+        let steps = x.min(y).min(self.size[0] - 1 - x).min(self.size[1] - 1 - y) as isize;
+        let step_cost = match self.options.connectivity {
+            Connectivity::Four => 1,
+            Connectivity::Eight(_) => OCTILE_ORTHOGONAL_COST,
+        };
+        steps * step_cost * self.min_cost
+    }
+
+    /**
+     * Walks the stored steps back from the exit to the entry.
+     * # Returns
+     * The first and last tile of the walk, every tile where it turns and both
+     * ends of every portal it jumps through
+     */
+    fn get_grid_path(&self, parent_steps: &StepTable, exit: [u32; 2]) -> Vec<[u32; 2]> {
+        let width = self.size[0];
+        let mut path = vec![exit];
+        let [mut x, mut y] = exit;
+        let mut last_step_index = NO_STEP;
+        loop {
+            let step_index = parent_steps.get((y * width + x) as usize);
+            if step_index == NO_STEP {
+                break;
+            }
+            [x, y] = if step_index == PORTAL_STEP {
+                self.get_portal_partner([x, y]).unwrap()
+            } else {
+                let [dx, dy] = STEPS[step_index as usize];
+                self.offset(x, y, [-dx, -dy]).unwrap()
+            };
+            if step_index == last_step_index {
+                path.pop();
+            }
+            path.push([x, y]);
+            last_step_index = step_index;
+        }
+        path.reverse();
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StepTable, TileSet};
+    use crate::maze::fixtures::{
+        get_color, get_exit_distance, get_maze, get_random_maze, CONNECTIVITIES,
+    };
+    use crate::maze::search::Algorithm;
+    use crate::maze::{MazeOptions, TerrainCosts};
+
+    /**
+     * The straight way down leads over expensive terrain, the detour to the
+     * right is free of it
+     */
+    const DETOUR: [&str; 5] = ["#.###", "#...#", "#g#.#", "#...#", "#.###"];
+
+    #[test]
+    fn tile_sets_and_step_tables_keep_every_tile_apart() {
+        let mut tiles = TileSet::new(130);
+        tiles.insert(63);
+        tiles.insert(64);
+        tiles.insert(129);
+        let inserted: Vec<usize> = (0..130)
+            .filter(|&tile_index| tiles.contains(tile_index))
+            .collect();
+        assert_eq!(inserted, vec![63, 64, 129]);
+        let mut steps = StepTable::new(5);
+        steps.set(2, 7);
+        steps.set(3, 1);
+        steps.set(2, 4);
+        assert_eq!(steps.get(2), 4);
+        assert_eq!(steps.get(3), 1);
+        assert_eq!(steps.get(4), 0xF);
+    }
+
+    #[test]
+    fn grid_paths_keep_the_tiles_where_they_turn() {
+        let maze = get_maze(
+            &["#.###", "#.###", "#...#", "###.#", "###.#"],
+            [1, 0],
+            MazeOptions::default(),
+        );
+        let solution = maze.solve_on_grid(Algorithm::Bfs).unwrap();
+        assert_eq!(solution.path, vec![[1, 0], [1, 2], [3, 2], [3, 4]]);
+        assert_eq!(solution.length, 6);
+    }
+
+    #[test]
+    fn bfs_takes_the_fewest_steps_and_dijkstra_the_cheapest_ones() {
+        let options = MazeOptions {
+            terrain: TerrainCosts::Palette(vec![(get_color('g'), 9)]),
+            ..MazeOptions::default()
+        };
+        let maze = get_maze(&DETOUR, [1, 0], options);
+        let fewest = maze.solve_on_grid(Algorithm::Bfs).unwrap();
+        let cheapest = maze.solve_on_grid(Algorithm::Dijkstra).unwrap();
+        assert_eq!(fewest.path, vec![[1, 0], [1, 4]]);
+        assert_eq!(
+            cheapest.path,
+            vec![[1, 0], [1, 1], [3, 1], [3, 3], [1, 3], [1, 4]]
+        );
+        assert!(cheapest.length < fewest.length);
+        assert_eq!(Some(cheapest.length), get_exit_distance(&maze));
+    }
+
+    #[test]
+    fn lazy_graphs_are_only_built_when_needed() {
+        let lazy = MazeOptions {
+            lazy_graph: true,
+            ..MazeOptions::default()
+        };
+        let maze = get_maze(&DETOUR, [1, 0], lazy);
+        assert!(maze.graph.get().is_none());
+        assert!(maze.solve_on_grid(Algorithm::AStar).is_some());
+        assert!(maze.graph.get().is_none());
+        assert!(maze.solve(Algorithm::AStar).is_some());
+        assert!(maze.graph.get().is_some());
+        let eager = get_maze(&DETOUR, [1, 0], MazeOptions::default());
+        assert!(eager.graph.get().is_some());
+    }
+
+    #[test]
+    fn every_grid_search_walks_a_valid_path() {
+        for connectivity in CONNECTIVITIES {
+            for seed in 1..=20 {
+                let options = MazeOptions {
+                    connectivity,
+                    ..MazeOptions::default()
+                };
+                let maze = get_random_maze([20, 14], seed, options);
+                let expected = get_exit_distance(&maze);
+                for algorithm in [Algorithm::Bfs, Algorithm::Dfs] {
+                    let solution = maze.solve_on_grid(algorithm);
+                    assert_eq!(solution.is_some(), expected.is_some(), "seed {}", seed);
+                    if let Some(solution) = solution {
+                        let validation = maze
+                            .validate_path(&maze.expand_path(&solution.path))
+                            .unwrap();
+                        assert_eq!(validation.length, solution.length);
+                        assert_eq!(validation.shortest_length, expected);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,611 @@
+use super::search::{search_graph, Algorithm, Solution};
+use super::{Directions, MazeError};
+use crate::graph::Graph;
+use img::{Rgb, RgbImage};
+use std::fs;
+
+/**
+ * The six neighbors of a hexagon as steps in axial coordinates, going
+ * counterclockwise from the east
+ */
+const HEX_STEPS: [[i64; 2]; 6] = [[1, 0], [1, -1], [0, -1], [-1, 0], [-1, 1], [0, 1]];
+
+/**
+ * The direction of every step of HEX_STEPS on a pointy topped hexagon
+ */
+const HEX_DIRECTIONS: [Directions; 6] = [
+    Directions::Right,
+    Directions::TopRight,
+    Directions::TopLeft,
+    Directions::Left,
+    Directions::BottomLeft,
+    Directions::BottomRight,
+];
+
+/**
+ * A maze of pointy topped hexagons, each either a wall or walkable. The rows
+ * are stored in offset coordinates where every odd row is shifted right by
+ * half a hexagon, neighbors are found in axial coordinates. Walking from one
+ * hexagon to the next costs 1.
+ */
+pub struct HexMaze {
+    size: [u32; 2],
+    /**
+     * True for every wall, in row major order
+     */
+    cells: Vec<bool>,
+    entry: [u32; 2],
+    /**
+     * Every node holds its hexagon and whether it is an opening
+     */
+    graph: Graph<([u32; 2], bool)>,
+    /**
+     * The index of the node placed on every hexagon, in row major order
+     */
+    node_indices: Vec<Option<usize>>,
+}
+
+impl HexMaze {
+    /**
+     * Reads a hex maze from a text file, see from_text.
+     */
+    pub fn new(path: String, entry_x: u32, entry_y: u32) -> Result<HexMaze, MazeError> {
+        Self::from_text(&fs::read_to_string(&path).unwrap(), entry_x, entry_y)
+    }
+
+    /**
+     * Reads one row of hexagons per line, "#" is a wall and "." is walkable.
+     * Whitespace is ignored, so the odd rows can be indented to show their
+     * shift.
+     * # Returns
+     * InvalidHexText if there is another character or the rows differ in
+     * length
+     */
+    pub fn from_text(text: &str, entry_x: u32, entry_y: u32) -> Result<HexMaze, MazeError> {
+        let mut cells: Vec<bool> = Vec::new();
+        let mut size = [0, 0];
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let row: Vec<bool> = line
+                .chars()
+                .filter(|character| !character.is_whitespace())
+                .map(|character| match character {
+                    '#' => Ok(true),
+                    '.' => Ok(false),
+                    _ => Err(MazeError::InvalidHexText),
+                })
+                .collect::<Result<_, _>>()?;
+            if size[1] > 0 && row.len() as u32 != size[0] {
+                return Err(MazeError::InvalidHexText);
+            }
+            size = [row.len() as u32, size[1] + 1];
+            cells.extend(row);
+        }
+        if cells.is_empty() {
+            return Err(MazeError::InvalidHexText);
+        }
+        Self::from_cells(size, cells, [entry_x, entry_y])
+    }
+
+    /**
+     * Carves a perfect maze with a randomized depth first search starting at
+     * the entry. A hexagon is only carved if it touches no carved hexagon but
+     * the one it is carved from, so the walkable hexagons form a tree. The
+     * border stays closed apart from the entry and the opening farthest away
+     * from it.
+     * # Returns
+     * CornerEntry if the entry has no neighbor inside the border
+     */
+    pub fn generate(
+        width: u32,
+        height: u32,
+        entry: [u32; 2],
+        seed: u64,
+    ) -> Result<HexMaze, MazeError> {
+        if entry[0] >= width || entry[1] >= height {
+            return Err(MazeError::OutOfBounds);
+        }
+        let mut maze = HexMaze {
+            size: [width, height],
+            cells: vec![true; (width * height) as usize],
+            entry,
+            graph: Graph::new(),
+            node_indices: Vec::new(),
+        };
+        if maze
+            .get_neighbors(entry)
+            .iter()
+            .all(|&next| maze.is_border(next))
+        {
+            return Err(MazeError::CornerEntry);
+        }
+        let mut random = seed.max(1);
+        let mut distances: Vec<usize> = vec![0; maze.cells.len()];
+        let mut stack = vec![entry];
+        let entry_index = maze.index(entry);
+        maze.cells[entry_index] = false;
+        while let Some(&tile) = stack.last() {
+            let candidates: Vec<[u32; 2]> = maze
+                .get_neighbors(tile)
+                .into_iter()
+                .filter(|&next| {
+                    maze.cells[maze.index(next)]
+                        && !maze.is_border(next)
+                        && maze
+                            .get_neighbors(next)
+                            .iter()
+                            .all(|&other| other == tile || maze.cells[maze.index(other)])
+                })
+                .collect();
+            if candidates.is_empty() {
+                stack.pop();
+                continue;
+            }
+            random ^= random << 13;
+            random ^= random >> 7;
+            random ^= random << 17;
+            let next = candidates[(random % candidates.len() as u64) as usize];
+            let next_index = maze.index(next);
+            maze.cells[next_index] = false;
+            distances[next_index] = distances[maze.index(tile)] + 1;
+            stack.push(next);
+        }
+        let exit = (0..maze.cells.len() as u32)
+            .map(|index| [index % width, index / width])
+            .filter(|&tile| tile != entry && maze.is_border(tile))
+            .filter_map(|tile| {
+                let carved: Vec<[u32; 2]> = maze
+                    .get_neighbors(tile)
+                    .into_iter()
+                    .filter(|&other| !maze.cells[maze.index(other)])
+                    .collect();
+                match carved[..] {
+                    [inner] if !maze.is_border(inner) => Some((distances[maze.index(inner)], tile)),
+                    _ => None,
+                }
+            })
+            .max_by_key(|&(distance, [x, y])| (distance, y, x));
+        if let Some((_, exit)) = exit {
+            let exit_index = maze.index(exit);
+            maze.cells[exit_index] = false;
+        }
+        Self::from_cells(maze.size, maze.cells, entry)
+    }
+
+    fn from_cells(size: [u32; 2], cells: Vec<bool>, entry: [u32; 2]) -> Result<HexMaze, MazeError> {
+        if entry[0] >= size[0] || entry[1] >= size[1] {
+            return Err(MazeError::OutOfBounds);
+        }
+        let mut maze = HexMaze {
+            size,
+            cells,
+            entry,
+            graph: Graph::new(),
+            node_indices: Vec::new(),
+        };
+        if maze.cells[maze.index(entry)] {
+            return Err(MazeError::NotWalkable);
+        }
+        maze.scan();
+        Ok(maze)
+    }
+
+    /**
+     * Writes one row per line with the hexagons separated by spaces and the
+     * odd rows indented, which can be read again by from_text.
+     */
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for y in 0..self.size[1] {
+            let row: Vec<&str> = (0..self.size[0])
+                .map(|x| {
+                    if self.cells[self.index([x, y])] {
+                        "#"
+                    } else {
+                        "."
+                    }
+                })
+                .collect();
+            if y % 2 == 1 {
+                text.push(' ');
+            }
+            text.push_str(&row.join(" "));
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn get_size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    pub fn get_entry(&self) -> [u32; 2] {
+        self.entry
+    }
+
+    pub fn get_graph(&self) -> &Graph<([u32; 2], bool)> {
+        &self.graph
+    }
+
+    pub fn get_node_index(&self, x: u32, y: u32) -> Option<usize> {
+        if x < self.size[0] && y < self.size[1] {
+            self.node_indices[self.index([x, y])]
+        } else {
+            None
+        }
+    }
+
+    fn index(&self, [x, y]: [u32; 2]) -> usize {
+        (y * self.size[0] + x) as usize
+    }
+
+    /**
+     * # Returns
+     * The hexagon in axial coordinates
+     */
+    fn to_axial([x, y]: [u32; 2]) -> [i64; 2] {
+        [x as i64 - (y as i64 - (y & 1) as i64) / 2, y as i64]
+    }
+
+    /**
+     * # Returns
+     * The hexagon the step leads to, None if it lies outside of the maze
+     */
+    fn offset(&self, tile: [u32; 2], step: [i64; 2]) -> Option<[u32; 2]> {
+        let [q, r] = Self::to_axial(tile);
+        let (q, r) = (q + step[0], r + step[1]);
+        let x = q + (r - (r & 1)) / 2;
+        if x < 0 || r < 0 || x >= self.size[0] as i64 || r >= self.size[1] as i64 {
+            return None;
+        }
+        Some([x as u32, r as u32])
+    }
+
+    /**
+     * # Returns
+     * Every neighboring hexagon inside the maze, walls included
+     */
+    fn get_neighbors(&self, tile: [u32; 2]) -> Vec<[u32; 2]> {
+        HEX_STEPS
+            .iter()
+            .filter_map(|&step| self.offset(tile, step))
+            .collect()
+    }
+
+    /**
+     * # Returns
+     * The index into HEX_STEPS and the hexagon of every walkable neighbor
+     */
+    fn get_moves(&self, tile: [u32; 2]) -> Vec<(usize, [u32; 2])> {
+        (0..HEX_STEPS.len())
+            .filter_map(|direction| {
+                self.offset(tile, HEX_STEPS[direction])
+                    .filter(|&next| !self.cells[self.index(next)])
+                    .map(|next| (direction, next))
+            })
+            .collect()
+    }
+
+    /**
+     * A hexagon is on the border if one of its neighbors lies outside of the
+     * maze.
+     */
+    fn is_border(&self, tile: [u32; 2]) -> bool {
+        HEX_STEPS
+            .iter()
+            .any(|&step| self.offset(tile, step).is_none())
+    }
+
+    /**
+     * The same as Maze::scan: every walkable hexagon that is an opening on the
+     * border, the entry, a junction, a corner or a dead end becomes a node.
+     * Straight corridors are skipped and their length is stored as the weight
+     * of the edge between the nodes at both of their ends.
+     */
+    fn scan(&mut self) {
+        let mut graph: Graph<([u32; 2], bool)> = Graph::new();
+        let mut node_indices: Vec<Option<usize>> = vec![None; self.cells.len()];
+        for y in 0..self.size[1] {
+            for x in 0..self.size[0] {
+                if self.cells[self.index([x, y])] {
+                    continue;
+                }
+                let moves = self.get_moves([x, y]);
+                let is_opening = self.is_border([x, y]);
+                let is_corridor = moves.len() == 2 && (moves[0].0 + 3) % 6 == moves[1].0;
+                if is_corridor && !is_opening && [x, y] != self.entry {
+                    continue;
+                }
+                node_indices[self.index([x, y])] = Some(graph.add_node(([x, y], is_opening)));
+            }
+        }
+        for node_index in 0..graph.get_node_amount() {
+            let tile = graph.get_node(node_index).element.0;
+            for (direction, mut next) in self.get_moves(tile) {
+                let mut steps = 1;
+                while node_indices[self.index(next)].is_none() {
+                    next = self.offset(next, HEX_STEPS[direction]).unwrap();
+                    steps += 1;
+                }
+                graph.set_mono_edge(node_index, node_indices[self.index(next)].unwrap(), steps);
+            }
+        }
+        self.graph = graph;
+        self.node_indices = node_indices;
+    }
+
+    /**
+     * # Returns
+     * The amount of steps between two hexagons if there were no walls
+     */
+    pub fn get_distance(&self, from: [u32; 2], to: [u32; 2]) -> isize {
+        let ([from_q, from_r], [to_q, to_r]) = (Self::to_axial(from), Self::to_axial(to));
+        let (dq, dr) = (to_q - from_q, to_r - from_r);
+        ((dq.abs() + dr.abs() + (dq + dr).abs()) / 2) as isize
+    }
+
+    /**
+     * Searches the graph from the entry to the nearest other opening with the
+     * same search as Maze::solve, A* estimates the distance to the nearest
+     * opening.
+     */
+    pub fn solve(&self, algorithm: Algorithm) -> Option<Solution> {
+        let start_node_index = self.get_node_index(self.entry[0], self.entry[1])?;
+        let exits: Vec<[u32; 2]> = (0..self.graph.get_node_amount())
+            .map(|node_index| self.graph.get_node(node_index).element)
+            .filter(|&(tile, is_opening)| is_opening && tile != self.entry)
+            .map(|(tile, _)| tile)
+            .collect();
+        let route = search_graph(
+            &self.graph,
+            algorithm,
+            start_node_index,
+            &|node_index| {
+                let (tile, is_opening) = self.graph.get_node(node_index).element;
+                is_opening && tile != self.entry
+            },
+            &|_| false,
+            &|node_index| {
+                let tile = self.graph.get_node(node_index).element.0;
+                exits
+                    .iter()
+                    .map(|&exit| self.get_distance(tile, exit))
+                    .min()
+                    .unwrap_or(0)
+            },
+            &mut |_| {},
+        )?;
+        Some(Solution {
+            path: route
+                .nodes
+                .iter()
+                .map(|&node_index| self.graph.get_node(node_index).element.0)
+                .collect(),
+            length: route.length,
+            expanded: route.expanded,
+        })
+    }
+
+    /**
+     * # Returns
+     * The index into HEX_STEPS and the amount of steps of the straight line
+     * between two nodes of a path
+     */
+    fn get_line(&self, from: [u32; 2], to: [u32; 2]) -> (usize, u32) {
+        let ([from_q, from_r], [to_q, to_r]) = (Self::to_axial(from), Self::to_axial(to));
+        let steps = self.get_distance(from, to) as i64;
+        let step = [(to_q - from_q) / steps, (to_r - from_r) / steps];
+        let direction = HEX_STEPS.iter().position(|&other| other == step).unwrap();
+        (direction, steps as u32)
+    }
+
+    /**
+     * Turns the node hexagons of a solution into the list of every hexagon
+     * walked.
+     */
+    pub fn expand_path(&self, path: &[[u32; 2]]) -> Vec<[u32; 2]> {
+        let mut tiles: Vec<[u32; 2]> = path.iter().take(1).copied().collect();
+        for pair in path.windows(2) {
+            let (direction, steps) = self.get_line(pair[0], pair[1]);
+            for _ in 0..steps {
+                let tile = *tiles.last().unwrap();
+                tiles.push(self.offset(tile, HEX_STEPS[direction]).unwrap());
+            }
+        }
+        tiles
+    }
+
+    /**
+     * # Returns
+     * The direction and amount of steps of every straight part of the path,
+     * west and east being left and right
+     */
+    pub fn get_directions(&self, path: &[[u32; 2]]) -> Vec<(Directions, u32)> {
+        let mut directions: Vec<(Directions, u32)> = Vec::new();
+        for pair in path.windows(2) {
+            let (direction, steps) = self.get_line(pair[0], pair[1]);
+            match directions.last_mut() {
+                Some((last, last_steps)) if *last == HEX_DIRECTIONS[direction] => {
+                    *last_steps += steps
+                }
+                _ => directions.push((HEX_DIRECTIONS[direction], steps)),
+            }
+        }
+        directions
+    }
+
+    /**
+     * Draws every hexagon with the given radius in pixels, walls in black and
+     * the walked hexagons of the solution in red.
+     */
+    pub fn export_png(&self, path: String, solution: Option<&Solution>, radius: u32) {
+        let radius = radius.max(2) as f64;
+        let width = 3f64.sqrt() * radius;
+        let image_size = [
+            (width * (self.size[0] as f64 + 0.5)).ceil() as u32,
+            (radius * (1.5 * self.size[1] as f64 + 0.5)).ceil() as u32,
+        ];
+        let mut colors: Vec<Rgb<u8>> = self
+            .cells
+            .iter()
+            .map(|&is_wall| {
+                if is_wall {
+                    Rgb([0, 0, 0])
+                } else {
+                    Rgb([255, 255, 255])
+                }
+            })
+            .collect();
+        if let Some(solution) = solution {
+            for tile in self.expand_path(&solution.path) {
+                colors[self.index(tile)] = Rgb([255, 0, 0]);
+            }
+        }
+        let mut image = RgbImage::from_pixel(image_size[0], image_size[1], Rgb([128, 128, 128]));
+        for (pixel_x, pixel_y, pixel) in image.enumerate_pixels_mut() {
+            let (px, py) = (pixel_x as f64 + 0.5, pixel_y as f64 + 0.5);
+            let row = ((py - radius) / (1.5 * radius)).round() as i64;
+            for y in (row - 1).max(0)..(row + 2).min(self.size[1] as i64) {
+                let shift = if y % 2 == 1 { 0.5 } else { 0.0 };
+                let column = (px / width - 0.5 - shift).round() as i64;
+                for x in (column - 1).max(0)..(column + 2).min(self.size[0] as i64) {
+                    let dx = (px - width * (x as f64 + 0.5 + shift)).abs();
+                    let dy = (py - radius * (1.0 + 1.5 * y as f64)).abs();
+                    if dx <= width / 2.0 && dy <= radius - dx / 3f64.sqrt() {
+                        *pixel = colors[self.index([x as u32, y as u32])];
+                    }
+                }
+            }
+        }
+        image.save(&path).unwrap();
+        println!("Bild wurde in {} gespeichert.", path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HexMaze;
+    use crate::maze::search::Algorithm;
+    use crate::maze::MazeError;
+    use std::collections::VecDeque;
+
+    /**
+     * Runs a BFS over the walkable hexagons.
+     * # Returns
+     * The amount of steps from the entry to every hexagon
+     */
+    fn get_hex_distances(maze: &HexMaze) -> Vec<Option<isize>> {
+        let mut distances: Vec<Option<isize>> = vec![None; maze.cells.len()];
+        let mut queue: VecDeque<[u32; 2]> = VecDeque::new();
+        distances[maze.index(maze.entry)] = Some(0);
+        queue.push_back(maze.entry);
+        while let Some(tile) = queue.pop_front() {
+            let distance = distances[maze.index(tile)].unwrap();
+            for (_, next) in maze.get_moves(tile) {
+                if distances[maze.index(next)].is_none() {
+                    distances[maze.index(next)] = Some(distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+
+    #[test]
+    fn odd_rows_are_shifted_to_the_right() {
+        let maze = HexMaze::from_text("# . #\n . . .\n# . #\n", 1, 0).unwrap();
+        assert_eq!(
+            maze.get_neighbors([1, 1]),
+            vec![[2, 1], [2, 0], [1, 0], [0, 1], [1, 2], [2, 2]]
+        );
+        assert_eq!(
+            maze.get_neighbors([1, 0]),
+            vec![[2, 0], [0, 0], [0, 1], [1, 1]]
+        );
+        for neighbor in maze.get_neighbors([1, 1]) {
+            assert_eq!(maze.get_distance([1, 1], neighbor), 1);
+        }
+        assert_eq!(maze.get_distance([0, 0], [2, 2]), 3);
+    }
+
+    #[test]
+    fn text_is_read_back_the_way_it_was_written() {
+        let maze = HexMaze::generate(9, 7, [1, 0], 5).unwrap();
+        let text = maze.to_text();
+        let read = HexMaze::from_text(&text, 1, 0).unwrap();
+        assert_eq!(read.to_text(), text);
+        assert!(matches!(
+            HexMaze::from_text("#.x\n...\n", 1, 0),
+            Err(MazeError::InvalidHexText)
+        ));
+        assert!(matches!(
+            HexMaze::from_text("#.#\n..\n", 1, 0),
+            Err(MazeError::InvalidHexText)
+        ));
+        assert!(matches!(
+            HexMaze::from_text("\n \n", 0, 0),
+            Err(MazeError::InvalidHexText)
+        ));
+        assert!(matches!(
+            HexMaze::from_text("#.#\n...\n", 0, 0),
+            Err(MazeError::NotWalkable)
+        ));
+        assert!(matches!(
+            HexMaze::from_text("#.#\n...\n", 3, 0),
+            Err(MazeError::OutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn generated_mazes_are_trees_with_one_exit() {
+        assert!(matches!(
+            HexMaze::generate(9, 7, [0, 0], 1),
+            Err(MazeError::CornerEntry)
+        ));
+        for seed in 1..=20 {
+            let maze = HexMaze::generate(15, 11, [1, 0], seed).unwrap();
+            assert_eq!(
+                maze.to_text(),
+                HexMaze::generate(15, 11, [1, 0], seed).unwrap().to_text()
+            );
+            let walkable: Vec<[u32; 2]> = (0..11)
+                .flat_map(|y| (0..15).map(move |x| [x, y]))
+                .filter(|&tile| !maze.cells[maze.index(tile)])
+                .collect();
+            let links: usize = walkable
+                .iter()
+                .map(|&tile| maze.get_moves(tile).len())
+                .sum();
+            assert_eq!(links / 2, walkable.len() - 1);
+            let distances = get_hex_distances(&maze);
+            assert!(walkable
+                .iter()
+                .all(|&tile| distances[maze.index(tile)].is_some()));
+            let openings: Vec<[u32; 2]> = walkable
+                .iter()
+                .copied()
+                .filter(|&tile| maze.is_border(tile) && tile != maze.get_entry())
+                .collect();
+            assert_eq!(openings.len(), 1);
+            let exit_distance = distances[maze.index(openings[0])];
+            for algorithm in [
+                Algorithm::Bfs,
+                Algorithm::Dfs,
+                Algorithm::Dijkstra,
+                Algorithm::AStar,
+            ] {
+                let solution = maze.solve(algorithm).unwrap();
+                assert_eq!(Some(solution.length), exit_distance, "seed {}", seed);
+                let tiles = maze.expand_path(&solution.path);
+                assert_eq!(tiles.len() as isize, solution.length + 1);
+                assert_eq!(*tiles.last().unwrap(), openings[0]);
+                assert!(tiles.iter().all(|&tile| !maze.cells[maze.index(tile)]));
+                let steps: u32 = maze
+                    .get_directions(&solution.path)
+                    .iter()
+                    .map(|&(_, steps)| steps)
+                    .sum();
+                assert_eq!(steps as isize, solution.length);
+            }
+        }
+    }
+}
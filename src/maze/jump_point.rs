@@ -0,0 +1,363 @@
+use super::search::Solution;
+use super::{Connectivity, CornerCutting, Maze, MazeError, Topology};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+impl Maze {
+    /**
+     * Jump Point Search from the entry to the nearest other opening. It works
+     * directly on the tiles without the graph and skips over open areas by
+     * only expanding tiles where an optimal path may have to turn. Only valid
+     * if every walkable tile has the same terrain cost and there are no
+     * portals, one-way tiles or doors and the maze is no torus.
+     * # Returns
+     * The jump points along the shortest path, consecutive ones are connected
+     * by a straight or diagonal line
+     */
+    pub fn solve_jps(&self) -> Result<Solution, MazeError> {
+        if self
+            .costs
+            .iter()
+            .zip(self.maze.iter())
+            .any(|(&cost, &is_wall)| !is_wall && cost as isize != self.min_cost)
+        {
+            return Err(MazeError::NonUniformCosts);
+        }
+        if !self.portals.is_empty() {
+            return Err(MazeError::PortalsUnsupported);
+        }
+        if !self.one_ways.is_empty() {
+            return Err(MazeError::OneWayUnsupported);
+        }
+        if !self.doors.is_empty() {
+            return Err(MazeError::DoorsUnsupported);
+        }
+        if self.options.topology == Topology::Torus {
+            return Err(MazeError::TorusUnsupported);
+        }
+        let [entry_x, entry_y] = self.entry;
+        if self.get_tile(entry_x, entry_y)? {
+            return Err(MazeError::NoExitFound);
+        }
+        let exits: Vec<[u32; 2]> = self.get_grid_exits();
+        let heuristic = |tile: [u32; 2]| -> isize {
+            exits
+                .iter()
+                .map(|&exit| self.get_cost_estimate(tile, exit))
+                .min()
+                .unwrap_or(0)
+        };
+        let mut known: HashMap<[u32; 2], (isize, Option<[u32; 2]>)> = HashMap::new();
+        let mut heap: BinaryHeap<Reverse<(isize, isize, [u32; 2])>> = BinaryHeap::new();
+        let mut expanded = 0;
+        known.insert(self.entry, (0, None));
+        heap.push(Reverse((heuristic(self.entry), 0, self.entry)));
+        while let Some(Reverse((_, distance, tile))) = heap.pop() {
+            let (best, parent) = known[&tile];
+            if best != distance {
+                continue;
+            }
+            expanded += 1;
+            if self.is_exit_tile(tile[0], tile[1]) {
+                let mut path = vec![tile];
+                let mut current = parent;
+                while let Some(previous) = current {
+                    path.push(previous);
+                    current = known[&previous].1;
+                }
+                path.reverse();
+                return Ok(Solution {
+                    path,
+                    length: distance,
+                    expanded,
+                });
+            }
+            let direction = parent.map(|parent| get_direction(parent, tile));
+            for step in self.get_pruned_steps(tile[0], tile[1], direction) {
+                if let Some((jump_point, cost)) = self.jump(tile[0], tile[1], step) {
+                    let jump_distance = distance + cost;
+                    if known
+                        .get(&jump_point)
+                        .is_none_or(|&(known_distance, _)| jump_distance < known_distance)
+                    {
+                        known.insert(jump_point, (jump_distance, Some(tile)));
+                        heap.push(Reverse((
+                            jump_distance + heuristic(jump_point),
+                            jump_distance,
+                            jump_point,
+                        )));
+                    }
+                }
+            }
+        }
+        Err(MazeError::NoExitFound)
+    }
+
+    /**
+     * Moves from a tile in the direction of the step until it reaches a tile
+     * where the search has to branch.
+     * # Returns
+     * That jump point and the cost of getting there, None if the walk ends at
+     * a wall without finding one
+     */
+    fn jump(&self, x: u32, y: u32, step: [i64; 2]) -> Option<([u32; 2], isize)> {
+        let [mut x, mut y] = [x, y];
+        let mut cost = 0;
+        loop {
+            let [next_x, next_y] = self.get_step(x, y, step)?;
+            cost += self.get_move_cost(x, y, step);
+            [x, y] = [next_x, next_y];
+            if self.is_exit_tile(x, y) {
+                return Some(([x, y], cost));
+            }
+            let pruned_steps = self.get_pruned_steps(x, y, Some(step));
+            let natural_steps = self.get_natural_steps(step);
+            if pruned_steps
+                .iter()
+                .any(|pruned_step| !natural_steps.contains(pruned_step))
+            {
+                return Some(([x, y], cost));
+            }
+            for natural_step in natural_steps
+                .iter()
+                .filter(|&&natural_step| natural_step != step)
+            {
+                if self.jump(x, y, *natural_step).is_some() {
+                    return Some(([x, y], cost));
+                }
+            }
+        }
+    }
+
+    /**
+     * # Returns
+     * The steps that continue a move in the given direction without needing
+     * a reason to turn: straight on, and for diagonal moves the two straight
+     * parts of the diagonal. In 4-connected mazes vertical moves play the role
+     * of the diagonal ones and may always continue horizontally.
+     */
+    fn get_natural_steps(&self, step: [i64; 2]) -> Vec<[i64; 2]> {
+        match self.options.connectivity {
+            Connectivity::Four if step[0] == 0 => vec![step, [1, 0], [-1, 0]],
+            Connectivity::Four => vec![step],
+            Connectivity::Eight(_) if step[0] != 0 && step[1] != 0 => {
+                vec![step, [step[0], 0], [0, step[1]]]
+            }
+            Connectivity::Eight(_) => vec![step],
+        }
+    }
+
+    /**
+     * Prunes the neighbors of a tile that was reached with the given step.
+     * A neighbor is kept if every path to it from the previous tile that
+     * avoids the current tile is longer, for diagonal moves paths of equal
+     * length are kept as well. In 8-connected mazes this comes down to the
+     * fixed forced neighbor rules of the corner cutting.
+     * # Returns
+     * The steps to the neighbors that remain, every possible step if the tile
+     * is the start of the search
+     */
+    fn get_pruned_steps(&self, x: u32, y: u32, direction: Option<[i64; 2]>) -> Vec<[i64; 2]> {
+        let moves = self.get_moves(x, y);
+        let step = match direction {
+            Some(step) => step,
+            None => return moves.into_iter().map(|(step, _)| step).collect(),
+        };
+        let is_blocked = |[step_x, step_y]: [i64; 2]| {
+            self.offset(x, y, [step_x, step_y])
+                .is_none_or(|[to_x, to_y]| self.get_tile(to_x, to_y).unwrap_or(true))
+        };
+        let corner_cutting = match self.options.connectivity {
+            Connectivity::Four => {
+                let natural_steps = self.get_natural_steps(step);
+                return moves
+                    .into_iter()
+                    .map(|(next_step, _)| next_step)
+                    .filter(|next_step| {
+                        if natural_steps.contains(next_step) {
+                            return true;
+                        }
+                        if *next_step == [-step[0], -step[1]] {
+                            return false;
+                        }
+                        is_blocked([next_step[0] - step[0], next_step[1] - step[1]])
+                    })
+                    .collect();
+            }
+            Connectivity::Eight(corner_cutting) => corner_cutting,
+        };
+        let mut kept_steps = self.get_natural_steps(step);
+        let [step_x, step_y] = step;
+        if step_x != 0 && step_y != 0 {
+            if corner_cutting != CornerCutting::ForbidIfEither {
+                if is_blocked([-step_x, 0]) {
+                    kept_steps.push([-step_x, step_y]);
+                }
+                if is_blocked([0, -step_y]) {
+                    kept_steps.push([step_x, -step_y]);
+                }
+            }
+        } else {
+            for side in [[step_y, step_x], [-step_y, -step_x]] {
+                let diagonal = [step_x + side[0], step_y + side[1]];
+                if corner_cutting == CornerCutting::ForbidIfEither {
+                    if is_blocked([side[0] - step_x, side[1] - step_y]) {
+                        kept_steps.push(side);
+                        kept_steps.push(diagonal);
+                    }
+                } else if is_blocked(side) {
+                    kept_steps.push(diagonal);
+                }
+            }
+        }
+        moves
+            .into_iter()
+            .map(|(next_step, _)| next_step)
+            .filter(|next_step| kept_steps.contains(next_step))
+            .collect()
+    }
+}
+
+fn get_direction(from: [u32; 2], to: [u32; 2]) -> [i64; 2] {
+    [
+        (to[0] as i64 - from[0] as i64).signum(),
+        (to[1] as i64 - from[1] as i64).signum(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::fixtures::{
+        get_color, get_exit_distance, get_maze, get_random_maze, CONNECTIVITIES,
+    };
+    use crate::maze::search::Algorithm;
+    use crate::maze::{
+        Connectivity, CornerCutting, Maze, MazeError, MazeOptions, TerrainCosts, Topology,
+    };
+
+    fn get_pruned(
+        rows: &[&str],
+        connectivity: Connectivity,
+        tile: [u32; 2],
+        step: [i64; 2],
+    ) -> Vec<[i64; 2]> {
+        let options = MazeOptions {
+            connectivity,
+            ..MazeOptions::default()
+        };
+        let maze = get_maze(rows, [0, 0], options);
+        let mut steps = maze.get_pruned_steps(tile[0], tile[1], Some(step));
+        steps.sort_unstable();
+        steps
+    }
+
+    #[test]
+    fn walls_beside_a_straight_move_force_the_diagonal_past_them() {
+        let beside = ["..#..", ".....", "....."];
+        let behind = [".#...", ".....", "....."];
+        let allow = Connectivity::Eight(CornerCutting::Allow);
+        let either = Connectivity::Eight(CornerCutting::ForbidIfEither);
+        let both = Connectivity::Eight(CornerCutting::ForbidIfBoth);
+        assert_eq!(
+            get_pruned(&beside, allow, [2, 1], [1, 0]),
+            vec![[1, -1], [1, 0]]
+        );
+        assert_eq!(
+            get_pruned(&beside, both, [2, 1], [1, 0]),
+            vec![[1, -1], [1, 0]]
+        );
+        assert_eq!(get_pruned(&beside, either, [2, 1], [1, 0]), vec![[1, 0]]);
+        assert_eq!(
+            get_pruned(&beside, Connectivity::Four, [2, 1], [1, 0]),
+            vec![[1, 0]]
+        );
+        assert_eq!(get_pruned(&behind, allow, [2, 1], [1, 0]), vec![[1, 0]]);
+        assert_eq!(
+            get_pruned(&behind, either, [2, 1], [1, 0]),
+            vec![[0, -1], [1, -1], [1, 0]]
+        );
+        assert_eq!(
+            get_pruned(&behind, Connectivity::Four, [2, 1], [1, 0]),
+            vec![[0, -1], [1, 0]]
+        );
+    }
+
+    #[test]
+    fn walls_behind_a_diagonal_move_force_the_turn_around_them() {
+        let rows = ["...", "#..", "..."];
+        let allow = Connectivity::Eight(CornerCutting::Allow);
+        let either = Connectivity::Eight(CornerCutting::ForbidIfEither);
+        assert_eq!(
+            get_pruned(&rows, allow, [1, 1], [1, 1]),
+            vec![[-1, 1], [0, 1], [1, 0], [1, 1]]
+        );
+        assert_eq!(
+            get_pruned(&rows, either, [1, 1], [1, 1]),
+            vec![[0, 1], [1, 0], [1, 1]]
+        );
+    }
+
+    #[test]
+    fn every_solver_agrees_with_the_tile_distances() {
+        for connectivity in CONNECTIVITIES {
+            for seed in 1..=40 {
+                let options = MazeOptions {
+                    connectivity,
+                    ..MazeOptions::default()
+                };
+                let maze = get_random_maze([24, 16], seed, options);
+                let expected = get_exit_distance(&maze);
+                let length =
+                    |maze: &Maze, algorithm| maze.solve(algorithm).map(|solution| solution.length);
+                match maze.solve_jps() {
+                    Ok(solution) => assert_eq!(Some(solution.length), expected),
+                    Err(MazeError::NoExitFound) => assert_eq!(expected, None),
+                    Err(error) => panic!("{}", error),
+                }
+                for algorithm in [Algorithm::Dijkstra, Algorithm::AStar] {
+                    assert_eq!(length(&maze, algorithm), expected, "seed {}", seed);
+                    let on_grid = maze
+                        .solve_on_grid(algorithm)
+                        .map(|solution| solution.length);
+                    assert_eq!(on_grid, expected, "seed {}", seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unsupported_mazes_are_refused() {
+        let rows = ["#.#", "#g#", "#D#", "#.#", "#.#"];
+        let refuse = |options: MazeOptions| get_maze(&rows, [1, 0], options).solve_jps().err();
+        let terrain = TerrainCosts::Palette(vec![(get_color('g'), 3)]);
+        assert!(matches!(
+            refuse(MazeOptions {
+                terrain,
+                ..MazeOptions::default()
+            }),
+            Some(MazeError::NonUniformCosts)
+        ));
+        assert!(matches!(
+            refuse(MazeOptions {
+                portals: vec![([1, 1], [1, 3])],
+                ..MazeOptions::default()
+            }),
+            Some(MazeError::PortalsUnsupported)
+        ));
+        assert!(matches!(
+            refuse(MazeOptions {
+                keys: vec![(get_color('K'), get_color('D'))],
+                ..MazeOptions::default()
+            }),
+            Some(MazeError::DoorsUnsupported)
+        ));
+        assert!(matches!(
+            refuse(MazeOptions {
+                topology: Topology::Torus,
+                exit: Some([1, 4]),
+                ..MazeOptions::default()
+            }),
+            Some(MazeError::TorusUnsupported)
+        ));
+    }
+}
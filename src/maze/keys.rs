@@ -0,0 +1,391 @@
+use super::search::Solution;
+use super::{Maze, MazeError};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/**
+ * Red, green and blue keys, each opening the doors of the darker shade of
+ * its color
+ */
+pub const DEFAULT_KEY_COLORS: [([u8; 3], [u8; 3]); 3] = [
+    ([255, 0, 0], [128, 0, 0]),
+    ([0, 255, 0], [0, 128, 0]),
+    ([0, 0, 255], [0, 0, 128]),
+];
+
+/**
+ * The most key colors a maze may have, the collected keys are tracked as the
+ * bits of a u32
+ */
+pub const MAX_KEY_COLORS: usize = 32;
+
+/**
+ * The shortest known distance of every pair of a node and collected keys,
+ * together with the pair it was reached from
+ */
+type KeyStates = HashMap<(usize, u32), (isize, Option<(usize, u32)>)>;
+
+pub struct KeyRoute {
+    pub solution: Solution,
+    /**
+     * The keys in the order in which they are picked up
+     */
+    pub collected: Vec<[u32; 2]>,
+}
+
+impl Maze {
+    /**
+     * # Returns
+     * The tiles holding a key together with the index of their color pair
+     */
+    pub fn get_keys(&self) -> Vec<([u32; 2], usize)> {
+        let mut keys: Vec<([u32; 2], usize)> =
+            self.keys.iter().map(|(&tile, &key)| (tile, key)).collect();
+        keys.sort_by_key(|&([x, y], _)| (y, x));
+        keys
+    }
+
+    /**
+     * # Returns
+     * The tiles holding a door together with the index of their color pair
+     */
+    pub fn get_doors(&self) -> Vec<([u32; 2], usize)> {
+        let mut doors: Vec<([u32; 2], usize)> = self
+            .doors
+            .iter()
+            .map(|(&tile, &door)| (tile, door))
+            .collect();
+        doors.sort_by_key(|&([x, y], _)| (y, x));
+        doors
+    }
+
+    /**
+     * # Returns
+     * True if the tile holds a door. Only solve_keys and validate_path pick
+     * up keys and pass doors, every other search, distance and walker treats
+     * doors as walls. The component labeling still joins the tiles on both
+     * sides of a door.
+     */
+    pub fn is_door(&self, tile: [u32; 2]) -> bool {
+        self.doors.contains_key(&tile)
+    }
+
+    /**
+     * # Returns
+     * True if the graph node lies on a door
+     */
+    pub(super) fn is_door_node(&self, node_index: usize) -> bool {
+        self.is_door(self.get_graph().get_node(node_index).element.0)
+    }
+
+    /**
+     * Same as get_step, but a step onto a door is not possible.
+     */
+    pub(super) fn get_unlocked_step(&self, x: u32, y: u32, step: [i64; 2]) -> Option<[u32; 2]> {
+        self.get_step(x, y, step)
+            .filter(|&tile| !self.is_door(tile))
+    }
+
+    /**
+     * Same as get_moves, but leaves out the steps onto doors.
+     */
+    pub(super) fn get_unlocked_moves(&self, x: u32, y: u32) -> Vec<([i64; 2], [u32; 2])> {
+        self.get_moves(x, y)
+            .into_iter()
+            .filter(|&(_, tile)| !self.is_door(tile))
+            .collect()
+    }
+
+    /**
+     * Searches for the shortest way to an exit where doors can only be passed
+     * after a key of their color was picked up. Runs Dijkstra over pairs of a
+     * graph node and the set of keys collected on the way there, walking over
+     * a key picks it up.
+     * # Returns
+     * NoExitFound if the puzzle cannot be solved
+     */
+    pub fn solve_keys(&self) -> Result<KeyRoute, MazeError> {
+        if self.options.keys.len() > MAX_KEY_COLORS {
+            return Err(MazeError::TooManyKeys);
+        }
+        let graph = self.get_graph();
+        let start_node_index = self
+            .get_node_index(self.entry[0], self.entry[1])
+            .ok_or(MazeError::NoExitFound)?;
+        let bit = |tiles: &HashMap<[u32; 2], usize>, node_index: usize| -> u32 {
+            tiles
+                .get(&graph.get_node(node_index).element.0)
+                .map_or(0, |&index| 1 << index)
+        };
+        let node_keys: Vec<u32> = (0..graph.get_node_amount())
+            .map(|node_index| bit(&self.keys, node_index))
+            .collect();
+        let node_doors: Vec<u32> = (0..graph.get_node_amount())
+            .map(|node_index| bit(&self.doors, node_index))
+            .collect();
+        let start = (start_node_index, node_keys[start_node_index]);
+        let mut known: KeyStates = HashMap::new();
+        let mut heap: BinaryHeap<Reverse<(isize, usize, u32)>> = BinaryHeap::new();
+        let mut expanded = 0;
+        known.insert(start, (0, None));
+        heap.push(Reverse((0, start.0, start.1)));
+        while let Some(Reverse((distance, node_index, collected))) = heap.pop() {
+            let state = (node_index, collected);
+            if known[&state].0 != distance {
+                continue;
+            }
+            expanded += 1;
+            if graph.get_node(node_index).element.1 && node_index != start_node_index {
+                return Ok(self.get_key_route(&known, state, distance, expanded));
+            }
+            for &(neighbor, weight) in graph.get_weighted_neighbors(node_index) {
+                if node_doors[neighbor] & !collected != 0 {
+                    continue;
+                }
+                let next_state = (neighbor, collected | node_keys[neighbor]);
+                let next_distance = distance + weight;
+                if known
+                    .get(&next_state)
+                    .is_none_or(|&(known_distance, _)| next_distance < known_distance)
+                {
+                    known.insert(next_state, (next_distance, Some(state)));
+                    heap.push(Reverse((next_distance, next_state.0, next_state.1)));
+                }
+            }
+        }
+        Err(MazeError::NoExitFound)
+    }
+
+    fn get_key_route(
+        &self,
+        known: &KeyStates,
+        exit: (usize, u32),
+        length: isize,
+        expanded: usize,
+    ) -> KeyRoute {
+        let graph = self.get_graph();
+        let mut states = vec![exit];
+        while let Some(parent) = known[states.last().unwrap()].1 {
+            states.push(parent);
+        }
+        states.reverse();
+        let mut collected: Vec<[u32; 2]> = Vec::new();
+        if states[0].1 != 0 {
+            collected.push(self.entry);
+        }
+        for pair in states.windows(2) {
+            if pair[1].1 != pair[0].1 {
+                collected.push(graph.get_node(pair[1].0).element.0);
+            }
+        }
+        KeyRoute {
+            solution: Solution {
+                path: states
+                    .iter()
+                    .map(|&(node_index, _)| graph.get_node(node_index).element.0)
+                    .collect(),
+                length,
+                expanded,
+            },
+            collected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::agents::Strategy;
+    use crate::maze::exits::Exits;
+    use crate::maze::fixtures::{get_color, get_exit_distance, get_maze, get_random_rows};
+    use crate::maze::search::Algorithm;
+    use crate::maze::walkers::Walker;
+    use crate::maze::{Maze, MazeError, MazeOptions};
+    use std::cmp::Reverse;
+    use std::collections::{BinaryHeap, HashMap};
+
+    /**
+     * Both ways around the block are locked by a door and there is no key.
+     */
+    const LOCKED: [&str; 7] = [
+        "###.###", "#.....#", "#.###.#", "#D###D#", "#.###.#", "#.....#", "###.###",
+    ];
+
+    /**
+     * The door on the way to the exit opens with the key in the dead end on
+     * the left
+     */
+    const DETOUR: [&str; 5] = ["###.###", "#K....#", "#####D#", "#.....#", "#.#####"];
+
+    fn get_key_options() -> MazeOptions {
+        MazeOptions {
+            keys: vec![
+                (get_color('K'), get_color('D')),
+                (get_color('L'), get_color('E')),
+            ],
+            ..MazeOptions::default()
+        }
+    }
+
+    /**
+     * Runs Dijkstra over pairs of a tile and the collected keys.
+     * # Returns
+     * The length of the shortest walk from the entry to an exit
+     */
+    fn get_key_distance(maze: &Maze) -> Option<isize> {
+        let key_bit = |tile: [u32; 2]| maze.keys.get(&tile).map_or(0, |&key| 1 << key);
+        let exits = maze.get_grid_exits();
+        let start = (maze.get_entry(), key_bit(maze.get_entry()));
+        let mut distances: HashMap<([u32; 2], u32), isize> = HashMap::new();
+        let mut heap: BinaryHeap<Reverse<(isize, [u32; 2], u32)>> = BinaryHeap::new();
+        distances.insert(start, 0);
+        heap.push(Reverse((0, start.0, start.1)));
+        while let Some(Reverse((distance, [x, y], collected))) = heap.pop() {
+            if distances[&([x, y], collected)] != distance {
+                continue;
+            }
+            if [x, y] != maze.get_entry() && exits.contains(&[x, y]) {
+                return Some(distance);
+            }
+            for (step, next) in maze.get_moves(x, y) {
+                if maze
+                    .doors
+                    .get(&next)
+                    .is_some_and(|&door| collected & 1 << door == 0)
+                {
+                    continue;
+                }
+                let state = (next, collected | key_bit(next));
+                let next_distance = distance + maze.get_move_cost(x, y, step);
+                if distances
+                    .get(&state)
+                    .is_none_or(|&known| next_distance < known)
+                {
+                    distances.insert(state, next_distance);
+                    heap.push(Reverse((next_distance, state.0, state.1)));
+                }
+            }
+        }
+        None
+    }
+
+    fn get_locked_maze() -> Maze {
+        let options = MazeOptions {
+            keys: vec![(get_color('K'), get_color('D'))],
+            ..MazeOptions::default()
+        };
+        get_maze(&LOCKED, [3, 0], options)
+    }
+
+    #[test]
+    fn doors_without_keys_block_every_search() {
+        let maze = get_locked_maze();
+        assert!(maze.is_door([1, 3]) && maze.is_door([5, 3]));
+        for algorithm in [
+            Algorithm::Bfs,
+            Algorithm::Dfs,
+            Algorithm::Dijkstra,
+            Algorithm::AStar,
+        ] {
+            assert!(maze.solve(algorithm).is_none());
+            assert!(maze.solve_on_grid(algorithm).is_none());
+        }
+        assert!(maze.solve_keys().is_err());
+        assert_eq!(get_exit_distance(&maze), None);
+        assert!(maze.get_exit_ranking(&Exits::Openings).unwrap().is_empty());
+        assert!(maze.get_shortest_paths().is_none());
+        assert!(maze.solve_bidirectional([3, 6], true).is_none());
+        assert!(maze.solve_bidirectional([3, 6], false).is_none());
+        assert!(maze.solve_k_shortest([3, 6], 2).is_empty());
+        assert_eq!(maze.get_shortest_exit_distance(), None);
+        assert!(maze.solve_checkpoints(&[[1, 1]], [3, 6]).is_err());
+    }
+
+    #[test]
+    fn doors_without_keys_stop_walkers_and_agents() {
+        let maze = get_locked_maze();
+        for walker in Walker::ALL {
+            assert!(maze.walk(walker).is_err());
+        }
+        for strategy in Strategy::ALL {
+            assert!(maze.explore(strategy, 1).is_err());
+        }
+        assert!(maze.explore_d_star_lite(1, None).is_err());
+        assert!(maze.solve_wall_breaks(1, Some(0)).is_err());
+        let route = maze.solve_wall_breaks(1, None).unwrap();
+        assert!(route.path.iter().all(|&tile| !maze.is_door(tile)));
+    }
+
+    #[test]
+    fn keys_are_fetched_before_their_doors() {
+        let maze = get_maze(&DETOUR, [3, 0], get_key_options());
+        let route = maze.solve_keys().unwrap();
+        assert_eq!(route.collected, vec![[1, 1]]);
+        assert_eq!(route.solution.length, 14);
+        let validation = maze
+            .validate_path(&maze.expand_path(&route.solution.path))
+            .unwrap();
+        assert_eq!(validation.length, 14);
+        let mut locked_in = DETOUR;
+        locked_in[1] = "#L....#";
+        let maze = get_maze(&locked_in, [3, 0], get_key_options());
+        assert!(matches!(maze.solve_keys(), Err(MazeError::NoExitFound)));
+    }
+
+    #[test]
+    fn too_many_key_colors_are_refused() {
+        let options = MazeOptions {
+            keys: (0..33)
+                .map(|index| ([index, 0, 1], [index, 0, 2]))
+                .collect(),
+            ..MazeOptions::default()
+        };
+        let maze = get_maze(&DETOUR, [3, 0], options);
+        assert!(matches!(maze.solve_keys(), Err(MazeError::TooManyKeys)));
+    }
+
+    #[test]
+    fn key_routes_agree_with_searching_the_tiles() {
+        let mut solved = 0;
+        for seed in 1..=60 {
+            let mut rows: Vec<Vec<char>> = get_random_rows([16, 12], seed)
+                .into_iter()
+                .map(|row| row.chars().collect())
+                .collect();
+            let mut floor = (0..12)
+                .flat_map(|y| (0..16).map(move |x| [x, y]))
+                .filter(|&[x, y]| y > 1 && rows[y][x] == '.')
+                .step_by(5)
+                .collect::<Vec<[usize; 2]>>()
+                .into_iter();
+            for tile in ['D', 'K', 'E', 'D', 'L', 'E', 'D'] {
+                if let Some([x, y]) = floor.next() {
+                    rows[y][x] = tile;
+                }
+            }
+            let rows: Vec<String> = rows
+                .into_iter()
+                .map(|row| row.into_iter().collect())
+                .collect();
+            let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+            let maze = get_maze(&rows, [3, 0], get_key_options());
+            let expected = get_key_distance(&maze);
+            match maze.solve_keys() {
+                Ok(route) => {
+                    solved += 1;
+                    assert_eq!(Some(route.solution.length), expected, "seed {}", seed);
+                    let tiles = maze.expand_path(&route.solution.path);
+                    assert_eq!(
+                        maze.validate_path(&tiles).unwrap().length,
+                        route.solution.length
+                    );
+                    assert!(route.collected.iter().all(|key| tiles.contains(key)));
+                }
+                Err(error) => {
+                    assert!(matches!(error, MazeError::NoExitFound));
+                    assert_eq!(expected, None, "seed {}", seed);
+                }
+            }
+        }
+        assert!(solved > 10);
+    }
+}
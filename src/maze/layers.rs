@@ -0,0 +1,330 @@
+use super::search::{search_graph, Algorithm};
+use super::{Maze, MazeError, MazeOptions};
+use crate::graph::Graph;
+use img::Rgb;
+
+/**
+ * Stair tiles lead to the same tile on the layer above or below, where there
+ * has to be a stair leading back.
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub enum Stairs {
+    Up,
+    Down,
+}
+
+/**
+ * Gold stairs lead up and brown stairs lead down
+ */
+pub const DEFAULT_STAIR_COLORS: [([u8; 3], Stairs); 2] =
+    [([255, 215, 0], Stairs::Up), ([139, 69, 19], Stairs::Down)];
+
+/**
+ * The color stair tiles are drawn with
+ */
+pub const STAIR_COLOR: Rgb<u8> = Rgb([255, 215, 0]);
+
+/**
+ * Several mazes of the same size stacked on top of each other, the first one
+ * is the lowest. The graphs of all layers are joined into one graph in which
+ * matching stairs are connected by an edge with the stair cost.
+ */
+pub struct LayeredMaze {
+    layers: Vec<Maze>,
+    /**
+     * The layer and the tile of the entry
+     */
+    entry: (usize, [u32; 2]),
+    /**
+     * Every node is the layer, the tile and whether it is an opening
+     */
+    graph: Graph<(usize, [u32; 2], bool)>,
+    /**
+     * The index of the first node of every layer in the joined graph
+     */
+    offsets: Vec<usize>,
+}
+
+pub struct LayeredSolution {
+    /**
+     * The layer and tile of every node along the path, a change of the layer
+     * between two of them is a walk over stairs
+     */
+    pub path: Vec<(usize, [u32; 2])>,
+    pub length: isize,
+    pub expanded: usize,
+}
+
+impl LayeredMaze {
+    /**
+     * Loads one image per layer, every layer is read with the same options.
+     * # Returns
+     * SizeMismatch if the layers differ in size and InvalidStairs if a stair
+     * does not have a matching stair on the layer it leads to or the stair
+     * cost is negative
+     */
+    pub fn new(
+        paths: &[String],
+        entry_layer: usize,
+        entry: [u32; 2],
+        options: MazeOptions,
+    ) -> Result<LayeredMaze, MazeError> {
+        if entry_layer >= paths.len() {
+            return Err(MazeError::OutOfBounds);
+        }
+        if options.stair_cost < 0 {
+            return Err(MazeError::InvalidStairs);
+        }
+        let mut layers: Vec<Maze> = Vec::with_capacity(paths.len());
+        for path in paths {
+            let mut layer_options = options.clone();
+            layer_options.lazy_graph = false;
+            let layer = Maze::new_with_options(path.clone(), entry[0], entry[1], layer_options)?;
+            if layers
+                .first()
+                .is_some_and(|first| first.get_size() != layer.get_size())
+            {
+                return Err(MazeError::SizeMismatch);
+            }
+            layers.push(layer);
+        }
+        let mut graph: Graph<(usize, [u32; 2], bool)> = Graph::new();
+        let mut offsets: Vec<usize> = Vec::with_capacity(layers.len());
+        for (layer_index, layer) in layers.iter().enumerate() {
+            let layer_graph = layer.get_graph();
+            offsets.push(graph.get_node_amount());
+            for node_index in 0..layer_graph.get_node_amount() {
+                let (tile, is_opening) = layer_graph.get_node(node_index).element;
+                // An explicit exit makes the entry an opening on every layer
+                let is_foreign_entry =
+                    layer_index != entry_layer && options.exit.is_some() && tile == entry;
+                graph.add_node((layer_index, tile, is_opening && !is_foreign_entry));
+            }
+        }
+        for (layer_index, layer) in layers.iter().enumerate() {
+            let layer_graph = layer.get_graph();
+            for node_index in 0..layer_graph.get_node_amount() {
+                for &(neighbor, weight) in layer_graph.get_weighted_neighbors(node_index) {
+                    graph.set_mono_edge(
+                        offsets[layer_index] + node_index,
+                        offsets[layer_index] + neighbor,
+                        weight,
+                    );
+                }
+            }
+            for (&[x, y], &stairs) in &layer.stairs {
+                let (target_layer, target_stairs) = match stairs {
+                    Stairs::Up => (layer_index + 1, Stairs::Down),
+                    Stairs::Down => (layer_index.wrapping_sub(1), Stairs::Up),
+                };
+                let target = layers
+                    .get(target_layer)
+                    .filter(|target| target.stairs.get(&[x, y]) == Some(&target_stairs))
+                    .ok_or(MazeError::InvalidStairs)?;
+                graph.set_mono_edge(
+                    offsets[layer_index] + layer.get_node_index(x, y).unwrap(),
+                    offsets[target_layer] + target.get_node_index(x, y).unwrap(),
+                    options.stair_cost,
+                );
+            }
+        }
+        Ok(LayeredMaze {
+            layers,
+            entry: (entry_layer, entry),
+            graph,
+            offsets,
+        })
+    }
+
+    pub fn get_layers(&self) -> &[Maze] {
+        &self.layers
+    }
+
+    pub fn get_graph(&self) -> &Graph<(usize, [u32; 2], bool)> {
+        &self.graph
+    }
+
+    /**
+     * # Returns
+     * The index of the joined graph node on the given tile of a layer
+     */
+    pub fn get_node_index(&self, layer: usize, tile: [u32; 2]) -> Option<usize> {
+        let node_index = self.layers.get(layer)?.get_node_index(tile[0], tile[1])?;
+        Some(self.offsets[layer] + node_index)
+    }
+
+    /**
+     * Searches the joined graph from the entry to the nearest opening on any
+     * layer. BFS finds the path with the fewest nodes, Dijkstra and A* the
+     * shortest one, DFS just any path. The estimate of A* ignores the layers.
+     */
+    pub fn solve(&self, algorithm: Algorithm) -> Option<LayeredSolution> {
+        let start_node_index = self.get_node_index(self.entry.0, self.entry.1)?;
+        let exits: Vec<[u32; 2]> = (0..self.graph.get_node_amount())
+            .filter(|&node_index| node_index != start_node_index)
+            .map(|node_index| self.graph.get_node(node_index).element)
+            .filter(|&(_, _, is_opening)| is_opening)
+            .map(|(_, tile, _)| tile)
+            .collect();
+        let heuristic = |node_index: usize| -> isize {
+            let (layer, tile, _) = self.graph.get_node(node_index).element;
+            exits
+                .iter()
+                .map(|&exit| self.layers[layer].get_cost_estimate(tile, exit))
+                .min()
+                .unwrap_or(0)
+        };
+        let route = search_graph(
+            &self.graph,
+            algorithm,
+            start_node_index,
+            &|node_index| {
+                node_index != start_node_index && self.graph.get_node(node_index).element.2
+            },
+            &|node_index| {
+                let (layer, tile, _) = self.graph.get_node(node_index).element;
+                self.layers[layer].is_door(tile)
+            },
+            &heuristic,
+            &mut |_| {},
+        )?;
+        Some(LayeredSolution {
+            path: route
+                .nodes
+                .iter()
+                .map(|&node_index| {
+                    let (layer, tile, _) = self.graph.get_node(node_index).element;
+                    (layer, tile)
+                })
+                .collect(),
+            length: route.length,
+            expanded: route.expanded,
+        })
+    }
+
+    /**
+     * Splits a path into the parts walked on a single layer.
+     * # Returns
+     * The layer of every part together with its tiles, like the path of a
+     * Solution
+     */
+    pub fn get_layer_segments(&self, path: &[(usize, [u32; 2])]) -> Vec<(usize, Vec<[u32; 2]>)> {
+        let mut segments: Vec<(usize, Vec<[u32; 2]>)> = Vec::new();
+        for &(layer, tile) in path {
+            match segments.last_mut() {
+                Some((last_layer, tiles)) if *last_layer == layer => tiles.push(tile),
+                _ => segments.push((layer, vec![tile])),
+            }
+        }
+        segments
+    }
+
+    /**
+     * Draws the path on every layer into its own image, the layer index gets
+     * appended to the given path before the file extension.
+     */
+    pub fn export_solution_pngs(&self, path: String, solution: &LayeredSolution) {
+        let (stem, extension) = path.rsplit_once('.').unwrap_or((&path, "png"));
+        let mut images: Vec<_> = self
+            .layers
+            .iter()
+            .map(|layer| layer.get_maze_image())
+            .collect();
+        for (layer, tiles) in self.get_layer_segments(&solution.path) {
+            for [x, y] in self.layers[layer].expand_path(&tiles) {
+                images[layer].put_pixel(x, y, Rgb([255, 0, 0]));
+            }
+        }
+        for (layer, image) in images.iter().enumerate() {
+            let layer_path = format!("{}_{}.{}", stem, layer, extension);
+            image.save(&layer_path).unwrap();
+            println!("Bild wurde in {} gespeichert.", layer_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LayeredMaze, Stairs};
+    use crate::maze::fixtures::{get_color, get_image};
+    use crate::maze::search::Algorithm;
+    use crate::maze::{MazeError, MazeOptions};
+    use img::Rgb;
+    use std::env;
+
+    /**
+     * The stairs at the end of the corridor on the lower layer lead to the
+     * exit on the upper one
+     */
+    const LOWER: [&str; 5] = ["#.###", "#.###", "#.###", "#U###", "#####"];
+    const UPPER: [&str; 5] = ["#.###", "#####", "#####", "#D..#", "###.#"];
+
+    fn get_layered_maze(
+        name: &str,
+        layers: &[&[&str]],
+        stair_cost: isize,
+    ) -> Result<LayeredMaze, MazeError> {
+        let paths: Vec<String> = layers
+            .iter()
+            .enumerate()
+            .map(|(index, rows)| {
+                let path = env::temp_dir().join(format!("layers_test_{}_{}.png", name, index));
+                get_image(rows).save(&path).unwrap();
+                path.to_str().unwrap().to_string()
+            })
+            .collect();
+        let options = MazeOptions {
+            stair_colors: vec![(get_color('U'), Stairs::Up), (get_color('D'), Stairs::Down)],
+            stair_cost,
+            ..MazeOptions::default()
+        };
+        LayeredMaze::new(&paths, 0, [1, 0], options)
+    }
+
+    #[test]
+    fn stairs_lead_to_the_exit_on_another_layer() {
+        let maze = get_layered_maze("stairs", &[&LOWER, &UPPER], 5).unwrap();
+        for algorithm in [Algorithm::Dijkstra, Algorithm::AStar] {
+            let solution = maze.solve(algorithm).unwrap();
+            assert_eq!(solution.length, 11);
+            assert_eq!(
+                maze.get_layer_segments(&solution.path),
+                vec![(0, vec![[1, 0], [1, 3]]), (1, vec![[1, 3], [3, 3], [3, 4]])]
+            );
+        }
+        let path = env::temp_dir().join("layers_test_solution.png");
+        let solution = maze.solve(Algorithm::Dijkstra).unwrap();
+        maze.export_solution_pngs(path.to_str().unwrap().to_string(), &solution);
+        let lower = img::open(env::temp_dir().join("layers_test_solution_0.png"))
+            .unwrap()
+            .to_rgb8();
+        let upper = img::open(env::temp_dir().join("layers_test_solution_1.png"))
+            .unwrap()
+            .to_rgb8();
+        assert_eq!(*lower.get_pixel(1, 2), Rgb([255, 0, 0]));
+        assert_eq!(*upper.get_pixel(2, 3), Rgb([255, 0, 0]));
+        assert_ne!(*upper.get_pixel(1, 0), Rgb([255, 0, 0]));
+    }
+
+    #[test]
+    fn stairs_need_a_matching_stair_on_the_next_layer() {
+        let flat: [&str; 5] = ["#.###", "#####", "#####", "#...#", "###.#"];
+        assert!(matches!(
+            get_layered_maze("unmatched", &[&LOWER, &flat], 1),
+            Err(MazeError::InvalidStairs)
+        ));
+        assert!(matches!(
+            get_layered_maze("below", &[&UPPER, &LOWER], 1),
+            Err(MazeError::InvalidStairs)
+        ));
+        assert!(matches!(
+            get_layered_maze("negative", &[&LOWER, &UPPER], -1),
+            Err(MazeError::InvalidStairs)
+        ));
+        let narrow: [&str; 5] = ["#.##", "####", "####", "#D.#", "##.#"];
+        assert!(matches!(
+            get_layered_maze("narrow", &[&LOWER, &narrow], 1),
+            Err(MazeError::SizeMismatch)
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+use super::{Directions, Maze};
+use img::Rgb;
+use std::collections::VecDeque;
+
+/**
+ * Yellow, teal, purple and olive tiles can only be left upwards, to the
+ * right, downwards and to the left
+ */
+pub const DEFAULT_ONE_WAY_COLORS: [([u8; 3], Directions); 4] = [
+    ([255, 255, 0], Directions::Top),
+    ([0, 128, 128], Directions::Right),
+    ([128, 0, 128], Directions::Bottom),
+    ([128, 128, 0], Directions::Left),
+];
+
+/**
+ * The color one-way tiles are drawn with
+ */
+pub const ONE_WAY_COLOR: Rgb<u8> = Rgb([70, 130, 180]);
+
+/**
+ * Tiles that can be reached from the entry but have no way to an exit
+ */
+const TRAP_COLOR: Rgb<u8> = Rgb([255, 0, 0]);
+
+impl Maze {
+    /**
+     * # Returns
+     * The one-way tiles in row major order with the direction they can be
+     * left in
+     */
+    pub fn get_one_way_tiles(&self) -> Vec<([u32; 2], Directions)> {
+        let mut tiles: Vec<([u32; 2], Directions)> = self
+            .one_ways
+            .iter()
+            .map(|(&tile, &step)| (tile, Directions::from_step(step).unwrap()))
+            .collect();
+        tiles.sort_by_key(|&([x, y], _)| (y, x));
+        tiles
+    }
+
+    /**
+     * Runs a BFS forwards from the entry and one backwards from every exit,
+     * following one-way tiles and portals.
+     * # Returns
+     * The tiles in row major order that can be reached from the entry but
+     * from which no exit can be reached anymore
+     */
+    pub fn get_trap_tiles(&self) -> Vec<[u32; 2]> {
+        let reachable = self.flood(&[self.entry], &|[x, y]| {
+            self.get_moves(x, y)
+                .into_iter()
+                .map(|(_, tile)| tile)
+                .collect()
+        });
+        let escapes = self.flood(&self.get_grid_exits(), &|[x, y]| {
+            self.get_predecessors(x, y)
+        });
+        (0..self.maze.len())
+            .filter(|&index| reachable[index] && !escapes[index])
+            .map(|index| [index as u32 % self.size[0], index as u32 / self.size[0]])
+            .collect()
+    }
+
+    /**
+     * Visits every walkable tile that can be reached from the start tiles with
+     * the given steps or by jumping through a portal, doors are never entered.
+     * # Returns
+     * Whether every tile was visited, in row major order
+     */
+    fn flood(
+        &self,
+        starts: &[[u32; 2]],
+        next_tiles: &dyn Fn([u32; 2]) -> Vec<[u32; 2]>,
+    ) -> Vec<bool> {
+        let mut visited = vec![false; self.maze.len()];
+        let mut queue: VecDeque<[u32; 2]> = VecDeque::new();
+        for &[x, y] in starts {
+            if !self.get_tile(x, y).unwrap_or(true) && !self.is_door([x, y]) {
+                visited[(y * self.size[0] + x) as usize] = true;
+                queue.push_back([x, y]);
+            }
+        }
+        while let Some(tile) = queue.pop_front() {
+            let partner = self.get_portal_partner(tile);
+            for [x, y] in next_tiles(tile).into_iter().chain(partner) {
+                let index = (y * self.size[0] + x) as usize;
+                if !visited[index] && !self.is_door([x, y]) {
+                    visited[index] = true;
+                    queue.push_back([x, y]);
+                }
+            }
+        }
+        visited
+    }
+
+    /**
+     * Draws the tiles from which the exit can no longer be reached in red.
+     */
+    pub fn export_traps_png(&self, path: String, traps: &[[u32; 2]]) {
+        let mut image = self.get_maze_image();
+        for &[x, y] in traps {
+            image.put_pixel(x, y, TRAP_COLOR);
+        }
+        image.save(&path).unwrap();
+        println!("Bild wurde in {} gespeichert.", path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::fixtures::{
+        get_color, get_exit_distance, get_maze, get_random_rows, get_tile_distances,
+    };
+    use crate::maze::search::Algorithm;
+    use crate::maze::{Directions, Maze, MazeError, MazeOptions};
+
+    fn get_one_way_maze(rows: &[&str]) -> Maze {
+        let options = MazeOptions {
+            one_way_colors: vec![
+                (get_color('^'), Directions::Top),
+                (get_color('>'), Directions::Right),
+                (get_color('v'), Directions::Bottom),
+                (get_color('<'), Directions::Left),
+            ],
+            ..MazeOptions::default()
+        };
+        get_maze(rows, [1, 0], options)
+    }
+
+    #[test]
+    fn one_way_tiles_can_only_be_left_in_their_direction() {
+        let maze = get_one_way_maze(&["#.###", "#...#", "#^#.#", "#...#", "#.###"]);
+        assert_eq!(maze.get_one_way_tiles(), vec![([1, 2], Directions::Top)]);
+        let solution = maze.solve(Algorithm::Dijkstra).unwrap();
+        assert_eq!(solution.length, 8);
+        assert_eq!(maze.solve_on_grid(Algorithm::Bfs).unwrap().length, 8);
+        let maze = get_one_way_maze(&["#.###", "#...#", "#v#.#", "#...#", "#.###"]);
+        assert_eq!(maze.solve(Algorithm::Dijkstra).unwrap().length, 4);
+        assert!(matches!(
+            maze.solve_jps(),
+            Err(MazeError::OneWayUnsupported)
+        ));
+    }
+
+    #[test]
+    fn tiles_behind_a_one_way_tile_can_be_traps() {
+        let maze = get_one_way_maze(&["#.####", "#.>..#", "#.####", "#.####", "#.####"]);
+        assert_eq!(maze.get_trap_tiles(), vec![[2, 1], [3, 1], [4, 1]]);
+        let maze = get_one_way_maze(&["#.####", "#.<..#", "#.####", "#.####", "#.####"]);
+        assert!(maze.get_trap_tiles().is_empty());
+    }
+
+    #[test]
+    fn traps_and_distances_agree_with_searching_the_tiles() {
+        let arrows = ['^', '>', 'v', '<'];
+        for seed in 1..=30 {
+            let mut rows: Vec<Vec<char>> = get_random_rows([16, 12], seed)
+                .into_iter()
+                .map(|row| row.chars().collect())
+                .collect();
+            let floor: Vec<[usize; 2]> = (2..11)
+                .flat_map(|y| (1..15).map(move |x| [x, y]))
+                .filter(|&[x, y]| rows[y][x] == '.')
+                .collect();
+            for (index, &[x, y]) in floor.iter().step_by(4).enumerate() {
+                rows[y][x] = arrows[(index + seed as usize) % 4];
+            }
+            let rows: Vec<String> = rows
+                .into_iter()
+                .map(|row| row.into_iter().collect())
+                .collect();
+            let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+            let options = MazeOptions {
+                one_way_colors: vec![
+                    (get_color('^'), Directions::Top),
+                    (get_color('>'), Directions::Right),
+                    (get_color('v'), Directions::Bottom),
+                    (get_color('<'), Directions::Left),
+                ],
+                ..MazeOptions::default()
+            };
+            let maze = get_maze(&rows, [3, 0], options);
+            let expected = get_exit_distance(&maze);
+            for algorithm in [Algorithm::Dijkstra, Algorithm::AStar] {
+                let length = maze.solve(algorithm).map(|solution| solution.length);
+                assert_eq!(length, expected, "seed {}", seed);
+                let on_grid = maze
+                    .solve_on_grid(algorithm)
+                    .map(|solution| solution.length);
+                assert_eq!(on_grid, expected, "seed {}", seed);
+            }
+            let width = maze.get_size()[0];
+            let from_entry = get_tile_distances(&maze, maze.get_entry());
+            let exits = maze.get_grid_exits();
+            let traps: Vec<[u32; 2]> = (0..maze.get_size()[1])
+                .flat_map(|y| (0..width).map(move |x| [x, y]))
+                .filter(|&[x, y]| from_entry[(y * width + x) as usize].is_some())
+                .filter(|&tile| {
+                    let from_tile = get_tile_distances(&maze, tile);
+                    exits
+                        .iter()
+                        .all(|&[x, y]| from_tile[(y * width + x) as usize].is_none())
+                })
+                .collect();
+            assert_eq!(maze.get_trap_tiles(), traps, "seed {}", seed);
+        }
+    }
+}
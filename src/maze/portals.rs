@@ -0,0 +1,253 @@
+use super::Maze;
+use img::Rgb;
+
+/**
+ * Magenta, cyan and orange, each marking the two ends of one portal
+ */
+pub const DEFAULT_PORTAL_COLORS: [[u8; 3]; 3] = [[255, 0, 255], [0, 255, 255], [255, 128, 0]];
+
+/**
+ * The color the ends of every portal are drawn with
+ */
+pub const PORTAL_COLOR: Rgb<u8> = Rgb([148, 0, 211]);
+
+impl Maze {
+    /**
+     * # Returns
+     * Every portal once as the pair of its ends, the end that comes first in
+     * row major order is the first one
+     */
+    pub fn get_portals(&self) -> Vec<([u32; 2], [u32; 2])> {
+        let mut portals: Vec<([u32; 2], [u32; 2])> = self
+            .portals
+            .iter()
+            .filter(|(from, to)| (from[1], from[0]) < (to[1], to[0]))
+            .map(|(&from, &to)| (from, to))
+            .collect();
+        portals.sort_by_key(|&(from, _)| (from[1], from[0]));
+        portals
+    }
+
+    /**
+     * # Returns
+     * The other end of the portal on the given tile, None if there is none
+     */
+    pub fn get_portal_partner(&self, tile: [u32; 2]) -> Option<[u32; 2]> {
+        self.portals.get(&tile).copied()
+    }
+
+    /**
+     * # Returns
+     * True if a path going from one tile directly to the other jumps through
+     * a portal. Walking is preferred if the tiles lie on a straight line that
+     * is cheaper to walk than the portal.
+     */
+    pub fn is_portal_jump(&self, from: [u32; 2], to: [u32; 2]) -> bool {
+        self.get_portal_partner(from) == Some(to)
+            && self
+                .get_straight_walk(from, to, true)
+                .is_none_or(|walk| self.options.portal_cost <= walk.cost)
+    }
+
+    /**
+     * Lower bounds for the cost of reaching an exit after stepping onto the
+     * end of a portal and jumping through it. Starts with the direct estimate
+     * from the other end and lowers them as long as walking on to another
+     * portal gives a lower bound.
+     * # Returns
+     * Every portal end with its bound, empty if the maze has no portals
+     */
+    pub(super) fn get_portal_tails(
+        &self,
+        direct: &dyn Fn([u32; 2]) -> isize,
+    ) -> Vec<([u32; 2], isize)> {
+        let mut tails: Vec<([u32; 2], isize)> = self
+            .portals
+            .iter()
+            .map(|(&from, &to)| (from, self.options.portal_cost + direct(to)))
+            .collect();
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for index in 0..tails.len() {
+                let partner = self.portals[&tails[index].0];
+                let bound =
+                    self.options.portal_cost + self.get_portal_estimate(partner, direct, &tails);
+                if bound < tails[index].1 {
+                    tails[index].1 = bound;
+                    improved = true;
+                }
+            }
+        }
+        tails
+    }
+
+    /**
+     * # Returns
+     * A lower bound for the cost of reaching an exit from the tile, either
+     * directly or through the portals, see get_portal_tails
+     */
+    pub(super) fn get_portal_estimate(
+        &self,
+        tile: [u32; 2],
+        direct: &dyn Fn([u32; 2]) -> isize,
+        tails: &[([u32; 2], isize)],
+    ) -> isize {
+        tails
+            .iter()
+            .map(|&(portal, tail)| self.get_cost_estimate(tile, portal) + tail)
+            .fold(direct(tile), isize::min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::fixtures::{
+        get_color, get_exit_distance, get_image, get_maze, get_random_rows, CONNECTIVITIES,
+    };
+    use crate::maze::search::Algorithm;
+    use crate::maze::{Maze, MazeError, MazeOptions};
+
+    /**
+     * Two colored portals, "P" leads from the dead end at the top to the one
+     * at the bottom
+     */
+    const PORTALS: [&str; 5] = ["#.###", "#.#P#", "#Q#Q#", "#.#P#", "#.###"];
+
+    fn get_colored_options() -> MazeOptions {
+        MazeOptions {
+            portal_colors: vec![get_color('P'), get_color('Q')],
+            ..MazeOptions::default()
+        }
+    }
+
+    #[test]
+    fn colored_tiles_pair_up_as_portals() {
+        let maze = get_maze(&PORTALS, [1, 0], get_colored_options());
+        assert_eq!(maze.get_portals(), vec![([3, 1], [3, 3]), ([1, 2], [3, 2])]);
+        assert_eq!(maze.get_portal_partner([3, 3]), Some([3, 1]));
+        assert_eq!(maze.get_portal_partner([1, 1]), None);
+    }
+
+    #[test]
+    fn portals_need_two_walkable_ends() {
+        let build = |rows: &[&str], options: MazeOptions| {
+            Maze::from_image(&get_image(rows), 1, 0, options).err()
+        };
+        let three_ends = ["#.###", "#P#P#", "#.#.#", "#P#.#", "#.###"];
+        assert!(matches!(
+            build(&three_ends, get_colored_options()),
+            Some(MazeError::InvalidPortal)
+        ));
+        for portal in [
+            ([1, 1], [0, 0]),
+            ([1, 1], [1, 1]),
+            ([1, 1], [9, 1]),
+            ([3, 1], [3, 3]),
+        ] {
+            let options = MazeOptions {
+                portals: vec![portal],
+                ..get_colored_options()
+            };
+            assert!(matches!(
+                build(&PORTALS, options),
+                Some(MazeError::InvalidPortal)
+            ));
+        }
+        let options = MazeOptions {
+            portal_cost: -1,
+            ..MazeOptions::default()
+        };
+        assert!(matches!(
+            build(&PORTALS, options),
+            Some(MazeError::InvalidPortal)
+        ));
+    }
+
+    #[test]
+    fn short_walks_are_preferred_over_expensive_jumps() {
+        let options = MazeOptions {
+            portals: vec![([1, 1], [1, 3])],
+            portal_cost: 5,
+            ..MazeOptions::default()
+        };
+        let maze = get_maze(
+            &["#.###", "#.###", "#.###", "#.###", "#.###"],
+            [1, 0],
+            options,
+        );
+        assert!(!maze.is_portal_jump([1, 1], [1, 3]));
+        let solution = maze.solve(Algorithm::Dijkstra).unwrap();
+        assert_eq!(solution.length, 4);
+        let tiles = maze.expand_path(&solution.path);
+        assert_eq!(tiles, (0..5).map(|y| [1, y]).collect::<Vec<[u32; 2]>>());
+        let options = MazeOptions {
+            portals: vec![([1, 1], [1, 3])],
+            portal_cost: 1,
+            ..MazeOptions::default()
+        };
+        let maze = get_maze(
+            &["#.###", "#.###", "#.###", "#.###", "#.###"],
+            [1, 0],
+            options,
+        );
+        assert!(maze.is_portal_jump([1, 1], [1, 3]));
+        let solution = maze.solve(Algorithm::Dijkstra).unwrap();
+        assert_eq!(solution.length, 3);
+        assert_eq!(
+            maze.validate_path(&maze.expand_path(&solution.path))
+                .unwrap()
+                .length,
+            3
+        );
+    }
+
+    #[test]
+    fn searches_through_portals_agree_with_the_tile_distances() {
+        for connectivity in CONNECTIVITIES {
+            for portal_cost in [0, 3, 40] {
+                for seed in 1..=15 {
+                    let rows = get_random_rows([20, 14], seed);
+                    let floor: Vec<[u32; 2]> = (2..13)
+                        .flat_map(|y| (1..19).map(move |x| [x, y]))
+                        .filter(|&[x, y]| rows[y as usize].as_bytes()[x as usize] == b'.')
+                        .step_by(7)
+                        .take(6)
+                        .collect();
+                    let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+                    let options = MazeOptions {
+                        connectivity,
+                        portals: floor
+                            .chunks(2)
+                            .filter(|ends| ends.len() == 2)
+                            .map(|ends| (ends[0], ends[1]))
+                            .collect(),
+                        portal_cost,
+                        ..MazeOptions::default()
+                    };
+                    let maze = get_maze(&rows, [3, 0], options);
+                    let expected = get_exit_distance(&maze);
+                    for algorithm in [Algorithm::Dijkstra, Algorithm::AStar] {
+                        let solution = maze.solve(algorithm);
+                        assert_eq!(
+                            solution.as_ref().map(|solution| solution.length),
+                            expected,
+                            "seed {}",
+                            seed
+                        );
+                        let on_grid = maze
+                            .solve_on_grid(algorithm)
+                            .map(|solution| solution.length);
+                        assert_eq!(on_grid, expected, "seed {}", seed);
+                        if let Some(solution) = solution {
+                            let validation = maze
+                                .validate_path(&maze.expand_path(&solution.path))
+                                .unwrap();
+                            assert_eq!(validation.length, solution.length);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
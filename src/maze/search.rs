@@ -0,0 +1,404 @@
+use super::exits::Exits;
+use super::{Maze, MazeError};
+use crate::graph::{Graph, Route};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Bfs,
+    Dfs,
+    Dijkstra,
+    AStar,
+}
+
+impl Algorithm {
+    pub fn from_name(name: &str) -> Option<Algorithm> {
+        match name.to_lowercase().as_str() {
+            "bfs" => Some(Algorithm::Bfs),
+            "dfs" => Some(Algorithm::Dfs),
+            "dijkstra" => Some(Algorithm::Dijkstra),
+            "astar" | "a*" => Some(Algorithm::AStar),
+            _ => None,
+        }
+    }
+}
+
+pub struct Solution {
+    /**
+     * The tiles along the path where it may turn, two consecutive tiles are
+     * connected by a straight or diagonal line
+     */
+    pub path: Vec<[u32; 2]>,
+    /**
+     * The summed up step costs of the path, see Maze::get_step_cost
+     */
+    pub length: isize,
+    pub expanded: usize,
+}
+
+/**
+ * One step of a search: the node taken from the frontier, the node it was
+ * reached from and the nodes that were added to the frontier from it.
+ */
+pub struct Expansion {
+    pub node: usize,
+    pub parent: Option<usize>,
+    pub queued: Vec<usize>,
+}
+
+/**
+ * The entries still to be expanded, taken in the order of the algorithm.
+ * The priority is only used by Dijkstra and A*, ties are broken by comparing
+ * the entries.
+ */
+pub(super) enum Frontier<T> {
+    Queue(VecDeque<T>),
+    Stack(Vec<T>),
+    Heap(BinaryHeap<Reverse<(isize, T)>>),
+}
+
+impl<T: Ord> Frontier<T> {
+    pub(super) fn new(algorithm: Algorithm) -> Frontier<T> {
+        match algorithm {
+            Algorithm::Bfs => Frontier::Queue(VecDeque::new()),
+            Algorithm::Dfs => Frontier::Stack(Vec::new()),
+            Algorithm::Dijkstra | Algorithm::AStar => Frontier::Heap(BinaryHeap::new()),
+        }
+    }
+
+    pub(super) fn push(&mut self, entry: T, priority: isize) {
+        match self {
+            Frontier::Queue(queue) => queue.push_back(entry),
+            Frontier::Stack(stack) => stack.push(entry),
+            Frontier::Heap(heap) => heap.push(Reverse((priority, entry))),
+        }
+    }
+
+    pub(super) fn pop(&mut self) -> Option<T> {
+        match self {
+            Frontier::Queue(queue) => queue.pop_front(),
+            Frontier::Stack(stack) => stack.pop(),
+            Frontier::Heap(heap) => heap.pop().map(|Reverse((_, entry))| entry),
+        }
+    }
+}
+
+impl Maze {
+    /**
+     * Searches the graph from the entry to the nearest other opening.
+     * BFS finds the path with the fewest nodes, Dijkstra and A* the shortest
+     * one, DFS just any path. Doors stay locked, solve_keys picks up keys.
+     */
+    pub fn solve(&self, algorithm: Algorithm) -> Option<Solution> {
+        self.search(algorithm, &self.get_openings_except_entry(), &mut |_| {})
+    }
+
+    /**
+     * Same as solve, but the search ends at the nearest of the given exits.
+     * An exit inside of a straight corridor counts as reached at the first
+     * end of the corridor the search expands, the path then walks on to it.
+     * # Returns
+     * NoExitFound if none of the exits can be reached
+     */
+    pub fn solve_exits(&self, algorithm: Algorithm, exits: &Exits) -> Result<Solution, MazeError> {
+        let exit_tiles = self.get_exit_tiles(exits)?;
+        let mut solution = self
+            .search(algorithm, &exit_tiles, &mut |_| {})
+            .ok_or(MazeError::NoExitFound)?;
+        let [end_x, end_y] = *solution.path.last().unwrap();
+        let end_node_index = self.get_node_index(end_x, end_y).unwrap();
+        let (exit, back_cost) = self
+            .get_exit_approaches(exits)?
+            .into_iter()
+            .filter_map(|(exit, approaches)| {
+                approaches
+                    .into_iter()
+                    .find(|&(node_index, _)| node_index == end_node_index)
+                    .map(|(_, back_cost)| (exit, back_cost))
+            })
+            .min_by_key(|&(_, back_cost)| back_cost)
+            .unwrap();
+        if exit != [end_x, end_y] {
+            solution.path.push(exit);
+            solution.length += back_cost;
+        }
+        Ok(solution)
+    }
+
+    /**
+     * Same as solve, but also returns every expansion in the order in which
+     * it happened.
+     */
+    pub fn solve_traced(&self, algorithm: Algorithm) -> (Option<Solution>, Vec<Expansion>) {
+        let mut trace: Vec<Expansion> = Vec::new();
+        let solution = self.search(
+            algorithm,
+            &self.get_openings_except_entry(),
+            &mut |expansion| trace.push(expansion),
+        );
+        (solution, trace)
+    }
+
+    /**
+     * Searches from the entry and from the given exit at the same time until
+     * both searches meet, with a bidirectional Dijkstra if the weights of the
+     * edges should count and a bidirectional BFS otherwise.
+     * # Returns
+     * None if one of the tiles is no graph node or they are not connected
+     */
+    pub fn solve_bidirectional(&self, exit: [u32; 2], weighted: bool) -> Option<Solution> {
+        let start_node_index = self.get_node_index(self.entry[0], self.entry[1])?;
+        let exit_node_index = self.get_node_index(exit[0], exit[1])?;
+        let graph = self.get_graph();
+        let route = if weighted {
+            graph.bidirectional_dijkstra(start_node_index, exit_node_index, &|node_index| {
+                self.is_door_node(node_index)
+            })
+        } else {
+            graph.bidirectional_bfs(start_node_index, exit_node_index, &|node_index| {
+                self.is_door_node(node_index)
+            })
+        }?;
+        Some(Solution {
+            path: route
+                .nodes
+                .iter()
+                .map(|&node_index| graph.get_node(node_index).element.0)
+                .collect(),
+            length: route.length,
+            expanded: route.expanded,
+        })
+    }
+
+    /**
+     * Finds up to k paths from the entry to the given exit that do not visit
+     * a node twice, shortest first, see Graph::get_k_shortest_paths.
+     */
+    pub fn solve_k_shortest(&self, exit: [u32; 2], k: usize) -> Vec<Solution> {
+        let graph = self.get_graph();
+        let (start_node_index, exit_node_index) = match (
+            self.get_node_index(self.entry[0], self.entry[1]),
+            self.get_node_index(exit[0], exit[1]),
+        ) {
+            (Some(start_node_index), Some(exit_node_index)) => (start_node_index, exit_node_index),
+            _ => return Vec::new(),
+        };
+        graph
+            .get_k_shortest_paths(start_node_index, exit_node_index, k, &|node_index| {
+                self.is_door_node(node_index)
+            })
+            .into_iter()
+            .map(|route| Solution {
+                path: route
+                    .nodes
+                    .iter()
+                    .map(|&node_index| graph.get_node(node_index).element.0)
+                    .collect(),
+                length: route.length,
+                expanded: route.expanded,
+            })
+            .collect()
+    }
+
+    /**
+     * Searches from the entry until one of the exits is expanded, the exits
+     * have to be graph nodes.
+     */
+    fn search(
+        &self,
+        algorithm: Algorithm,
+        exits: &[[u32; 2]],
+        observer: &mut dyn FnMut(Expansion),
+    ) -> Option<Solution> {
+        let start_node_index = self.get_node_index(self.entry[0], self.entry[1])?;
+        let node_amount = self.get_graph().get_node_amount();
+        let mut is_exit = vec![false; node_amount];
+        for exit in exits {
+            if let Some(exit_node_index) = self.get_node_index(exit[0], exit[1]) {
+                is_exit[exit_node_index] = true;
+            }
+        }
+        let direct = |tile: [u32; 2]| -> isize {
+            exits
+                .iter()
+                .map(|&exit| self.get_cost_estimate(tile, exit))
+                .min()
+                .unwrap_or(0)
+        };
+        let portal_tails = self.get_portal_tails(&direct);
+        let heuristic = |node_index: usize| -> isize {
+            let tile = self.get_graph().get_node(node_index).element.0;
+            self.get_portal_estimate(tile, &direct, &portal_tails)
+        };
+        let route = search_graph(
+            self.get_graph(),
+            algorithm,
+            start_node_index,
+            &|node_index| is_exit[node_index],
+            &|node_index| self.is_door_node(node_index),
+            &heuristic,
+            observer,
+        )?;
+        Some(Solution {
+            path: route
+                .nodes
+                .iter()
+                .map(|&node_index| self.get_graph().get_node(node_index).element.0)
+                .collect(),
+            length: route.length,
+            expanded: route.expanded,
+        })
+    }
+}
+
+/**
+ * Searches any graph from the start node until a node accepted as exit is
+ * expanded, the heuristic is only asked by A*. Blocked nodes are never
+ * entered. Every expansion is handed to the observer.
+ * # Returns
+ * The nodes along the found path, None if no exit can be reached
+ */
+pub(super) fn search_graph<T>(
+    graph: &Graph<T>,
+    algorithm: Algorithm,
+    start_node_index: usize,
+    is_exit: &dyn Fn(usize) -> bool,
+    is_blocked: &dyn Fn(usize) -> bool,
+    heuristic: &dyn Fn(usize) -> isize,
+    observer: &mut dyn FnMut(Expansion),
+) -> Option<Route> {
+    let heuristic = |node_index: usize| -> isize {
+        if algorithm == Algorithm::AStar {
+            heuristic(node_index)
+        } else {
+            0
+        }
+    };
+    let node_amount = graph.get_node_amount();
+    let mut frontier: Frontier<(isize, usize)> = Frontier::new(algorithm);
+    let mut distances: Vec<Option<isize>> = vec![None; node_amount];
+    let mut parents: Vec<Option<usize>> = vec![None; node_amount];
+    let mut closed = vec![false; node_amount];
+    let mut expanded = 0;
+    distances[start_node_index] = Some(0);
+    frontier.push((0, start_node_index), heuristic(start_node_index));
+    while let Some((distance, node_index)) = frontier.pop() {
+        if closed[node_index] || distances[node_index] != Some(distance) {
+            continue;
+        }
+        closed[node_index] = true;
+        expanded += 1;
+        if is_exit(node_index) {
+            observer(Expansion {
+                node: node_index,
+                parent: parents[node_index],
+                queued: Vec::new(),
+            });
+            let mut nodes = vec![node_index];
+            while let Some(parent) = parents[*nodes.last().unwrap()] {
+                nodes.push(parent);
+            }
+            nodes.reverse();
+            return Some(Route {
+                nodes,
+                length: distance,
+                expanded,
+            });
+        }
+        let mut queued: Vec<usize> = Vec::new();
+        for &(child_index, weight) in graph.get_weighted_neighbors(node_index) {
+            if closed[child_index] || is_blocked(child_index) {
+                continue;
+            }
+            let child_distance = distance + weight;
+            let improves = match algorithm {
+                Algorithm::Bfs => distances[child_index].is_none(),
+                Algorithm::Dfs => true,
+                Algorithm::Dijkstra | Algorithm::AStar => {
+                    distances[child_index].is_none_or(|known| child_distance < known)
+                }
+            };
+            if improves {
+                distances[child_index] = Some(child_distance);
+                parents[child_index] = Some(node_index);
+                frontier.push(
+                    (child_distance, child_index),
+                    child_distance + heuristic(child_index),
+                );
+                queued.push(child_index);
+            }
+        }
+        observer(Expansion {
+            node: node_index,
+            parent: parents[node_index],
+            queued,
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Algorithm;
+    use crate::maze::components::get_component_color;
+    use crate::maze::fixtures::{get_maze, get_random_maze, CONNECTIVITIES};
+    use crate::maze::MazeOptions;
+    use std::env;
+
+    #[test]
+    fn bidirectional_searches_meet_on_a_shortest_path() {
+        for connectivity in CONNECTIVITIES {
+            for seed in 1..=20 {
+                let options = MazeOptions {
+                    connectivity,
+                    ..MazeOptions::default()
+                };
+                let maze = get_random_maze([24, 16], seed, options);
+                let solution = match maze.solve(Algorithm::Dijkstra) {
+                    Some(solution) => solution,
+                    None => continue,
+                };
+                let exit = *solution.path.last().unwrap();
+                let weighted = maze.solve_bidirectional(exit, true).unwrap();
+                assert_eq!(weighted.length, solution.length, "seed {}", seed);
+                let unweighted = maze.solve_bidirectional(exit, false).unwrap();
+                assert!(unweighted.length >= solution.length);
+                for route in [&weighted, &unweighted] {
+                    let path = &route.path;
+                    assert_eq!((path[0], *path.last().unwrap()), (maze.get_entry(), exit));
+                    let validation = maze.validate_path(&maze.expand_path(path)).unwrap();
+                    assert_eq!(validation.length, route.length);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn alternatives_are_listed_shortest_first_and_drawn_below_it() {
+        let maze = get_maze(
+            &["#.###", "#...#", "#.#.#", "#...#", "#.###"],
+            [1, 0],
+            MazeOptions::default(),
+        );
+        let solutions = maze.solve_k_shortest([1, 4], 3);
+        let found: Vec<(Vec<[u32; 2]>, isize)> = solutions
+            .iter()
+            .map(|solution| (solution.path.clone(), solution.length))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (vec![[1, 0], [1, 1], [1, 3], [1, 4]], 4),
+                (vec![[1, 0], [1, 1], [3, 1], [3, 3], [1, 3], [1, 4]], 8)
+            ]
+        );
+        assert!(maze.solve_k_shortest([2, 2], 3).is_empty());
+        let path = env::temp_dir().join("alternative_paths_test.png");
+        maze.export_solutions_png(path.to_str().unwrap().to_string(), &solutions);
+        let image = img::open(&path).unwrap().to_rgb8();
+        assert_eq!(*image.get_pixel(1, 1), get_component_color(0));
+        assert_eq!(*image.get_pixel(1, 2), get_component_color(0));
+        assert_eq!(*image.get_pixel(3, 2), get_component_color(1));
+    }
+}
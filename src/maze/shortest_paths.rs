@@ -0,0 +1,475 @@
+use super::Maze;
+use img::Rgb;
+use std::collections::HashMap;
+
+/**
+ * Tiles that every shortest path walks over
+ */
+const SHARED_COLOR: Rgb<u8> = Rgb([255, 0, 0]);
+/**
+ * Tiles that only some of the shortest paths walk over
+ */
+const ALTERNATIVE_COLOR: Rgb<u8> = Rgb([255, 165, 0]);
+
+/**
+ * Every shortest path from the entry to the closest exits, kept as the runs of
+ * nodes joined by edges without cost that lie on at least one of them.
+ */
+pub struct ShortestPaths {
+    pub length: isize,
+    /**
+     * The exits that can be reached with the shortest length
+     */
+    pub exits: Vec<[u32; 2]>,
+    /**
+     * The amount of distinct shortest paths, saturates at u128::MAX
+     */
+    pub count: u128,
+    /**
+     * Runs of nodes that do not visit a node twice and are joined by edges
+     * without cost, every shortest path is a chain of them that is joined by
+     * edges with cost
+     */
+    segments: Vec<Vec<usize>>,
+    /**
+     * The segments the shortest paths continue with after every segment
+     */
+    successors: Vec<Vec<usize>>,
+    /**
+     * The segments the shortest paths begin with
+     */
+    starts: Vec<usize>,
+    /**
+     * The amount of shortest paths from the entry to the end of every
+     * segment
+     */
+    forward_counts: Vec<u128>,
+    /**
+     * The amount of shortest paths from the beginning of every segment to
+     * one of the exits
+     */
+    backward_counts: Vec<u128>,
+}
+
+impl ShortestPaths {
+    pub fn is_unique(&self) -> bool {
+        self.count == 1
+    }
+}
+
+impl Maze {
+    /**
+     * Runs Dijkstra from the entry over the whole graph and counts the
+     * shortest paths to the closest exits without enumerating them. Edges
+     * without cost, like zero cost portals, can form loops between nodes at
+     * the same distance, so the paths are split into runs of such edges that
+     * do not visit a node twice. Runs are joined by edges with cost, which
+     * always lead further away from the entry, and every path is counted
+     * once. The runs are listed one by one and are meant to stay short.
+     * # Returns
+     * None if no exit can be reached
+     */
+    pub fn get_shortest_paths(&self) -> Option<ShortestPaths> {
+        let graph = self.get_graph();
+        let start_node_index = self.get_node_index(self.entry[0], self.entry[1])?;
+        let distances = graph.get_distances(start_node_index, &|node_index| {
+            self.is_door_node(node_index)
+        });
+        let is_exit = |node_index: usize| {
+            node_index != start_node_index && graph.get_node(node_index).element.1
+        };
+        let length = (0..graph.get_node_amount())
+            .filter(|&node_index| is_exit(node_index))
+            .filter_map(|node_index| distances[node_index])
+            .min()?;
+        let is_end =
+            |node_index: usize| is_exit(node_index) && distances[node_index] == Some(length);
+        let is_tight = |node_index: usize, neighbor: usize, weight: isize| {
+            distances[node_index].is_some_and(|distance| {
+                distance + weight <= length && distances[neighbor] == Some(distance + weight)
+            })
+        };
+        let mut node_indices: Vec<usize> = (0..graph.get_node_amount())
+            .filter(|&node_index| distances[node_index].is_some_and(|distance| distance <= length))
+            .collect();
+        node_indices.sort_by_key(|&node_index| distances[node_index]);
+        // Segments are listed by the distance of their nodes, so every
+        // segment comes after the segments leading to it
+        let mut segments: Vec<Vec<usize>> = Vec::new();
+        let mut segments_from: Vec<Vec<usize>> = vec![Vec::new(); graph.get_node_amount()];
+        let mut is_on_route = vec![false; graph.get_node_amount()];
+        for &node_index in &node_indices {
+            let mut route: Vec<usize> = vec![node_index];
+            let mut stack: Vec<usize> = vec![0];
+            is_on_route[node_index] = true;
+            segments_from[node_index].push(segments.len());
+            segments.push(route.clone());
+            while let Some(edge) = stack.last_mut() {
+                let last = *route.last().unwrap();
+                let edges = graph.get_weighted_neighbors(last);
+                if *edge == edges.len() || is_end(last) {
+                    stack.pop();
+                    is_on_route[last] = false;
+                    route.pop();
+                    continue;
+                }
+                let (neighbor, weight) = edges[*edge];
+                *edge += 1;
+                if weight == 0 && is_tight(last, neighbor, weight) && !is_on_route[neighbor] {
+                    is_on_route[neighbor] = true;
+                    route.push(neighbor);
+                    stack.push(0);
+                    segments_from[node_index].push(segments.len());
+                    segments.push(route.clone());
+                }
+            }
+        }
+        let successors: Vec<Vec<usize>> = segments
+            .iter()
+            .map(|segment| {
+                let last = *segment.last().unwrap();
+                if is_end(last) {
+                    return Vec::new();
+                }
+                graph
+                    .get_weighted_neighbors(last)
+                    .iter()
+                    .filter(|&&(neighbor, weight)| weight > 0 && is_tight(last, neighbor, weight))
+                    .flat_map(|&(neighbor, _)| segments_from[neighbor].iter().copied())
+                    .collect()
+            })
+            .collect();
+        let starts = segments_from[start_node_index].clone();
+        let mut forward_counts: Vec<u128> = vec![0; segments.len()];
+        for &segment in &starts {
+            forward_counts[segment] = 1;
+        }
+        for segment in 0..segments.len() {
+            for &next in &successors[segment] {
+                forward_counts[next] = forward_counts[next].saturating_add(forward_counts[segment]);
+            }
+        }
+        let mut backward_counts: Vec<u128> = vec![0; segments.len()];
+        for segment in (0..segments.len()).rev() {
+            backward_counts[segment] = if is_end(*segments[segment].last().unwrap()) {
+                1
+            } else {
+                successors[segment].iter().fold(0, |count: u128, &next| {
+                    count.saturating_add(backward_counts[next])
+                })
+            };
+        }
+        let count = starts.iter().fold(0, |count: u128, &segment| {
+            count.saturating_add(backward_counts[segment])
+        });
+        let mut exits: Vec<usize> = (0..segments.len())
+            .filter(|&segment| forward_counts[segment] > 0 && backward_counts[segment] > 0)
+            .map(|segment| *segments[segment].last().unwrap())
+            .filter(|&node_index| is_end(node_index))
+            .collect();
+        exits.sort_unstable();
+        exits.dedup();
+        Some(ShortestPaths {
+            length,
+            exits: exits
+                .into_iter()
+                .map(|node_index| graph.get_node(node_index).element.0)
+                .collect(),
+            count,
+            segments,
+            successors,
+            starts,
+            forward_counts,
+            backward_counts,
+        })
+    }
+
+    /**
+     * Lists the shortest paths one after another until the limit is reached.
+     * # Returns
+     * The node tiles of every path, like the path of a Solution
+     */
+    pub fn enumerate_shortest_paths(
+        &self,
+        shortest_paths: &ShortestPaths,
+        limit: usize,
+    ) -> Vec<Vec<[u32; 2]>> {
+        let graph = self.get_graph();
+        let mut paths: Vec<Vec<[u32; 2]>> = Vec::new();
+        let mut route: Vec<usize> = Vec::new();
+        let mut taken: Vec<usize> = Vec::new();
+        let mut stack: Vec<Vec<usize>> = vec![get_shortest_successors(
+            shortest_paths,
+            &shortest_paths.starts,
+        )];
+        while let Some(successors) = stack.last_mut() {
+            if paths.len() >= limit {
+                break;
+            }
+            match successors.pop() {
+                Some(segment) if shortest_paths.successors[segment].is_empty() => {
+                    paths.push(
+                        route
+                            .iter()
+                            .chain(&shortest_paths.segments[segment])
+                            .map(|&node_index| graph.get_node(node_index).element.0)
+                            .collect(),
+                    );
+                }
+                Some(segment) => {
+                    route.extend(&shortest_paths.segments[segment]);
+                    taken.push(segment);
+                    stack.push(get_shortest_successors(
+                        shortest_paths,
+                        &shortest_paths.successors[segment],
+                    ));
+                }
+                None => {
+                    stack.pop();
+                    if let Some(segment) = taken.pop() {
+                        route.truncate(route.len() - shortest_paths.segments[segment].len());
+                    }
+                }
+            }
+        }
+        paths
+    }
+    /**
+     * Draws every shortest path on top of each other. Tiles that all of them
+     * share are red, tiles only some of them walk over are orange.
+     */
+    pub fn export_shortest_paths_png(&self, path: String, shortest_paths: &ShortestPaths) {
+        let graph = self.get_graph();
+        let mut image = self.get_maze_image();
+        let get_color = |paths: u128| {
+            if paths == shortest_paths.count {
+                SHARED_COLOR
+            } else {
+                ALTERNATIVE_COLOR
+            }
+        };
+        let mut node_paths: Vec<u128> = vec![0; graph.get_node_amount()];
+        let mut edge_paths: HashMap<(usize, usize), u128> = HashMap::new();
+        for (segment, nodes) in shortest_paths.segments.iter().enumerate() {
+            let forward_count = shortest_paths.forward_counts[segment];
+            let paths = forward_count.saturating_mul(shortest_paths.backward_counts[segment]);
+            if paths == 0 {
+                continue;
+            }
+            for &node_index in nodes {
+                node_paths[node_index] = node_paths[node_index].saturating_add(paths);
+            }
+            for pair in nodes.windows(2) {
+                let edge = edge_paths.entry((pair[0], pair[1])).or_insert(0);
+                *edge = edge.saturating_add(paths);
+            }
+            let last = *nodes.last().unwrap();
+            for &next in &shortest_paths.successors[segment] {
+                let edge = edge_paths
+                    .entry((last, shortest_paths.segments[next][0]))
+                    .or_insert(0);
+                *edge = edge.saturating_add(
+                    forward_count.saturating_mul(shortest_paths.backward_counts[next]),
+                );
+            }
+        }
+        let mut edge_paths: Vec<((usize, usize), u128)> = edge_paths.into_iter().collect();
+        edge_paths.sort_unstable();
+        for ((from, to), paths) in edge_paths {
+            if paths == 0 {
+                continue;
+            }
+            let [from, to] = [graph.get_node(from).element.0, graph.get_node(to).element.0];
+            for [x, y] in self.get_walked_tiles(from, to) {
+                image.put_pixel(x, y, get_color(paths));
+            }
+        }
+        for (node_index, &paths) in node_paths.iter().enumerate() {
+            if paths > 0 {
+                let [x, y] = graph.get_node(node_index).element.0;
+                image.put_pixel(x, y, get_color(paths));
+            }
+        }
+        image.save(&path).unwrap();
+        println!("Bild wurde in {} gespeichert.", path);
+    }
+}
+
+/**
+ * # Returns
+ * The segments the shortest paths continue with that lead to an exit, in
+ * reverse order so that they can be popped in order
+ */
+fn get_shortest_successors(shortest_paths: &ShortestPaths, segments: &[usize]) -> Vec<usize> {
+    segments
+        .iter()
+        .rev()
+        .filter(|&&segment| shortest_paths.backward_counts[segment] > 0)
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ALTERNATIVE_COLOR, SHARED_COLOR};
+    use crate::maze::fixtures::{
+        get_color, get_image, get_maze, get_random_maze, get_tile_distances,
+    };
+    use crate::maze::{Maze, MazeOptions};
+    use img::Rgb;
+    use std::env;
+
+    const RING: [&str; 7] = [
+        "###.###", "#.....#", "#.###.#", "#.###.#", "#.###.#", "#.....#", "###.###",
+    ];
+
+    /**
+     * Counts the shortest walks from the entry to the closest exits tile by
+     * tile, a walk reaches every tile from a neighbor one step closer to the
+     * entry
+     */
+    fn count_tile_paths(maze: &Maze) -> Option<u128> {
+        let distances = get_tile_distances(maze, maze.get_entry());
+        let index = |[x, y]: [u32; 2]| (y * maze.get_size()[0] + x) as usize;
+        let mut tiles: Vec<[u32; 2]> = (0..maze.get_size()[1])
+            .flat_map(|y| (0..maze.get_size()[0]).map(move |x| [x, y]))
+            .filter(|&tile| distances[index(tile)].is_some())
+            .collect();
+        tiles.sort_by_key(|&tile| distances[index(tile)]);
+        let mut counts: Vec<u128> = vec![0; distances.len()];
+        counts[index(maze.get_entry())] = 1;
+        for &[x, y] in &tiles {
+            for (_, next) in maze.get_moves(x, y) {
+                if distances[index(next)] == Some(distances[index([x, y])].unwrap() + 1) {
+                    counts[index(next)] += counts[index([x, y])];
+                }
+            }
+        }
+        let exits: Vec<[u32; 2]> = maze
+            .get_grid_exits()
+            .into_iter()
+            .filter(|&exit| exit != maze.get_entry() && distances[index(exit)].is_some())
+            .collect();
+        let length = exits.iter().map(|&exit| distances[index(exit)]).min()?;
+        Some(
+            exits
+                .iter()
+                .filter(|&&exit| distances[index(exit)] == length)
+                .map(|&exit| counts[index(exit)])
+                .sum(),
+        )
+    }
+
+    fn get_ring(portal_cost: isize) -> Maze {
+        let options = MazeOptions {
+            portals: vec![([1, 3], [5, 3])],
+            portal_cost,
+            ..MazeOptions::default()
+        };
+        get_maze(&RING, [3, 0], options)
+    }
+
+    #[test]
+    fn zero_cost_portals_add_paths_in_both_directions() {
+        let maze = get_ring(0);
+        let shortest_paths = maze.get_shortest_paths().unwrap();
+        assert_eq!(shortest_paths.length, 10);
+        assert_eq!(shortest_paths.exits, vec![[3, 6]]);
+        assert_eq!(shortest_paths.count, 4);
+        let mut paths = maze.enumerate_shortest_paths(&shortest_paths, 10);
+        assert_eq!(paths.len(), 4);
+        for path in &paths {
+            let validation = maze.validate_path(&maze.expand_path(path)).unwrap();
+            assert!(validation.is_optimal());
+        }
+        assert!(paths.contains(&vec![
+            [3, 0],
+            [3, 1],
+            [5, 1],
+            [5, 3],
+            [1, 3],
+            [1, 5],
+            [3, 5],
+            [3, 6]
+        ]));
+        paths.sort();
+        paths.dedup();
+        assert_eq!(paths.len(), 4);
+        assert_eq!(maze.enumerate_shortest_paths(&shortest_paths, 3).len(), 3);
+    }
+
+    #[test]
+    fn portals_with_cost_add_no_shortest_path() {
+        let maze = get_ring(1);
+        let shortest_paths = maze.get_shortest_paths().unwrap();
+        assert_eq!(shortest_paths.count, 2);
+        assert!(!shortest_paths.is_unique());
+        assert_eq!(maze.enumerate_shortest_paths(&shortest_paths, 10).len(), 2);
+    }
+
+    #[test]
+    fn corridors_have_a_unique_shortest_path() {
+        let maze = get_maze(
+            &["#.###", "#.###", "#...#", "###.#", "###.#"],
+            [1, 0],
+            MazeOptions::default(),
+        );
+        let shortest_paths = maze.get_shortest_paths().unwrap();
+        assert!(shortest_paths.is_unique());
+        assert_eq!(shortest_paths.exits, vec![[3, 4]]);
+        assert_eq!(
+            maze.enumerate_shortest_paths(&shortest_paths, 10),
+            vec![vec![[1, 0], [1, 2], [3, 2], [3, 4]]]
+        );
+    }
+
+    #[test]
+    fn open_rooms_count_every_order_of_the_steps() {
+        let room = ["#.####", "#....#", "#....#", "#....#", "#....#", "####.#"];
+        let maze = get_maze(&room, [1, 0], MazeOptions::default());
+        let shortest_paths = maze.get_shortest_paths().unwrap();
+        assert_eq!(shortest_paths.length, 8);
+        assert_eq!(shortest_paths.count, 20);
+        let mut paths = maze.enumerate_shortest_paths(&shortest_paths, 100);
+        for path in &paths {
+            let validation = maze.validate_path(&maze.expand_path(path)).unwrap();
+            assert!(validation.is_optimal());
+        }
+        paths.sort();
+        paths.dedup();
+        assert_eq!(paths.len(), 20);
+    }
+
+    #[test]
+    fn path_counts_agree_with_counting_tile_by_tile() {
+        for seed in 1..=40 {
+            let maze = get_random_maze([16, 12], seed, MazeOptions::default());
+            let count = maze
+                .get_shortest_paths()
+                .map(|shortest_paths| shortest_paths.count);
+            assert_eq!(count, count_tile_paths(&maze), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn shared_and_alternative_tiles_are_colored_apart() {
+        let maze = get_maze(
+            &["##.##", "#...#", "#.#.#", "#...#", "##.##"],
+            [2, 0],
+            MazeOptions::default(),
+        );
+        let shortest_paths = maze.get_shortest_paths().unwrap();
+        assert_eq!(shortest_paths.count, 2);
+        let path = env::temp_dir().join("shortest_paths_test.png");
+        maze.export_shortest_paths_png(path.to_str().unwrap().to_string(), &shortest_paths);
+        let mut expected = get_image(&["##S##", "#ASA#", "#A#A#", "#ASA#", "##S##"]);
+        for pixel in expected.pixels_mut() {
+            if *pixel == Rgb(get_color('S')) {
+                *pixel = SHARED_COLOR;
+            } else if *pixel == Rgb(get_color('A')) {
+                *pixel = ALTERNATIVE_COLOR;
+            }
+        }
+        assert_eq!(img::open(&path).unwrap().to_rgb8(), expected);
+    }
+}
@@ -0,0 +1,255 @@
+use super::{Maze, MazeError};
+use std::cell::OnceCell;
+use std::collections::HashSet;
+
+impl Maze {
+    /**
+     * Turns the tile into a wall or a walkable tile and repairs the graph
+     * around it instead of scanning the whole maze again. Nodes can only
+     * appear or vanish on the tile and its neighbors, and only the corridors
+     * passing them change, so just the edges of the nodes at their ends are
+     * walked again.
+     * # Returns
+     * OutOfBounds if the tile lies outside of the maze, InvalidPortal if it
+     * is the end of a portal that would be walled in and ProtectedTile if it
+     * is the entry, a key, a door or a stair
+     */
+    pub fn set_wall(&mut self, x: u32, y: u32, is_wall: bool) -> Result<(), MazeError> {
+        if self.get_tile(x, y)? == is_wall {
+            return Ok(());
+        }
+        if is_wall && self.portals.contains_key(&[x, y]) {
+            return Err(MazeError::InvalidPortal);
+        }
+        if is_wall
+            && ([x, y] == self.entry
+                || self.keys.contains_key(&[x, y])
+                || self.doors.contains_key(&[x, y])
+                || self.stairs.contains_key(&[x, y]))
+        {
+            return Err(MazeError::ProtectedTile);
+        }
+        let width = self.size[0];
+        let index = move |[x, y]: [u32; 2]| (y * width + x) as usize;
+        if !is_wall && !self.costs.is_empty() {
+            self.min_cost = self.min_cost.min(self.costs[index([x, y])] as isize);
+        }
+        let area = self.get_neighborhood(x, y);
+        let affected = match self.graph.get() {
+            Some(_) => self.get_affected_tiles(&area),
+            None => {
+                self.set_tile(x, y, is_wall);
+                return Ok(());
+            }
+        };
+        self.set_tile(x, y, is_wall);
+        let (mut graph, mut node_indices) = self.graph.take().unwrap();
+        for &tile in &affected {
+            graph.clear_edges(node_indices[index(tile)].unwrap());
+        }
+        for &[area_x, area_y] in &area {
+            let is_node = self.is_node_tile(area_x, area_y);
+            match node_indices[index([area_x, area_y])] {
+                Some(node_index) if !is_node => {
+                    graph.swap_remove_node(node_index);
+                    node_indices[index([area_x, area_y])] = None;
+                    if node_index < graph.get_node_amount() {
+                        let moved = graph.get_node(node_index).element.0;
+                        node_indices[index(moved)] = Some(node_index);
+                    }
+                }
+                None if is_node => {
+                    let is_opening = self.is_opening_tile(area_x, area_y);
+                    node_indices[index([area_x, area_y])] =
+                        Some(graph.add_node(([area_x, area_y], is_opening)));
+                }
+                _ => {}
+            }
+        }
+        let nodes: HashSet<usize> = affected
+            .iter()
+            .chain(area.iter())
+            .filter_map(|&tile| node_indices[index(tile)])
+            .collect();
+        let steps = self.get_steps();
+        for &node_index in &nodes {
+            self.add_corridor_edges(&mut graph, &node_indices, node_index, &steps);
+        }
+        for &node_index in &nodes {
+            self.add_portal_edge(&mut graph, &node_indices, node_index);
+            let tile = graph.get_node(node_index).element.0;
+            if let Some(&partner) = self.portals.get(&tile) {
+                let partner_index = node_indices[index(partner)].unwrap();
+                self.add_portal_edge(&mut graph, &node_indices, partner_index);
+            }
+        }
+        self.graph = OnceCell::from((graph, node_indices));
+        Ok(())
+    }
+
+    /**
+     * # Returns
+     * The tile and the tiles around it, the only ones whose moves change if
+     * the tile is turned into a wall or back
+     */
+    fn get_neighborhood(&self, x: u32, y: u32) -> Vec<[u32; 2]> {
+        let mut tiles: Vec<[u32; 2]> = vec![[x, y]];
+        for step_y in -1..=1 {
+            for step_x in -1..=1 {
+                if let Some(tile) = self.offset(x, y, [step_x, step_y]) {
+                    if !tiles.contains(&tile) {
+                        tiles.push(tile);
+                    }
+                }
+            }
+        }
+        tiles
+    }
+
+    /**
+     * # Returns
+     * The tiles of the nodes in the area and of the nodes at the ends of
+     * every corridor starting in or passing through it
+     */
+    fn get_affected_tiles(&self, area: &[[u32; 2]]) -> HashSet<[u32; 2]> {
+        let (graph, node_indices) = self.graph.get().unwrap();
+        let mut tiles: HashSet<[u32; 2]> = HashSet::new();
+        for &[x, y] in area {
+            if self.get_tile(x, y).unwrap() {
+                continue;
+            }
+            if node_indices[(y * self.size[0] + x) as usize].is_some() {
+                tiles.insert([x, y]);
+            }
+            for step in self.get_steps() {
+                if let Some((node_index, _, _)) = self.follow_corridor(node_indices, x, y, step) {
+                    tiles.insert(graph.get_node(node_index).element.0);
+                }
+            }
+        }
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::fixtures::{get_color, get_maze, get_random_rows, CONNECTIVITIES};
+    use crate::maze::search::Algorithm;
+    use crate::maze::{Maze, MazeError, MazeOptions, TerrainCosts, Topology};
+    use std::collections::BTreeSet;
+
+    type Nodes = BTreeSet<([u32; 2], bool)>;
+    type Edges = BTreeSet<([u32; 2], [u32; 2], isize)>;
+
+    /**
+     * # Returns
+     * The nodes and edges of the graph by their tiles, which do not depend on
+     * the order the nodes were added in
+     */
+    fn get_tile_graph(maze: &Maze) -> (Nodes, Edges) {
+        let graph = maze.get_graph();
+        let mut nodes: Nodes = BTreeSet::new();
+        let mut edges: Edges = BTreeSet::new();
+        for node_index in 0..graph.get_node_amount() {
+            let (tile, is_opening) = graph.get_node(node_index).element;
+            assert_eq!(maze.get_node_index(tile[0], tile[1]), Some(node_index));
+            nodes.insert((tile, is_opening));
+            for &(neighbor, weight) in graph.get_weighted_neighbors(node_index) {
+                edges.insert((tile, graph.get_node(neighbor).element.0, weight));
+            }
+        }
+        (nodes, edges)
+    }
+
+    #[test]
+    fn protected_tiles_stay_walkable() {
+        let options = MazeOptions {
+            portals: vec![([1, 1], [1, 3])],
+            ..MazeOptions::default()
+        };
+        let mut maze = get_maze(&["#.#", "#.#", "#.#", "#.#", "#.#"], [1, 0], options);
+        assert!(matches!(
+            maze.set_wall(1, 0, true),
+            Err(MazeError::ProtectedTile)
+        ));
+        assert!(matches!(
+            maze.set_wall(1, 1, true),
+            Err(MazeError::InvalidPortal)
+        ));
+        assert!(matches!(
+            maze.set_wall(3, 0, true),
+            Err(MazeError::OutOfBounds)
+        ));
+        assert!(maze.set_wall(1, 2, true).is_ok());
+        assert_eq!(maze.solve(Algorithm::Bfs).unwrap().length, 2);
+        assert!(maze.set_wall(1, 1, false).is_ok());
+    }
+
+    #[test]
+    fn repaired_graphs_match_a_fresh_scan() {
+        let topologies = [(Topology::Bounded, None), (Topology::Torus, Some([14, 10]))];
+        for connectivity in CONNECTIVITIES {
+            for (topology, exit) in topologies {
+                for seed in 1..=6 {
+                    let mut rows: Vec<Vec<char>> = get_random_rows([16, 12], seed)
+                        .into_iter()
+                        .map(|row| row.chars().collect())
+                        .collect();
+                    rows[10][14] = '.';
+                    for (index, &[x, y]) in [[5, 5], [6, 5], [7, 6], [9, 3]].iter().enumerate() {
+                        rows[y][x] = if index < 2 { '.' } else { 'g' };
+                    }
+                    let options = MazeOptions {
+                        connectivity,
+                        topology,
+                        exit,
+                        terrain: TerrainCosts::Palette(vec![(get_color('g'), 4)]),
+                        portals: vec![([5, 5], [6, 5])],
+                        ..MazeOptions::default()
+                    };
+                    let build = |rows: &[Vec<char>]| {
+                        let rows: Vec<String> =
+                            rows.iter().map(|row| row.iter().collect()).collect();
+                        let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+                        get_maze(&rows, [3, 0], options.clone())
+                    };
+                    let mut maze = build(&rows);
+                    let fixed = rows.clone();
+                    let mut random = seed;
+                    for _ in 0..40 {
+                        random ^= random << 13;
+                        random ^= random >> 7;
+                        random ^= random << 17;
+                        let [x, y] = [random as usize % 16, (random >> 8) as usize % 12];
+                        if [x, y] == [3, 0] || [x, y] == [14, 10] {
+                            continue;
+                        }
+                        let is_wall = rows[y][x] != '#';
+                        match maze.set_wall(x as u32, y as u32, is_wall) {
+                            Ok(()) => {}
+                            Err(MazeError::InvalidPortal) => continue,
+                            Err(error) => panic!("{}", error),
+                        }
+                        rows[y][x] = match (is_wall, fixed[y][x]) {
+                            (true, _) => '#',
+                            (false, '#') => '.',
+                            (false, tile) => tile,
+                        };
+                        let fresh = build(&rows);
+                        assert_eq!(
+                            get_tile_graph(&maze),
+                            get_tile_graph(&fresh),
+                            "seed {} tile {:?}",
+                            seed,
+                            [x, y]
+                        );
+                        let length = |maze: &Maze| {
+                            maze.solve(Algorithm::AStar).map(|solution| solution.length)
+                        };
+                        assert_eq!(length(&maze), length(&fresh));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use super::{Maze, MazeError};
+use img::RgbImage;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter, Result as fmtResult};
+
+pub struct PathValidation {
+    pub length: isize,
+    pub shortest_length: Option<isize>,
+}
+
+impl PathValidation {
+    pub fn is_optimal(&self) -> bool {
+        self.shortest_length == Some(self.length)
+    }
+}
+
+impl Maze {
+    /**
+     * Checks a proposed solution given as the list of every visited tile.
+     * Walking over a key picks it up, a door can only be passed with a key
+     * of its color.
+     * # Returns
+     * The cost of the path together with the cost of the shortest solution,
+     * or the first step that breaks the rules
+     */
+    pub fn validate_path(&self, path: &[[u32; 2]]) -> Result<PathValidation, PathError> {
+        let first = *path.first().ok_or(PathError::Empty)?;
+        if first != self.entry {
+            return Err(PathError::WrongStart(first));
+        }
+        let mut length = 0;
+        let mut collected: Vec<usize> = Vec::new();
+        for (step, &[x, y]) in path.iter().enumerate() {
+            match self.get_tile(x, y) {
+                Err(_) => return Err(PathError::OutOfBounds(step, [x, y])),
+                Ok(true) => return Err(PathError::Wall(step, [x, y])),
+                Ok(false) => {}
+            }
+            if let Some(&door) = self.doors.get(&[x, y]) {
+                if !collected.contains(&door) {
+                    return Err(PathError::LockedDoor(step, [x, y]));
+                }
+            }
+            if let Some(&key) = self.keys.get(&[x, y]) {
+                collected.push(key);
+            }
+            if step > 0 {
+                let [previous_x, previous_y] = path[step - 1];
+                let delta = self
+                    .get_steps()
+                    .into_iter()
+                    .find(|&delta| self.offset(previous_x, previous_y, delta) == Some([x, y]));
+                let delta = match delta {
+                    Some(delta) => delta,
+                    None if self.get_portal_partner([previous_x, previous_y]) == Some([x, y]) => {
+                        length += self.options.portal_cost;
+                        continue;
+                    }
+                    None => return Err(PathError::NotContiguous(step, [x, y])),
+                };
+                if !self.can_leave([previous_x, previous_y], delta) {
+                    return Err(PathError::AgainstOneWay(step, [x, y]));
+                }
+                if self.get_step(previous_x, previous_y, delta).is_none() {
+                    return Err(PathError::CutsCorner(step, [x, y]));
+                }
+                length += self.get_move_cost(previous_x, previous_y, delta);
+            }
+        }
+        let last = *path.last().unwrap();
+        if last == self.entry || !self.is_opening(last[0], last[1]) {
+            return Err(PathError::NoExit(last));
+        }
+        let shortest_length = if self.doors.is_empty() {
+            self.get_shortest_exit_distance()
+        } else {
+            self.solve_keys()
+                .ok()
+                .map(|key_route| key_route.solution.length)
+        };
+        Ok(PathValidation {
+            length,
+            shortest_length,
+        })
+    }
+
+    /**
+     * Reads a solution that was painted onto a copy of the maze image in the
+     * given color, see get_painted_path.
+     * # Returns
+     * UnreadableImage if the image cannot be opened
+     */
+    pub fn read_painted_path(
+        &self,
+        path: String,
+        color: [u8; 3],
+    ) -> Result<Vec<[u32; 2]>, MazeError> {
+        let image: RgbImage = img::open(&path)
+            .map_err(|_| MazeError::UnreadableImage)?
+            .to_rgb8();
+        self.get_painted_path(&image, color)
+    }
+
+    /**
+     * Follows the tiles painted in the given color from the entry with the
+     * steps the connectivity of the maze allows, every tile is taken once. A
+     * diagonal step to the tile behind a painted corner is left out so that
+     * the corner is walked around, and a painted end of a portal without a
+     * painted neighbor continues at the other end. Painted paths must not
+     * touch themselves. Tiles that can not be reached that way are appended
+     * afterwards so that the validation reports the gap.
+     * # Returns
+     * AmbiguousPath if the path can continue to more than one tile
+     */
+    pub fn get_painted_path(
+        &self,
+        image: &RgbImage,
+        color: [u8; 3],
+    ) -> Result<Vec<[u32; 2]>, MazeError> {
+        if image.dimensions() != (self.size[0], self.size[1]) {
+            return Err(MazeError::SizeMismatch);
+        }
+        let index = |[x, y]: [u32; 2]| (y * self.size[0] + x) as usize;
+        let mut painted: Vec<bool> = image.pixels().map(|pixel| pixel.0 == color).collect();
+        let mut tiles: Vec<[u32; 2]> = Vec::new();
+        let mut tile = self.entry;
+        while painted[index(tile)] {
+            painted[index(tile)] = false;
+            tiles.push(tile);
+            let [x, y] = tile;
+            let candidates: Vec<([i64; 2], [u32; 2])> = self
+                .get_steps()
+                .into_iter()
+                .filter_map(|step| self.offset(x, y, step).map(|next| (step, next)))
+                .filter(|&(_, next)| painted[index(next)])
+                .collect();
+            let is_straight = |[step_x, step_y]: [i64; 2]| step_x == 0 || step_y == 0;
+            let is_behind_corner = |next: [u32; 2]| {
+                candidates.iter().any(|&(step, [corner_x, corner_y])| {
+                    is_straight(step)
+                        && [[0, -1], [1, 0], [0, 1], [-1, 0]]
+                            .iter()
+                            .any(|&side| self.offset(corner_x, corner_y, side) == Some(next))
+                })
+            };
+            let mut nexts: Vec<[u32; 2]> = candidates
+                .iter()
+                .filter(|&&(step, next)| is_straight(step) || !is_behind_corner(next))
+                .map(|&(_, next)| next)
+                .collect();
+            if nexts.is_empty() {
+                nexts.extend(
+                    self.get_portal_partner(tile)
+                        .filter(|&partner| painted[index(partner)]),
+                );
+            }
+            match nexts.as_slice() {
+                [] => break,
+                [next] => tile = *next,
+                _ => return Err(MazeError::AmbiguousPath),
+            }
+        }
+        for (index, is_painted) in painted.iter().enumerate() {
+            if *is_painted {
+                tiles.push([index as u32 % self.size[0], index as u32 / self.size[0]]);
+            }
+        }
+        Ok(tiles)
+    }
+
+    fn is_opening(&self, x: u32, y: u32) -> bool {
+        match self.get_node_index(x, y) {
+            Some(node_index) => self.get_graph().get_node(node_index).element.1,
+            None => false,
+        }
+    }
+}
+
+pub enum PathError {
+    Empty,
+    WrongStart([u32; 2]),
+    OutOfBounds(usize, [u32; 2]),
+    Wall(usize, [u32; 2]),
+    NotContiguous(usize, [u32; 2]),
+    CutsCorner(usize, [u32; 2]),
+    AgainstOneWay(usize, [u32; 2]),
+    LockedDoor(usize, [u32; 2]),
+    NoExit([u32; 2]),
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmtResult {
+        match self {
+            Self::Empty => write!(f, "The Path is empty"),
+            Self::WrongStart([x, y]) => {
+                write!(f, "The Path starts at ({},{}) instead of the Entry", x, y)
+            }
+            Self::OutOfBounds(step, [x, y]) => {
+                write!(f, "Step {} leaves the Maze at ({},{})", step, x, y)
+            }
+            Self::Wall(step, [x, y]) => write!(f, "Step {} crosses a Wall at ({},{})", step, x, y),
+            Self::NotContiguous(step, [x, y]) => {
+                write!(
+                    f,
+                    "Step {} jumps to ({},{}) which is not adjacent",
+                    step, x, y
+                )
+            }
+            Self::CutsCorner(step, [x, y]) => {
+                write!(f, "Step {} cuts a Corner to reach ({},{})", step, x, y)
+            }
+            Self::AgainstOneWay(step, [x, y]) => {
+                write!(
+                    f,
+                    "Step {} leaves a One-Way Tile against its Direction to ({},{})",
+                    step, x, y
+                )
+            }
+            Self::LockedDoor(step, [x, y]) => {
+                write!(
+                    f,
+                    "Step {} enters a locked Door at ({},{}) without its Key",
+                    step, x, y
+                )
+            }
+            Self::NoExit([x, y]) => write!(f, "The Path ends at ({},{}) which is no Exit", x, y),
+        }
+    }
+}
+
+impl Debug for PathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmtResult {
+        Display::fmt(self, f)
+    }
+}
+
+impl Error for PathError {}
+
+#[cfg(test)]
+mod tests {
+    use super::PathError;
+    use crate::maze::fixtures::{get_color, get_image, get_maze};
+    use crate::maze::{Connectivity, CornerCutting, MazeError, MazeOptions};
+
+    const LOOP: [&str; 5] = ["#.###", "#...#", "#.#.#", "#...#", "#.###"];
+
+    #[test]
+    fn valid_paths_are_compared_with_the_shortest_one() {
+        let maze = get_maze(&LOOP, [1, 0], MazeOptions::default());
+        let straight = [[1, 0], [1, 1], [1, 2], [1, 3], [1, 4]];
+        let validation = maze.validate_path(&straight).unwrap();
+        assert_eq!(validation.length, 4);
+        assert!(validation.is_optimal());
+        let detour = [
+            [1, 0],
+            [1, 1],
+            [2, 1],
+            [3, 1],
+            [3, 2],
+            [3, 3],
+            [2, 3],
+            [1, 3],
+            [1, 4],
+        ];
+        let validation = maze.validate_path(&detour).unwrap();
+        assert_eq!(validation.length, 8);
+        assert_eq!(validation.shortest_length, Some(4));
+        assert!(!validation.is_optimal());
+    }
+
+    #[test]
+    fn broken_paths_report_the_first_wrong_step() {
+        let maze = get_maze(&LOOP, [1, 0], MazeOptions::default());
+        let validate = |path: &[[u32; 2]]| maze.validate_path(path).err().unwrap();
+        assert!(matches!(validate(&[]), PathError::Empty));
+        assert!(matches!(validate(&[[1, 1]]), PathError::WrongStart([1, 1])));
+        assert!(matches!(
+            validate(&[[1, 0], [1, 1], [2, 2]]),
+            PathError::Wall(2, [2, 2])
+        ));
+        assert!(matches!(
+            validate(&[[1, 0], [1, 1], [1, 3]]),
+            PathError::NotContiguous(2, [1, 3])
+        ));
+        assert!(matches!(
+            validate(&[[1, 0], [1, 1], [5, 1]]),
+            PathError::OutOfBounds(2, [5, 1])
+        ));
+        assert!(matches!(
+            validate(&[[1, 0], [1, 1], [2, 1]]),
+            PathError::NoExit([2, 1])
+        ));
+    }
+
+    const DIAGONAL: [&str; 5] = ["#####.#", "####..#", "##...##", "#..####", "#.#####"];
+
+    fn get_diagonal_options() -> MazeOptions {
+        MazeOptions {
+            connectivity: Connectivity::Eight(CornerCutting::Allow),
+            ..MazeOptions::default()
+        }
+    }
+
+    #[test]
+    fn painted_paths_are_followed_diagonally_upwards() {
+        let maze = get_maze(&DIAGONAL, [1, 4], get_diagonal_options());
+        let painted = get_image(&["#####R#", "####R.#", "##.R.##", "#.R####", "#R#####"]);
+        let tiles = maze.get_painted_path(&painted, get_color('R')).unwrap();
+        assert_eq!(tiles, vec![[1, 4], [2, 3], [3, 2], [4, 1], [5, 0]]);
+        assert!(maze.validate_path(&tiles).unwrap().is_optimal());
+    }
+
+    #[test]
+    fn painted_corners_are_walked_around() {
+        let maze = get_maze(&DIAGONAL, [5, 0], get_diagonal_options());
+        let painted = get_image(&["#####R#", "####RR#", "##RRR##", "#RR####", "#R#####"]);
+        let tiles = maze.get_painted_path(&painted, get_color('R')).unwrap();
+        assert_eq!(
+            tiles,
+            vec![
+                [5, 0],
+                [5, 1],
+                [4, 1],
+                [4, 2],
+                [3, 2],
+                [2, 2],
+                [2, 3],
+                [1, 3],
+                [1, 4]
+            ]
+        );
+        assert_eq!(maze.validate_path(&tiles).unwrap().length, 80);
+    }
+
+    #[test]
+    fn painted_paths_touching_themselves_are_ambiguous() {
+        let maze = get_maze(
+            &["#.###", "#...#", "#...#", "###.#"],
+            [1, 0],
+            MazeOptions::default(),
+        );
+        let painted = get_image(&["#R###", "#RR.#", "#RRR#", "###R#"]);
+        assert!(matches!(
+            maze.get_painted_path(&painted, get_color('R')),
+            Err(MazeError::AmbiguousPath)
+        ));
+    }
+
+    #[test]
+    fn unreadable_images_are_reported() {
+        let maze = get_maze(&DIAGONAL, [1, 4], MazeOptions::default());
+        let path = String::from("./does_not_exist.png");
+        assert!(matches!(
+            maze.read_painted_path(path, get_color('R')),
+            Err(MazeError::UnreadableImage)
+        ));
+    }
+}
@@ -0,0 +1,259 @@
+use super::search::{Algorithm, Expansion, Solution};
+use super::Maze;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use img::{Rgb, RgbImage};
+use std::io::{stdout, Stdout, Write};
+
+const NODE_COLOR: Rgb<u8> = Rgb([255, 160, 122]);
+
+struct Viewer<'a> {
+    maze: &'a Maze,
+    trace: Vec<Expansion>,
+    solution: Option<Solution>,
+    canvas: RgbImage,
+    step: usize,
+    offset: [u32; 2],
+    zoom: u32,
+    show_nodes: bool,
+    show_path: bool,
+}
+
+impl Maze {
+    /**
+     * Opens an interactive view of the maze in the terminal in which the
+     * search of the given algorithm can be stepped through expansion by
+     * expansion.
+     *
+     * Arrow keys scroll, + and - zoom, space and backspace step forwards and
+     * backwards, e jumps to the end of the search, n toggles the graph nodes,
+     * p the path and q quits.
+     */
+    pub fn run_viewer(&self, algorithm: Algorithm) -> crossterm::Result<()> {
+        let (solution, trace) = self.solve_traced(algorithm);
+        let mut viewer = Viewer {
+            maze: self,
+            trace,
+            solution,
+            canvas: self.get_maze_image(),
+            step: 0,
+            offset: [0, 0],
+            zoom: 1,
+            show_nodes: false,
+            show_path: false,
+        };
+        let mut out = stdout();
+        terminal::enable_raw_mode()?;
+        execute!(out, EnterAlternateScreen, Hide)?;
+        let result = viewer.run(&mut out);
+        execute!(out, ResetColor, Show, LeaveAlternateScreen)?;
+        terminal::disable_raw_mode()?;
+        result
+    }
+}
+
+impl<'a> Viewer<'a> {
+    fn run(&mut self, out: &mut Stdout) -> crossterm::Result<()> {
+        loop {
+            self.draw(out)?;
+            if let Event::Key(key) = event::read()? {
+                let [columns, rows] = self.get_view_tiles()?;
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Left => self.offset[0] = self.offset[0].saturating_sub(columns / 2),
+                    KeyCode::Right => self.offset[0] += columns / 2,
+                    KeyCode::Up => self.offset[1] = self.offset[1].saturating_sub(rows / 2),
+                    KeyCode::Down => self.offset[1] += rows / 2,
+                    KeyCode::Char('+') => self.zoom = (self.zoom / 2).max(1),
+                    KeyCode::Char('-') => self.zoom = (self.zoom * 2).min(64),
+                    KeyCode::Char(' ') => self.set_step(self.step + 1),
+                    KeyCode::Backspace => self.set_step(self.step.saturating_sub(1)),
+                    KeyCode::Char('e') => self.set_step(self.trace.len()),
+                    KeyCode::Char('n') => self.show_nodes = !self.show_nodes,
+                    KeyCode::Char('p') => self.show_path = !self.show_path,
+                    _ => {}
+                }
+                let [columns, rows] = self.get_view_tiles()?;
+                let [width, height] = self.maze.size;
+                self.offset[0] = self.offset[0].min(width.saturating_sub(columns));
+                self.offset[1] = self.offset[1].min(height.saturating_sub(rows));
+            }
+        }
+    }
+
+    /**
+     * # Returns
+     * The amount of tiles that fit on the screen next to and below each other,
+     * a tile takes two characters in width and one line is kept for the status
+     */
+    fn get_view_tiles(&self) -> crossterm::Result<[u32; 2]> {
+        let (columns, rows) = terminal::size()?;
+        Ok([
+            (columns as u32 / 2) * self.zoom,
+            (rows as u32).saturating_sub(1) * self.zoom,
+        ])
+    }
+
+    fn set_step(&mut self, step: usize) {
+        let step = step.min(self.trace.len());
+        if step < self.step {
+            self.canvas = self.maze.get_maze_image();
+            self.step = 0;
+        }
+        for expansion in &self.trace[self.step..step] {
+            self.maze.draw_expansion(&mut self.canvas, expansion);
+        }
+        self.step = step;
+    }
+
+    fn get_color(&self, x: u32, y: u32) -> Rgb<u8> {
+        let mut color = *self.canvas.get_pixel(x, y);
+        if self.show_nodes
+            && color == Rgb([255, 255, 255])
+            && self.maze.get_node_index(x, y).is_some()
+        {
+            color = NODE_COLOR;
+        }
+        color
+    }
+
+    /**
+     * Picks the color of a block of zoom x zoom tiles. Colored tiles win over
+     * plain ones, otherwise the block is a wall if most of its tiles are.
+     */
+    fn get_block_color(&self, block_x: u32, block_y: u32, path: &[bool]) -> Option<Rgb<u8>> {
+        let [width, height] = self.maze.size;
+        let (start_x, start_y) = (block_x * self.zoom, block_y * self.zoom);
+        if start_x >= width || start_y >= height {
+            return None;
+        }
+        let mut walls = 0;
+        let mut tiles = 0;
+        let mut colored: Option<Rgb<u8>> = None;
+        for y in start_y..(start_y + self.zoom).min(height) {
+            for x in start_x..(start_x + self.zoom).min(width) {
+                if path[(y * width + x) as usize] {
+                    return Some(Rgb([255, 0, 0]));
+                }
+                tiles += 1;
+                match self.get_color(x, y) {
+                    Rgb([0, 0, 0]) => walls += 1,
+                    Rgb([255, 255, 255]) => {}
+                    color => colored = Some(color),
+                }
+            }
+        }
+        Some(colored.unwrap_or(if walls * 2 > tiles {
+            Rgb([0, 0, 0])
+        } else {
+            Rgb([255, 255, 255])
+        }))
+    }
+
+    fn draw(&self, out: &mut Stdout) -> crossterm::Result<()> {
+        let (columns, rows) = terminal::size()?;
+        let [width, _] = self.maze.size;
+        let mut path = vec![false; self.maze.maze.len()];
+        if self.show_path || self.step == self.trace.len() {
+            if let Some(solution) = &self.solution {
+                for [x, y] in self.maze.expand_path(&solution.path) {
+                    path[(y * width + x) as usize] = true;
+                }
+            }
+        }
+        let [offset_x, offset_y] = [self.offset[0] / self.zoom, self.offset[1] / self.zoom];
+        for row in 0..rows.saturating_sub(1) {
+            queue!(out, MoveTo(0, row))?;
+            for column in 0..columns / 2 {
+                let block =
+                    self.get_block_color(offset_x + column as u32, offset_y + row as u32, &path);
+                let color = match block {
+                    Some(Rgb([r, g, b])) => Color::Rgb { r, g, b },
+                    None => Color::Reset,
+                };
+                queue!(out, SetBackgroundColor(color), Print("  "))?;
+            }
+        }
+        let status = format!(
+            "Schritt {}/{} | Zoom 1:{} | Position ({},{}) | Leertaste: weiter, Rücktaste: zurück, e: Ende, n: Knoten, p: Weg, q: Beenden",
+            self.step,
+            self.trace.len(),
+            self.zoom,
+            self.offset[0],
+            self.offset[1]
+        );
+        queue!(
+            out,
+            ResetColor,
+            MoveTo(0, rows.saturating_sub(1)),
+            terminal::Clear(terminal::ClearType::CurrentLine),
+            Print(status.chars().take(columns as usize).collect::<String>())
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Viewer, NODE_COLOR};
+    use crate::maze::fixtures::get_maze;
+    use crate::maze::search::Algorithm;
+    use crate::maze::{Maze, MazeOptions};
+    use img::Rgb;
+
+    const FORK: [&str; 5] = ["#.###", "#...#", "#.#.#", "#.#.#", "#.#.#"];
+
+    fn get_viewer(maze: &Maze) -> Viewer<'_> {
+        let (solution, trace) = maze.solve_traced(Algorithm::Bfs);
+        Viewer {
+            maze,
+            trace,
+            solution,
+            canvas: maze.get_maze_image(),
+            step: 0,
+            offset: [0, 0],
+            zoom: 1,
+            show_nodes: false,
+            show_path: false,
+        }
+    }
+
+    #[test]
+    fn stepping_back_redraws_the_earlier_expansions() {
+        let maze = get_maze(&FORK, [1, 0], MazeOptions::default());
+        let mut viewer = get_viewer(&maze);
+        viewer.set_step(2);
+        let after_two = viewer.canvas.clone();
+        viewer.set_step(usize::MAX);
+        assert_eq!(viewer.step, viewer.trace.len());
+        viewer.set_step(2);
+        assert_eq!(viewer.canvas, after_two);
+        viewer.set_step(0);
+        assert_eq!(viewer.canvas, maze.get_maze_image());
+    }
+
+    #[test]
+    fn nodes_and_zoomed_blocks_get_their_colors() {
+        let maze = get_maze(&FORK, [1, 0], MazeOptions::default());
+        let mut viewer = get_viewer(&maze);
+        assert_eq!(viewer.get_color(1, 1), Rgb([255, 255, 255]));
+        viewer.show_nodes = true;
+        assert_eq!(viewer.get_color(1, 1), NODE_COLOR);
+        assert_eq!(viewer.get_color(1, 2), Rgb([255, 255, 255]));
+        let mut path = vec![false; 25];
+        viewer.zoom = 2;
+        assert_eq!(viewer.get_block_color(0, 0, &path), Some(NODE_COLOR));
+        assert_eq!(
+            viewer.get_block_color(1, 1, &path),
+            Some(Rgb([255, 255, 255]))
+        );
+        assert_eq!(viewer.get_block_color(2, 1, &path), Some(Rgb([0, 0, 0])));
+        assert_eq!(viewer.get_block_color(3, 0, &path), None);
+        path[12] = true;
+        assert_eq!(viewer.get_block_color(1, 1, &path), Some(Rgb([255, 0, 0])));
+    }
+}
@@ -0,0 +1,643 @@
+use super::{Maze, MazeError};
+use img::codecs::gif::{GifEncoder, Repeat};
+use img::{Frame, Rgb, RgbImage};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter, Result as fmtResult};
+use std::fs::File;
+
+const WALKED_COLOR: Rgb<u8> = Rgb([255, 0, 0]);
+const FILLED_COLOR: Rgb<u8> = Rgb([160, 160, 160]);
+const POSITION_COLOR: Rgb<u8> = Rgb([0, 0, 255]);
+
+/**
+ * A step and the tile it leads to
+ */
+type Move = ([i64; 2], [u32; 2]);
+
+/**
+ * The hand a walker keeps on the wall
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+/**
+ * The algorithms a person could follow inside the maze, they only look at
+ * the tile they stand on instead of searching the graph
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub enum Walker {
+    /**
+     * Keeps one hand on the wall
+     */
+    WallFollower(Hand),
+    /**
+     * Walks in one main direction and follows the wall with one hand until
+     * the turns made add up to zero again
+     */
+    Pledge(Hand),
+    /**
+     * Marks every passage it walks and never walks a passage a third time
+     */
+    Tremaux,
+    /**
+     * Fills every dead end until only the ways from the entry to the exits
+     * are left
+     */
+    DeadEndFilling,
+}
+
+impl Walker {
+    pub const ALL: [Walker; 6] = [
+        Walker::WallFollower(Hand::Right),
+        Walker::WallFollower(Hand::Left),
+        Walker::Pledge(Hand::Right),
+        Walker::Pledge(Hand::Left),
+        Walker::Tremaux,
+        Walker::DeadEndFilling,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Walker> {
+        match name.to_lowercase().as_str() {
+            "rechts" | "right" => Some(Walker::WallFollower(Hand::Right)),
+            "links" | "left" => Some(Walker::WallFollower(Hand::Left)),
+            "pledge" | "pledge-rechts" => Some(Walker::Pledge(Hand::Right)),
+            "pledge-links" => Some(Walker::Pledge(Hand::Left)),
+            "tremaux" | "trémaux" => Some(Walker::Tremaux),
+            "sackgassen" | "dead-end" => Some(Walker::DeadEndFilling),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Walker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Walker::WallFollower(Hand::Right) => "Rechte-Hand-Regel",
+                Walker::WallFollower(Hand::Left) => "Linke-Hand-Regel",
+                Walker::Pledge(Hand::Right) => "Pledge mit der rechten Hand",
+                Walker::Pledge(Hand::Left) => "Pledge mit der linken Hand",
+                Walker::Tremaux => "Trémaux",
+                Walker::DeadEndFilling => "Sackgassen füllen",
+            }
+        )
+    }
+}
+
+pub struct Walk {
+    pub walker: Walker,
+    /**
+     * Every tile walked over from the entry to the exit, tiles walked more
+     * than once appear more than once
+     */
+    pub path: Vec<[u32; 2]>,
+    /**
+     * The summed up step costs of the walked path
+     */
+    pub length: isize,
+    /**
+     * The tiles filled by dead-end filling in the order they were filled,
+     * empty for every other walker
+     */
+    pub filled: Vec<[u32; 2]>,
+}
+
+/**
+ * A walker that keeps one hand on the wall. It turns as far as possible to
+ * the side of its hand and takes a step, turning back only if there is no
+ * other way.
+ */
+pub(super) struct WallFollower<'a> {
+    maze: &'a Maze,
+    hand: Hand,
+    /**
+     * The index of the step the walker faces, see Maze::get_steps
+     */
+    heading: usize,
+    /**
+     * Every tile and heading the walker has been at, being there again means
+     * it walks in a circle
+     */
+    followed: HashSet<([u32; 2], usize)>,
+}
+
+impl<'a> WallFollower<'a> {
+    /**
+     * Creates a walker at the entry that faces the first step leading away
+     * from it.
+     */
+    pub(super) fn new(maze: &'a Maze, hand: Hand) -> WallFollower<'a> {
+        WallFollower {
+            maze,
+            hand,
+            heading: maze.get_entry_heading(),
+            followed: HashSet::new(),
+        }
+    }
+
+    /**
+     * # Returns
+     * The step taken from the tile and the tile it leads to, NoExitFound if
+     * the walker is stuck or was at the tile facing the same direction before
+     */
+    pub(super) fn get_next_step(&mut self, tile: [u32; 2]) -> Result<Move, MazeError> {
+        if !self.followed.insert((tile, self.heading)) {
+            return Err(MazeError::NoExitFound);
+        }
+        let (heading, step, next) = self
+            .maze
+            .get_hand_step(tile, self.heading, self.hand)
+            .ok_or(MazeError::NoExitFound)?;
+        self.heading = heading;
+        Ok((step, next))
+    }
+}
+
+/**
+ * A walker that keeps going in its main direction until it hits a wall, then
+ * follows the wall with one hand and counts its turns. It leaves the wall as
+ * soon as the turns add up to zero, so it cannot get caught circling an
+ * island like a wall follower.
+ */
+pub(super) struct Pledge<'a> {
+    maze: &'a Maze,
+    hand: Hand,
+    main_heading: usize,
+    heading: usize,
+    /**
+     * The summed up turns, one for every step index turned towards the hand
+     * and minus one for every step index turned away from it
+     */
+    turns: i64,
+    followed: HashSet<([u32; 2], usize, i64)>,
+}
+
+impl<'a> Pledge<'a> {
+    pub(super) fn new(maze: &'a Maze, hand: Hand) -> Pledge<'a> {
+        let heading = maze.get_entry_heading();
+        Pledge {
+            maze,
+            hand,
+            main_heading: heading,
+            heading,
+            turns: 0,
+            followed: HashSet::new(),
+        }
+    }
+
+    /**
+     * # Returns
+     * The step taken from the tile and the tile it leads to, NoExitFound if
+     * the walker is stuck, was at the tile facing the same direction with
+     * the same turns before or has turned more often than the maze has tiles
+     */
+    pub(super) fn get_next_step(&mut self, tile: [u32; 2]) -> Result<Move, MazeError> {
+        let steps = self.maze.get_steps();
+        let turn_limit = (steps.len() * self.maze.maze.len()) as i64;
+        if self.turns.abs() > turn_limit || !self.followed.insert((tile, self.heading, self.turns))
+        {
+            return Err(MazeError::NoExitFound);
+        }
+        let main_step = steps[self.main_heading];
+        if self.turns == 0 {
+            if let Some(next) = self.maze.get_unlocked_step(tile[0], tile[1], main_step) {
+                self.heading = self.main_heading;
+                return Ok((main_step, next));
+            }
+            // Turns away from the hand until the wall is at the side of the hand
+            for turn in 1..steps.len() as i64 {
+                let heading = turn_heading(self.main_heading, -turn, self.hand, steps.len());
+                if let Some(next) = self
+                    .maze
+                    .get_unlocked_step(tile[0], tile[1], steps[heading])
+                {
+                    self.heading = heading;
+                    self.turns = -turn;
+                    return Ok((steps[heading], next));
+                }
+            }
+            return Err(MazeError::NoExitFound);
+        }
+        let (heading, step, next) = self
+            .maze
+            .get_hand_step(tile, self.heading, self.hand)
+            .ok_or(MazeError::NoExitFound)?;
+        let turn = match self.hand {
+            Hand::Right => heading as i64 - self.heading as i64,
+            Hand::Left => self.heading as i64 - heading as i64,
+        };
+        // Turning back counts as turning away from the hand, like in get_hand_step
+        let quarter = steps.len() as i64 / 4;
+        self.turns += quarter - (quarter - turn).rem_euclid(steps.len() as i64);
+        self.heading = heading;
+        Ok((step, next))
+    }
+}
+
+/**
+ * A walker that marks every passage it walks. It takes the passage with the
+ * fewest marks, arriving over a new passage at a tile that was visited
+ * before turns it back.
+ */
+pub(super) struct Tremaux<'a> {
+    maze: &'a Maze,
+    /**
+     * How often every passage was walked, a passage is stored as the ordered
+     * pair of its tiles
+     */
+    marks: HashMap<([u32; 2], [u32; 2]), u8>,
+    visited: Vec<bool>,
+}
+
+impl<'a> Tremaux<'a> {
+    pub(super) fn new(maze: &'a Maze) -> Tremaux<'a> {
+        Tremaux {
+            maze,
+            marks: HashMap::new(),
+            visited: vec![false; maze.maze.len()],
+        }
+    }
+
+    /**
+     * # Returns
+     * The step taken from the tile reached from the previous tile and the
+     * tile it leads to, NoExitFound if every passage of the tile is marked
+     * twice
+     */
+    pub(super) fn get_next_step(
+        &mut self,
+        tile: [u32; 2],
+        previous: Option<[u32; 2]>,
+    ) -> Result<Move, MazeError> {
+        let passage = |from: [u32; 2], to: [u32; 2]| (from.min(to), from.max(to));
+        let index = (tile[1] * self.maze.size[0] + tile[0]) as usize;
+        let was_visited = self.visited[index];
+        self.visited[index] = true;
+        let moves = self.maze.get_unlocked_moves(tile[0], tile[1]);
+        let mut chosen = None;
+        if let Some(previous) = previous {
+            let is_new = self.marks.get(&passage(tile, previous)) == Some(&1);
+            if was_visited && is_new {
+                chosen = moves.iter().find(|&&(_, next)| next == previous).copied();
+            }
+        }
+        let (step, next) = chosen
+            .or_else(|| {
+                moves
+                    .iter()
+                    .map(|&(step, next)| {
+                        let marks = self.marks.get(&passage(tile, next)).copied();
+                        (marks.unwrap_or(0), Some(next) != previous, step, next)
+                    })
+                    .filter(|&(marks, _, _, _)| marks < 2)
+                    .min_by_key(|&(marks, is_forward, _, _)| (marks, is_forward))
+                    .map(|(_, _, step, next)| (step, next))
+            })
+            .ok_or(MazeError::NoExitFound)?;
+        *self.marks.entry(passage(tile, next)).or_insert(0) += 1;
+        Ok((step, next))
+    }
+}
+
+/**
+ * # Returns
+ * The index of the step reached by turning the given amount of step indices
+ * towards the hand, negative amounts turn away from it
+ */
+fn turn_heading(heading: usize, turn: i64, hand: Hand, step_amount: usize) -> usize {
+    let turn = match hand {
+        Hand::Right => turn,
+        Hand::Left => -turn,
+    };
+    (heading as i64 + turn).rem_euclid(step_amount as i64) as usize
+}
+
+impl Maze {
+    /**
+     * # Returns
+     * The index of the first step that leads away from the entry
+     */
+    fn get_entry_heading(&self) -> usize {
+        let steps = self.get_steps();
+        self.get_unlocked_moves(self.entry[0], self.entry[1])
+            .first()
+            .and_then(|(step, _)| steps.iter().position(|known| known == step))
+            .unwrap_or(0)
+    }
+
+    /**
+     * Tries to turn a quarter towards the hand first and then every step
+     * further away from it, turning back is tried last.
+     * # Returns
+     * The index of the step taken, the step and the tile it leads to, None if
+     * no step can be taken from the tile
+     */
+    fn get_hand_step(
+        &self,
+        tile: [u32; 2],
+        heading: usize,
+        hand: Hand,
+    ) -> Option<(usize, [i64; 2], [u32; 2])> {
+        let steps = self.get_steps();
+        let quarter = (steps.len() / 4) as i64;
+        (0..steps.len() as i64).find_map(|turn| {
+            let next_heading = turn_heading(heading, quarter - turn, hand, steps.len());
+            let next = self.get_unlocked_step(tile[0], tile[1], steps[next_heading])?;
+            Some((next_heading, steps[next_heading], next))
+        })
+    }
+
+    /**
+     * Walks the maze with the given walker from the entry until it stands on
+     * an exit. Portals are ignored and doors stay locked.
+     * # Returns
+     * NoExitFound if the walker gives up, wall followers give up when they
+     * walk in a circle, Pledge when it circles with the same turns and
+     * Trémaux when every passage is marked twice
+     */
+    pub fn walk(&self, walker: Walker) -> Result<Walk, MazeError> {
+        match walker {
+            Walker::WallFollower(hand) => {
+                let mut wall_follower = WallFollower::new(self, hand);
+                self.walk_steps(walker, &mut |tile, _| wall_follower.get_next_step(tile))
+            }
+            Walker::Pledge(hand) => {
+                let mut pledge = Pledge::new(self, hand);
+                self.walk_steps(walker, &mut |tile, _| pledge.get_next_step(tile))
+            }
+            Walker::Tremaux => {
+                let mut tremaux = Tremaux::new(self);
+                self.walk_steps(walker, &mut |tile, previous| {
+                    tremaux.get_next_step(tile, previous)
+                })
+            }
+            Walker::DeadEndFilling => self.fill_dead_ends(),
+        }
+    }
+
+    /**
+     * Takes the steps chosen from the current and the previous tile until an
+     * exit is reached.
+     */
+    fn walk_steps(
+        &self,
+        walker: Walker,
+        next_step: &mut dyn FnMut([u32; 2], Option<[u32; 2]>) -> Result<Move, MazeError>,
+    ) -> Result<Walk, MazeError> {
+        let mut walk = Walk {
+            walker,
+            path: vec![self.entry],
+            length: 0,
+            filled: Vec::new(),
+        };
+        loop {
+            let tile = *walk.path.last().unwrap();
+            if self.is_exit_tile(tile[0], tile[1]) {
+                return Ok(walk);
+            }
+            let previous = walk.path.len().checked_sub(2).map(|index| walk.path[index]);
+            let (step, next) = next_step(tile, previous)?;
+            walk.length += self.get_move_cost(tile[0], tile[1], step);
+            walk.path.push(next);
+        }
+    }
+
+    /**
+     * Fills every walkable tile that has at most one unfilled neighbor and is
+     * neither the entry nor an exit, until there is no such tile left. Only
+     * the ways between the entry and the exits and the loops along them stay
+     * unfilled, the walk is the way with the fewest steps through them. Doors
+     * are filled like walls.
+     * # Returns
+     * NoExitFound if no exit stays connected to the entry
+     */
+    fn fill_dead_ends(&self) -> Result<Walk, MazeError> {
+        let index = |[x, y]: [u32; 2]| (y * self.size[0] + x) as usize;
+        let get_neighbors = |[x, y]: [u32; 2]| -> Vec<[u32; 2]> {
+            let mut neighbors: Vec<[u32; 2]> = self
+                .get_unlocked_moves(x, y)
+                .into_iter()
+                .map(|(_, next)| next)
+                .chain(self.get_predecessors(x, y))
+                .filter(|&neighbor| neighbor != [x, y] && !self.is_door(neighbor))
+                .collect();
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            neighbors
+        };
+        let is_kept = |[x, y]: [u32; 2]| [x, y] == self.entry || self.is_exit_tile(x, y);
+        let mut degrees = vec![0; self.maze.len()];
+        let mut is_filled = vec![false; self.maze.len()];
+        let mut queue: VecDeque<[u32; 2]> = VecDeque::new();
+        for y in 0..self.size[1] {
+            for x in 0..self.size[0] {
+                if self.maze[index([x, y])] || self.is_door([x, y]) {
+                    continue;
+                }
+                degrees[index([x, y])] = get_neighbors([x, y]).len();
+                if degrees[index([x, y])] <= 1 && !is_kept([x, y]) {
+                    is_filled[index([x, y])] = true;
+                    queue.push_back([x, y]);
+                }
+            }
+        }
+        let mut filled: Vec<[u32; 2]> = Vec::new();
+        while let Some(tile) = queue.pop_front() {
+            filled.push(tile);
+            for neighbor in get_neighbors(tile) {
+                degrees[index(neighbor)] -= 1;
+                if degrees[index(neighbor)] <= 1
+                    && !is_filled[index(neighbor)]
+                    && !is_kept(neighbor)
+                {
+                    is_filled[index(neighbor)] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        let mut parents: Vec<Option<([i64; 2], [u32; 2])>> = vec![None; self.maze.len()];
+        let mut visited = vec![false; self.maze.len()];
+        let mut bfs_queue: VecDeque<[u32; 2]> = VecDeque::from(vec![self.entry]);
+        visited[index(self.entry)] = true;
+        while let Some(tile) = bfs_queue.pop_front() {
+            if self.is_exit_tile(tile[0], tile[1]) {
+                let mut steps: Vec<([i64; 2], [u32; 2])> = Vec::new();
+                let mut current = tile;
+                while let Some((step, parent)) = parents[index(current)] {
+                    steps.push((step, parent));
+                    current = parent;
+                }
+                steps.reverse();
+                let mut path: Vec<[u32; 2]> = steps.iter().map(|&(_, parent)| parent).collect();
+                path.push(tile);
+                return Ok(Walk {
+                    walker: Walker::DeadEndFilling,
+                    length: steps
+                        .iter()
+                        .map(|&(step, [x, y])| self.get_move_cost(x, y, step))
+                        .sum(),
+                    path,
+                    filled,
+                });
+            }
+            for (step, next) in self.get_unlocked_moves(tile[0], tile[1]) {
+                if !is_filled[index(next)] && !visited[index(next)] {
+                    visited[index(next)] = true;
+                    parents[index(next)] = Some((step, tile));
+                    bfs_queue.push_back(next);
+                }
+            }
+        }
+        Err(MazeError::NoExitFound)
+    }
+
+    fn get_walk_image(&self, walk: &Walk, filled: usize, walked: usize) -> RgbImage {
+        let mut image = self.get_maze_image();
+        for &[x, y] in &walk.filled[..filled] {
+            image.put_pixel(x, y, FILLED_COLOR);
+        }
+        for &[x, y] in &walk.path[..walked] {
+            image.put_pixel(x, y, WALKED_COLOR);
+        }
+        if let Some(&[x, y]) = walk.path[..walked].last() {
+            image.put_pixel(x, y, POSITION_COLOR);
+        }
+        image
+    }
+
+    /**
+     * Draws the filled dead ends in gray and the walked tiles in red.
+     */
+    pub fn export_walk_png(&self, path: String, walk: &Walk) {
+        let image = self.get_walk_image(walk, walk.filled.len(), walk.path.len());
+        image.save(&path).unwrap();
+        println!("Bild wurde in {} gespeichert.", path);
+    }
+
+    /**
+     * Writes the walk as an animated GIF. A frame is taken every `every`
+     * filled dead-end tiles and then every `every` steps, the walker is drawn
+     * in blue. Every tile is drawn as a square of `scale` pixels.
+     */
+    pub fn export_walk_gif(&self, path: String, walk: &Walk, every: usize, scale: u32) {
+        let every = every.max(1);
+        let mut frames: Vec<Frame> = Vec::new();
+        for filled in (every..=walk.filled.len()).step_by(every) {
+            let image = self.get_walk_image(walk, filled, 0);
+            frames.push(self.get_frame(&image, scale, 100));
+        }
+        for walked in (1..=walk.path.len()).step_by(every) {
+            let image = self.get_walk_image(walk, walk.filled.len(), walked);
+            frames.push(self.get_frame(&image, scale, 100));
+        }
+        let image = self.get_walk_image(walk, walk.filled.len(), walk.path.len());
+        frames.push(self.get_frame(&image, scale, 3000));
+        let mut encoder = GifEncoder::new(File::create(&path).unwrap());
+        encoder.set_repeat(Repeat::Infinite).unwrap();
+        encoder.encode_frames(frames).unwrap();
+        println!("Animation wurde in {} gespeichert.", path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hand, Walker};
+    use crate::maze::fixtures::{get_exit_distance, get_maze, get_random_maze, CONNECTIVITIES};
+    use crate::maze::{Connectivity, MazeError, MazeOptions};
+
+    /**
+     * The corridor from the entry ends in a junction with an exit on either
+     * side
+     */
+    const JUNCTION: [&str; 4] = ["##.##", "##.##", ".....", "#####"];
+
+    #[test]
+    fn walkers_are_found_by_their_names() {
+        for walker in Walker::ALL {
+            let name = match walker {
+                Walker::WallFollower(Hand::Right) => "rechts",
+                Walker::WallFollower(Hand::Left) => "left",
+                Walker::Pledge(Hand::Right) => "pledge",
+                Walker::Pledge(Hand::Left) => "pledge-links",
+                Walker::Tremaux => "Trémaux",
+                Walker::DeadEndFilling => "dead-end",
+            };
+            assert!(Walker::from_name(name) == Some(walker));
+        }
+        assert!(Walker::from_name("geradeaus").is_none());
+    }
+
+    #[test]
+    fn the_hand_decides_the_side_at_a_junction() {
+        let maze = get_maze(&JUNCTION, [2, 0], MazeOptions::default());
+        let right = maze.walk(Walker::WallFollower(Hand::Right)).unwrap();
+        assert_eq!(right.path, vec![[2, 0], [2, 1], [2, 2], [1, 2], [0, 2]]);
+        let left = maze.walk(Walker::WallFollower(Hand::Left)).unwrap();
+        assert_eq!(left.path, vec![[2, 0], [2, 1], [2, 2], [3, 2], [4, 2]]);
+        assert_eq!(left.length, 4);
+    }
+
+    #[test]
+    fn walkers_give_up_in_closed_mazes() {
+        let maze = get_maze(
+            &["#.###", "#...#", "#.#.#", "#...#", "#####"],
+            [1, 0],
+            MazeOptions::default(),
+        );
+        for walker in Walker::ALL {
+            assert!(matches!(maze.walk(walker), Err(MazeError::NoExitFound)));
+        }
+    }
+
+    #[test]
+    fn dead_end_filling_keeps_only_the_ways_to_the_exits() {
+        let maze = get_maze(
+            &["#.###", "#...#", "#.#.#", "#.###", "#.###"],
+            [1, 0],
+            MazeOptions::default(),
+        );
+        let walk = maze.walk(Walker::DeadEndFilling).unwrap();
+        assert_eq!(walk.filled, vec![[3, 2], [3, 1], [2, 1]]);
+        assert_eq!(walk.path, (0..5).map(|y| [1, y]).collect::<Vec<[u32; 2]>>());
+    }
+
+    #[test]
+    fn walkers_walk_valid_paths_to_an_exit() {
+        for connectivity in CONNECTIVITIES {
+            for seed in 1..=20 {
+                let options = MazeOptions {
+                    connectivity,
+                    ..MazeOptions::default()
+                };
+                let maze = get_random_maze([18, 13], seed, options);
+                let expected = get_exit_distance(&maze);
+                for walker in Walker::ALL {
+                    let walk = match maze.walk(walker) {
+                        Ok(walk) => walk,
+                        Err(_) => {
+                            // Pledge may circle back to the entry, the only
+                            // opening it is sure to find
+                            let is_pledge = matches!(walker, Walker::Pledge(_));
+                            assert!(is_pledge || expected.is_none(), "{} seed {}", walker, seed);
+                            continue;
+                        }
+                    };
+                    let validation = maze.validate_path(&walk.path).unwrap();
+                    assert_eq!(validation.length, walk.length);
+                    assert!(expected.is_some_and(|shortest| shortest <= walk.length));
+                    if walker == Walker::DeadEndFilling {
+                        assert!(walk.path.iter().all(|tile| !walk.filled.contains(tile)));
+                        if connectivity == Connectivity::Four {
+                            assert_eq!(Some(walk.length), expected, "seed {}", seed);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use super::{Maze, MazeError};
+use img::Rgb;
+use std::collections::{HashSet, VecDeque};
+
+/**
+ * The walls that have to be knocked through
+ */
+const BREAK_COLOR: Rgb<u8> = Rgb([255, 165, 0]);
+
+pub struct WallBreakRoute {
+    /**
+     * Every tile walked from the entry to the exit, the broken walls included
+     */
+    pub path: Vec<[u32; 2]>,
+    /**
+     * The walls that have to be removed, in the order in which they are passed
+     */
+    pub walls: Vec<[u32; 2]>,
+    /**
+     * The summed up cost of breaking the walls
+     */
+    pub cost: isize,
+    pub expanded: usize,
+}
+
+impl Maze {
+    /**
+     * Searches the tile grid for the exit that can be reached by breaking
+     * the fewest walls. Runs a 0-1 BFS in which stepping onto a wall costs the
+     * wall cost and stepping onto a walkable tile is free, terrain, keys and
+     * portals are ignored. The path is then walked again with a BFS through
+     * the broken walls, so it takes the fewest steps instead of a detour.
+     * # Returns
+     * InvalidWallCost if the wall cost is below 1 and NoExitFound if no exit
+     * can be reached without breaking more than the given amount of walls
+     */
+    pub fn solve_wall_breaks(
+        &self,
+        wall_cost: isize,
+        max_breaks: Option<usize>,
+    ) -> Result<WallBreakRoute, MazeError> {
+        if wall_cost < 1 {
+            return Err(MazeError::InvalidWallCost);
+        }
+        let index = |[x, y]: [u32; 2]| (y * self.size[0] + x) as usize;
+        let mut is_exit = vec![false; self.maze.len()];
+        for exit in self.get_grid_exits() {
+            is_exit[index(exit)] = true;
+        }
+        let mut costs: Vec<Option<isize>> = vec![None; self.maze.len()];
+        let mut parents: Vec<Option<[u32; 2]>> = vec![None; self.maze.len()];
+        let mut closed = vec![false; self.maze.len()];
+        let mut deque: VecDeque<(isize, [u32; 2])> = VecDeque::new();
+        let mut expanded = 0;
+        costs[index(self.entry)] = Some(0);
+        deque.push_back((0, self.entry));
+        while let Some((cost, tile)) = deque.pop_front() {
+            if closed[index(tile)] || costs[index(tile)] != Some(cost) {
+                continue;
+            }
+            closed[index(tile)] = true;
+            expanded += 1;
+            if is_exit[index(tile)] {
+                let mut walls: HashSet<[u32; 2]> = HashSet::new();
+                let mut current = tile;
+                while let Some(parent) = parents[index(current)] {
+                    if self.maze[index(current)] {
+                        walls.insert(current);
+                    }
+                    current = parent;
+                }
+                let path = self.get_fewest_steps(tile, &walls);
+                return Ok(WallBreakRoute {
+                    walls: path
+                        .iter()
+                        .skip(1)
+                        .filter(|&&tile| self.maze[index(tile)])
+                        .copied()
+                        .collect(),
+                    path,
+                    cost,
+                    expanded,
+                });
+            }
+            for step in self.get_steps() {
+                let next = match self.get_break_step(tile, step) {
+                    Some(next) => next,
+                    None => continue,
+                };
+                let is_wall = self.maze[index(next)];
+                let next_cost = if is_wall { cost + wall_cost } else { cost };
+                if max_breaks.is_some_and(|max| (next_cost / wall_cost) as usize > max) {
+                    continue;
+                }
+                if costs[index(next)].is_none_or(|known| next_cost < known) {
+                    costs[index(next)] = Some(next_cost);
+                    parents[index(next)] = Some(tile);
+                    if is_wall {
+                        deque.push_back((next_cost, next));
+                    } else {
+                        deque.push_front((next_cost, next));
+                    }
+                }
+            }
+        }
+        Err(MazeError::NoExitFound)
+    }
+
+    /**
+     * # Returns
+     * The tile reached by the step if walls could be walked through, corners
+     * and one-way tiles are respected and doors cannot be broken
+     */
+    fn get_break_step(&self, [x, y]: [u32; 2], step: [i64; 2]) -> Option<[u32; 2]> {
+        let next = self.offset(x, y, step)?;
+        if !self.can_leave([x, y], step) || self.cuts_corner([x, y], next) || self.is_door(next) {
+            return None;
+        }
+        Some(next)
+    }
+
+    /**
+     * Runs a BFS from the entry to the exit that may only pass the given
+     * walls.
+     * # Returns
+     * Every tile of the path with the fewest steps
+     */
+    fn get_fewest_steps(&self, exit: [u32; 2], walls: &HashSet<[u32; 2]>) -> Vec<[u32; 2]> {
+        let index = |[x, y]: [u32; 2]| (y * self.size[0] + x) as usize;
+        let mut parents: Vec<Option<[u32; 2]>> = vec![None; self.maze.len()];
+        let mut visited = vec![false; self.maze.len()];
+        let mut queue: VecDeque<[u32; 2]> = VecDeque::new();
+        visited[index(self.entry)] = true;
+        queue.push_back(self.entry);
+        while let Some(tile) = queue.pop_front() {
+            if tile == exit {
+                break;
+            }
+            for step in self.get_steps() {
+                if let Some(next) = self.get_break_step(tile, step) {
+                    let is_open = !self.maze[index(next)] || walls.contains(&next);
+                    if is_open && !visited[index(next)] {
+                        visited[index(next)] = true;
+                        parents[index(next)] = Some(tile);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        let mut path = vec![exit];
+        while let Some(parent) = parents[index(*path.last().unwrap())] {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+
+    /**
+     * Draws the path in red and the walls that have to be broken in orange.
+     */
+    pub fn export_wall_breaks_png(&self, path: String, route: &WallBreakRoute) {
+        let mut image = self.get_maze_image();
+        for &[x, y] in &route.path {
+            image.put_pixel(x, y, Rgb([255, 0, 0]));
+        }
+        for &[x, y] in &route.walls {
+            image.put_pixel(x, y, BREAK_COLOR);
+        }
+        image.save(&path).unwrap();
+        println!("Bild wurde in {} gespeichert.", path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::fixtures::{get_exit_distance, get_maze, get_random_rows};
+    use crate::maze::{MazeError, MazeOptions};
+
+    /**
+     * A single wall separates the entry from the exit
+     */
+    const SPLIT: [&str; 5] = ["#.###", "#.###", "#####", "#.###", "#.###"];
+
+    #[test]
+    fn walls_in_the_way_are_broken() {
+        let maze = get_maze(&SPLIT, [1, 0], MazeOptions::default());
+        let route = maze.solve_wall_breaks(3, Some(1)).unwrap();
+        assert_eq!(route.walls, vec![[1, 2]]);
+        assert_eq!(route.cost, 3);
+        assert_eq!(
+            route.path,
+            (0..5).map(|y| [1, y]).collect::<Vec<[u32; 2]>>()
+        );
+        assert!(matches!(
+            maze.solve_wall_breaks(3, Some(0)),
+            Err(MazeError::NoExitFound)
+        ));
+        assert!(matches!(
+            maze.solve_wall_breaks(0, None),
+            Err(MazeError::InvalidWallCost)
+        ));
+    }
+
+    #[test]
+    fn the_fewest_walls_are_broken() {
+        for seed in 1..=40 {
+            let rows = get_random_rows([16, 12], seed);
+            let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+            let maze = get_maze(&rows, [3, 0], MazeOptions::default());
+            let route = maze.solve_wall_breaks(2, None).unwrap();
+            assert_eq!(route.cost, 2 * route.walls.len() as isize);
+            assert_eq!(route.walls.is_empty(), get_exit_distance(&maze).is_some());
+            if !route.walls.is_empty() {
+                let fewer = Some(route.walls.len() - 1);
+                assert!(maze.solve_wall_breaks(2, fewer).is_err(), "seed {}", seed);
+            }
+            let mut broken: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+            for &[x, y] in &route.walls {
+                broken[y as usize][x as usize] = '.';
+            }
+            let broken: Vec<String> = broken
+                .into_iter()
+                .map(|row| row.into_iter().collect())
+                .collect();
+            let broken: Vec<&str> = broken.iter().map(String::as_str).collect();
+            let opened = get_maze(&broken, [3, 0], MazeOptions::default());
+            let validation = opened.validate_path(&route.path).unwrap();
+            assert_eq!(validation.length, route.path.len() as isize - 1);
+        }
+    }
+}